@@ -0,0 +1,264 @@
+use anyhow::{anyhow, Context, Result};
+
+use clap::ValueEnum;
+
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+use crate::normalize::{RINK_LENGTH_FT, RINK_WIDTH_FT};
+use crate::reader::OutputReader;
+use crate::render::{
+    draw_frame, draw_shot, draw_trail, read_normalized_goal, rink_svg, svg_document, team_colors, RenderOptions,
+};
+use crate::resample::{resample_frames, Interpolation, ResampleOptions};
+use crate::shot::detect_shot;
+
+pub const DEFAULT_FPS: f64 = 10.0;
+pub const DEFAULT_WIDTH: u32 = 800;
+pub const DEFAULT_TRAIL_SECONDS: f64 = 2.0;
+
+/// File format of an animation
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum AnimationFormat {
+    Gif,
+    /// animated PNG: bigger files than GIF, but without GIF's 256 colors
+    Apng,
+}
+
+impl AnimationFormat {
+    /// Works out the format from a file's extension: .gif, or .png/.apng
+    pub fn from_path<P>(path: P) -> Option<AnimationFormat>
+    where
+        P: AsRef<Path>,
+    {
+        let extension = path.as_ref().extension()?.to_str()?.to_ascii_lowercase();
+        match extension.as_str() {
+            "gif" => Some(AnimationFormat::Gif),
+            "png" | "apng" => Some(AnimationFormat::Apng),
+            _ => None,
+        }
+    }
+}
+
+/// Settings for animating a goal
+#[derive(Debug, Clone, Copy)]
+pub struct AnimateOptions {
+    pub format: AnimationFormat,
+    /// frames per second; the tracking data gets resampled to this rate
+    pub fps: f64,
+    /// width of the animation in pixels; the height follows from the rink
+    pub width: u32,
+    /// how many seconds of the puck's path to draw behind it; 0 turns the
+    /// trail off
+    pub trail_seconds: f64,
+}
+
+/// Animates a goal to a GIF or APNG file
+/// Like `render`, the rink is drawn with the scoring team attacking the net
+/// on the right.  The shot line shows up once the puck's been released.
+pub fn animate<P>(reader: &OutputReader, game_id: u32, event_id: u32, options: &AnimateOptions, path: P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let goal = read_normalized_goal(reader, game_id, event_id)?;
+    // the shot is found on the original frames, as resampling smooths out
+    // the jump in puck speed
+    let shot = detect_shot(&goal.frames, goal.goal.scoring_team_id);
+    let release_seconds = shot
+        .as_ref()
+        .map(|s| goal.frames[s.release_frame].seconds_since(&goal.frames[0]));
+    let frames = resample_frames(&goal.frames, &ResampleOptions::new(options.fps, Interpolation::Linear))?;
+    if frames.is_empty() {
+        return Err(anyhow!("No frames for goal {} in game {}", event_id, game_id));
+    }
+    let colors = team_colors(&frames, goal.home_team_id);
+
+    let width = options.width.max(1);
+    let height = ((width as f64) * RINK_WIDTH_FT / RINK_LENGTH_FT).round().max(1.0) as u32;
+    let render_options = RenderOptions {
+        scale: width as f64 / RINK_LENGTH_FT,
+    };
+    let trail_frames = (options.trail_seconds * options.fps).round().max(0.0) as usize;
+    let title = format!("Game {}, goal {}", game_id, event_id);
+
+    let mut svg_options = resvg::usvg::Options::default();
+    svg_options.fontdb_mut().load_system_fonts();
+
+    // each frame is written as soon as it's drawn, so a long clip doesn't
+    // keep every frame's pixels in memory
+    let path = path.as_ref();
+    let file = File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut encoder = AnimationEncoder::new(options.format, BufWriter::new(file), width, height, options.fps, frames.len())?;
+    for (i, frame) in frames.iter().enumerate() {
+        let mut body = rink_svg();
+        if trail_frames > 0 {
+            draw_trail(&mut body, &frames[i.saturating_sub(trail_frames)..=i]);
+        }
+        if let (Some(shot), Some(release_seconds)) = (&shot, release_seconds) {
+            if frame.seconds_since(&frames[0]) >= release_seconds {
+                draw_shot(&mut body, &goal.frames, shot);
+            }
+        }
+        draw_frame(&mut body, frame, &colors);
+        let svg = svg_document(&body, &title, &render_options);
+        encoder.write_frame(&mut rasterize(&svg, width, height, &svg_options)?)?;
+    }
+    encoder.finish()
+}
+
+/// Draws an SVG onto a white background, as RGBA pixels
+fn rasterize(svg: &str, width: u32, height: u32, svg_options: &resvg::usvg::Options) -> Result<Vec<u8>> {
+    let tree = resvg::usvg::Tree::from_str(svg, svg_options)?;
+    let mut pixmap = resvg::tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| anyhow!("Invalid animation size: {}x{}", width, height))?;
+    pixmap.fill(resvg::tiny_skia::Color::WHITE);
+    let scale = width as f32 / tree.size().width();
+    resvg::render(&tree, resvg::tiny_skia::Transform::from_scale(scale, scale), &mut pixmap.as_mut());
+    // the background is opaque, so the premultiplied pixels are the same as
+    // plain RGBA
+    Ok(pixmap.take())
+}
+
+/// Writes the frames of an animation one at a time
+enum AnimationEncoder<W: std::io::Write> {
+    Gif {
+        encoder: gif::Encoder<W>,
+        width: u16,
+        height: u16,
+        /// in hundredths of a second
+        delay: u16,
+    },
+    Apng(png::Writer<W>),
+}
+
+impl<W: std::io::Write> AnimationEncoder<W> {
+    /// Starts an animation with `num_frames` frames of RGBA pixels
+    fn new(
+        format: AnimationFormat,
+        writer: W,
+        width: u32,
+        height: u32,
+        fps: f64,
+        num_frames: usize,
+    ) -> Result<AnimationEncoder<W>> {
+        match format {
+            AnimationFormat::Gif => {
+                let (width, height) = (
+                    u16::try_from(width).context("Animation too wide for a GIF")?,
+                    u16::try_from(height).context("Animation too tall for a GIF")?,
+                );
+                let mut encoder = gif::Encoder::new(writer, width, height, &[])?;
+                encoder.set_repeat(gif::Repeat::Infinite)?;
+                let delay = (100.0 / fps).round().max(1.0) as u16;
+                Ok(AnimationEncoder::Gif { encoder, width, height, delay })
+            }
+            AnimationFormat::Apng => {
+                let mut encoder = png::Encoder::new(writer, width, height);
+                encoder.set_color(png::ColorType::Rgba);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder.set_animated(num_frames as u32, 0)?;
+                let (numerator, denominator) = apng_frame_delay(fps);
+                encoder.set_frame_delay(numerator, denominator)?;
+                Ok(AnimationEncoder::Apng(encoder.write_header()?))
+            }
+        }
+    }
+
+    fn write_frame(&mut self, image: &mut [u8]) -> Result<()> {
+        match self {
+            AnimationEncoder::Gif { encoder, width, height, delay } => {
+                let mut frame = gif::Frame::from_rgba_speed(*width, *height, image, 10);
+                frame.delay = *delay;
+                encoder.write_frame(&frame)?;
+            }
+            AnimationEncoder::Apng(writer) => writer.write_image_data(image)?,
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            AnimationEncoder::Gif { encoder, .. } => encoder.into_inner()?.flush()?,
+            AnimationEncoder::Apng(writer) => writer.finish()?,
+        }
+        Ok(())
+    }
+}
+
+/// The delay between APNG frames, as a fraction of a second that fits in
+/// u16s: exact for frame rates with up to three decimal places, like 29.97,
+/// and as close as u16s allow for the rest
+fn apng_frame_delay(fps: f64) -> (u16, u16) {
+    let max = u16::MAX as f64;
+    for scale in [1.0, 10.0, 100.0, 1000.0] {
+        let scaled_fps = fps * scale;
+        if (scaled_fps - scaled_fps.round()).abs() < 1e-9 && (1.0..=max).contains(&scaled_fps.round()) {
+            let (numerator, denominator) = (scale as u64, scaled_fps.round() as u64);
+            let divisor = gcd(numerator, denominator);
+            return ((numerator / divisor) as u16, (denominator / divisor) as u16);
+        }
+    }
+    if fps >= 1.0 {
+        ((max / fps).round().max(1.0) as u16, u16::MAX)
+    } else {
+        (u16::MAX, (max * fps).round().max(1.0) as u16)
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 { a } else { gcd(b, a % b) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn animation_format_from_path() {
+        assert_eq!(AnimationFormat::from_path("goal.gif"), Some(AnimationFormat::Gif));
+        assert_eq!(AnimationFormat::from_path("goal.PNG"), Some(AnimationFormat::Apng));
+        assert_eq!(AnimationFormat::from_path("goal.apng"), Some(AnimationFormat::Apng));
+        assert_eq!(AnimationFormat::from_path("goal.mp4"), None);
+        assert_eq!(AnimationFormat::from_path("goal"), None);
+    }
+
+    #[test]
+    fn apng_frame_delay_fractions() {
+        assert_eq!(apng_frame_delay(10.0), (1, 10));
+        assert_eq!(apng_frame_delay(120.0), (1, 120));
+        assert_eq!(apng_frame_delay(29.97), (100, 2997));
+        assert_eq!(apng_frame_delay(0.5), (2, 1));
+        // a third of a frame per second can't be written exactly
+        assert_eq!(apng_frame_delay(1.0 / 3.0), (u16::MAX, 21845));
+    }
+
+    // two frames of a red square, written out and read back
+    #[test]
+    fn write_gif_and_apng() {
+        let encode = |format| {
+            let mut bytes = vec![];
+            let mut encoder = AnimationEncoder::new(format, &mut bytes, 4, 2, 10.0, 2).unwrap();
+            for _ in 0..2 {
+                encoder.write_frame(&mut [255, 0, 0, 255].repeat(4 * 2)).unwrap();
+            }
+            encoder.finish().unwrap();
+            bytes
+        };
+
+        let gif_bytes = encode(AnimationFormat::Gif);
+        let mut decoder = gif::DecodeOptions::new().read_info(gif_bytes.as_slice()).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (4, 2));
+        let mut num_frames = 0;
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            assert_eq!(frame.delay, 10);
+            num_frames += 1;
+        }
+        assert_eq!(num_frames, 2);
+
+        let png_bytes = encode(AnimationFormat::Apng);
+        let reader = png::Decoder::new(png_bytes.as_slice()).read_info().unwrap();
+        let animation = reader.info().animation_control().unwrap();
+        assert_eq!(animation.num_frames, 2);
+    }
+}
@@ -1,3 +1,6 @@
+// field names mirror the JSON returned by the NHL API
+#![allow(non_snake_case)]
+
 use chrono::NaiveDate;
 
 use reqwest;
@@ -13,9 +16,63 @@ use std::path::Path;
 use anyhow::{anyhow, Context, Result};
 
 use crate::api_calls::week_or_shorter_period::WeekOrShorterPeriod;
+use crate::schema::{record_response, Endpoint};
+use crate::tracking::parse_tracking;
+
+/// Where the NHL's game and schedule endpoints live
+pub const API_BASE_URL: &str = "https://api-web.nhle.com";
+
+/// Where the NHL's tracking data (sprites) lives
+pub const SPRITES_BASE_URL: &str = "https://wsr.nhle.com";
+
+/// Base URL for the game and schedule endpoints
+/// Setting `NHL_API_BASE_URL` points the downloader somewhere else, like a
+/// mirror of downloaded data (see `mirror`).
+pub fn api_base_url() -> String {
+    std::env::var("NHL_API_BASE_URL").unwrap_or_else(|_| String::from(API_BASE_URL))
+}
+
+/// Base URL for the tracking data, which `NHL_SPRITES_BASE_URL` overrides
+/// Only used for goals without a replay URL of their own.
+pub fn sprites_base_url() -> String {
+    std::env::var("NHL_SPRITES_BASE_URL").unwrap_or_else(|_| String::from(SPRITES_BASE_URL))
+}
+
+/// Tracking data shorter than this is empty: the NHL sometimes answers with
+/// nothing (or next to nothing) for goals it doesn't have tracking for yet
+pub const EMPTY_TRACKING_LEN: usize = 10;
+
+/// What came of downloading a goal's tracking data
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackingOutcome {
+    Saved,
+    /// empty or next to empty, as is common for a few hours after a game
+    Empty,
+    /// not tracking data the parser can read, e.g. cut off partway through
+    Invalid(String),
+}
+
+impl TrackingOutcome {
+    pub fn is_saved(&self) -> bool {
+        *self == TrackingOutcome::Saved
+    }
+}
+
+/// Checks tracking data before it's saved: `Saved` if it's good to save
+pub fn check_tracking(tracking: &str) -> TrackingOutcome {
+    if tracking.trim().len() < EMPTY_TRACKING_LEN {
+        return TrackingOutcome::Empty;
+    }
+    match parse_tracking(tracking) {
+        Ok(_) => TrackingOutcome::Saved,
+        Err(e) => TrackingOutcome::Invalid(e.to_string()),
+    }
+}
 
 /// Saves the tracking data for a goal to a file
-/// This requires headers to get the data from the NHL site.
+/// This requires headers to get the data from the NHL site.  Empty or invalid
+/// tracking data isn't saved, so a bad response never looks like a download;
+/// the outcome says what was wrong with it.
 pub fn save_goal_data<P>(
     client: &Client,
     headers: HeaderMap,
@@ -23,28 +80,38 @@ pub fn save_goal_data<P>(
     game_id: u32,
     goal: &GoalDetails,
     output_path: P,
-) -> Result<()>
+) -> Result<TrackingOutcome>
 where
     P: AsRef<Path>,
 {
-    const LEN_THRESHOLD: usize = 10;
-
     // get the tracking data
     let api_url = match &goal.ppt_replay_url {
         Some(url) => url.to_string(),
         None => format!(
-            "https://wsr.nhle.com/sprites/{}/{}/ev{}.json",
-            season, game_id, goal.event_id
+            "{}/sprites/{}/{}/ev{}.json",
+            sprites_base_url(), season, game_id, goal.event_id
         ),
     };
     let resp = client.get(api_url).headers(headers).send()?;
     if resp.status() == 200 {
         let resp_text = resp.text()?;
+        if resp_text.trim().len() >= EMPTY_TRACKING_LEN {
+            record_response(Endpoint::Sprites, &format!("game {}, goal {}", game_id, goal.event_id), &resp_text);
+        }
 
-        // there are rare cases where the response is an empty string or have 
-        // no info, so we should print out a warning for that
-        if resp_text.len() < LEN_THRESHOLD {
-            println!("Empty response string for season: {}, game id: {}, goal id: {}", season, game_id, goal.event_id);
+        // there are cases where the response is an empty string, has no info
+        // or is cut off, so warn about those instead of saving them
+        let outcome = check_tracking(&resp_text);
+        match &outcome {
+            TrackingOutcome::Saved => (),
+            TrackingOutcome::Empty => {
+                println!("Empty response string for season: {}, game id: {}, goal id: {}", season, game_id, goal.event_id);
+                return Ok(outcome);
+            },
+            TrackingOutcome::Invalid(e) => {
+                println!("Invalid tracking data for season: {}, game id: {}, goal id: {}: {}", season, game_id, goal.event_id, e);
+                return Ok(outcome);
+            },
         }
         // save the data to a file
         let mut file = File::create(output_path).with_context(|| {
@@ -54,11 +121,11 @@ where
             )
         })?;
         write!(file, "{}", resp_text)?;
-        Ok(())
+        Ok(outcome)
     } else {
         let err_msg = format!(
-            "Unable to get data for season: {}, game id: {}, goal id: {}",
-            season, game_id, goal.event_id
+            "Unable to get data for season: {}, game id: {}, goal id: {} (status {})",
+            season, game_id, goal.event_id, resp.status()
         );
         Err(anyhow!(err_msg))
     }
@@ -90,6 +157,7 @@ pub struct Game {
     pub season: u32,            // need the season to get goal location info
     pub startTimeUTC: String,   // used for creating folders for games
     pub venueUTCOffset: String, // used for creating folders for games
+    pub gameState: Option<String>, // used to tell when a game's started or over
 }
 
 pub mod week_or_shorter_period {
@@ -114,7 +182,7 @@ pub mod week_or_shorter_period {
             // need to check that the end date is 6 days
             // or less after the start date
             let diff = (end_date - start_date).num_days();
-            if (diff <= VALID_NUM_DAYS_DIFF) && (diff >= 0) {
+            if (0..=VALID_NUM_DAYS_DIFF).contains(&diff) {
                 Ok(Self {
                     start_date,
                     end_date,
@@ -136,7 +204,7 @@ pub mod week_or_shorter_period {
 
     impl fmt::Display for WeekOrShorterPeriod {
         fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            write!(f, "{} to {}", self.start_date.format("%Y-%m-%d").to_string(), self.end_date.format("%Y-%m-%d").to_string())
+            write!(f, "{} to {}", self.start_date.format("%Y-%m-%d"), self.end_date.format("%Y-%m-%d"))
         }
     }
 }
@@ -149,12 +217,14 @@ pub fn get_game_ids_period(
 ) -> Result<Vec<Game>> {
     // let client = Client::new();
     let sched_url = format!(
-        "https://api-web.nhle.com/v1/schedule/{}",
+        "{}/v1/schedule/{}",
+        api_base_url(),
         week.get_start_date()
     );
     let mut games = vec![];
     let resp = client.get(sched_url).send()?;
     let text = resp.text()?;
+    record_response(Endpoint::Schedule, &format!("week of {}", week.get_start_date()), &text);
 
     // parse the response
     let sched_resp: ScheduleResponse = serde_json::from_str(&text)?;
@@ -162,7 +232,7 @@ pub fn get_game_ids_period(
     for game_day in &sched_resp.gameWeek {
         // check that the game day falls w/n the period
         let game_date = NaiveDate::parse_from_str(&game_day.date, "%Y-%m-%d")
-            .expect(&format!("Invalid date: {}", &game_day.date));
+            .unwrap_or_else(|_| panic!("Invalid date: {}", &game_day.date));
         if week.within(&game_date) {
             for g in &game_day.games {
                 games.push(g.clone())
@@ -174,52 +244,57 @@ pub fn get_game_ids_period(
 
 // structs to parse pbp info
 /// the response from the play-by-play endpoint
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct PbpResponse {
     plays: Vec<Event>,
     pub id: u32, // this is the game id
     pub season: u32,
     homeTeam: Team,
-    pub gameDate: String
+    pub gameDate: String,
+    pub gameState: Option<String>,
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct Event {
     eventId: u32,
     homeTeamDefendingSide: String,
     typeDescKey: String,
     pptReplayUrl: Option<String>,
+    situationCode: Option<String>,
     details: Option<EventDetails>, // details isn't always present
     periodDescriptor: PeriodInfo,
 }
 
 /// generic event details for all event types
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct EventDetails {
     eventOwnerTeamId: Option<u16>,
 }
 
 /// period info used in deserialization
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 pub struct PeriodInfo {
     periodType: String,
 }
 
 /// represents a side of the ice
 /// by the NHL's API
-#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
 pub enum IceSide {
     Left,
     Right,
 }
 
 /// event details for goals specifically
-#[derive(Debug, Serialize, Clone, PartialEq)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct GoalDetails {
     pub event_id: u32,
-    ppt_replay_url: Option<String>,
-    scoring_team_id: u16,
-    home_team_defending_side: IceSide,
+    pub ppt_replay_url: Option<String>,
+    pub scoring_team_id: u16,
+    pub home_team_defending_side: IceSide,
+    /// four digits: away goalie in net (1 or 0), away skaters, home skaters,
+    /// home goalie in net; missing from data saved by older versions
+    pub situation_code: Option<String>,
 }
 
 /// helper struct to serialize extra info needed for all the goals in a game
@@ -231,17 +306,24 @@ pub struct PbpBoxscoreInfo {
 
 /// Get the pbp data for a game
 pub fn get_pbp_data(client: &Client, game_id: &str) -> Result<PbpResponse> {
+    let pbp_resp: PbpResponse = serde_json::from_str(&get_pbp_text(client, game_id)?)?;
+    Ok(pbp_resp)
+}
+
+/// Get the raw pbp response for a game, which has its roster as well as its
+/// plays (see `parse_roster`)
+pub fn get_pbp_text(client: &Client, game_id: &str) -> Result<String> {
     let pbp_url = format!(
-        "https://api-web.nhle.com/v1/gamecenter/{}/play-by-play",
+        "{}/v1/gamecenter/{}/play-by-play",
+        api_base_url(),
         game_id
     );
     let resp = client.get(pbp_url).send()?;
 
     if resp.status() == 200 {
         let resp_text = resp.text()?;
-        let pbp_resp: PbpResponse = serde_json::from_str(&resp_text)?;
-
-        Ok(pbp_resp)
+        record_response(Endpoint::PlayByPlay, &format!("game {}", game_id), &resp_text);
+        Ok(resp_text)
     } else {
         let err_msg = format!("Unable to get play-by-play for game id: {}, response status: {}.", game_id, resp.status());
         Err(anyhow!(err_msg))
@@ -294,6 +376,7 @@ pub fn parse_goal_data(mut pbp: PbpResponse) -> GameExportData {
                     ppt_replay_url: goal_event.pptReplayUrl,
                     scoring_team_id: scoring_team,
                     home_team_defending_side,
+                    situation_code: goal_event.situationCode,
                 };
                 goals.push(goal_details);
             }
@@ -305,7 +388,7 @@ pub fn parse_goal_data(mut pbp: PbpResponse) -> GameExportData {
             }
         }
     }
-    GameExportData { home_team_id: pbp.homeTeam.id, goals: goals }
+    GameExportData { home_team_id: pbp.homeTeam.id, goals }
 }
 
 /////////////////////
@@ -328,7 +411,8 @@ pub struct BoxscoreInfo {
 /// Returns an error if unable to get the boxscore data
 pub fn get_hometeam_id(client: &Client, game: &Game) -> Result<BoxscoreInfo> {
     let boxscore_url = format!(
-        "https://api-web.nhle.com/v1/gamecenter/{}/boxscore",
+        "{}/v1/gamecenter/{}/boxscore",
+        api_base_url(),
         game.id
     );
     let resp = client.get(boxscore_url).send()?;
@@ -369,6 +453,7 @@ pub struct LandingResponse {
     pub id: u32,
     pub season: u32,
     pub gameDate: String,
+    pub gameState: Option<String>,
     homeTeam: Team,
     awayTeam: Team,
 
@@ -376,7 +461,7 @@ pub struct LandingResponse {
     summary: Summary
 }
 
-#[derive(Deserialize, Debug)]
+#[derive(Deserialize, Debug, Clone)]
 struct Team {
     id: u16,
 }
@@ -402,7 +487,8 @@ struct GoalInfo {
     eventId: u32,
     pptReplayUrl: Option<String>,
     homeTeamDefendingSide: String,
-    isHome: bool
+    isHome: bool,
+    situationCode: Option<String>,
 }
 
 /// Use the landing endpoint to get all the necessary information for a game:
@@ -418,13 +504,15 @@ struct GoalInfo {
 ///     - scoring team
 pub fn get_game_info(game_id: &str, client: &Client) -> Result<LandingResponse> {
     let landing_url = format!(
-        "https://api-web.nhle.com/v1/gamecenter/{}/landing",
+        "{}/v1/gamecenter/{}/landing",
+        api_base_url(),
         game_id
     );
     let resp = client.get(landing_url).send()?;
 
     if resp.status() == 200 {
         let resp_text = resp.text()?;
+        record_response(Endpoint::Landing, &format!("game {}", game_id), &resp_text);
         let landing_resp: LandingResponse = serde_json::from_str(&resp_text)?;
 
         Ok(landing_resp)
@@ -434,10 +522,10 @@ pub fn get_game_info(game_id: &str, client: &Client) -> Result<LandingResponse>
     }
 }
 
-#[derive(Serialize, Debug, PartialEq)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct GameExportData {
     pub goals: Vec<GoalDetails>,
-    home_team_id: u16
+    pub home_team_id: u16
 }
 
 /// From the landing response, get the game and goal data that's needed
@@ -475,15 +563,89 @@ pub fn extract_export_game_data(landing_resp: &LandingResponse) -> Result<GameEx
             goals.push(GoalDetails {
                 event_id: g.eventId,
                 ppt_replay_url: g.pptReplayUrl.clone(),
-                scoring_team_id: scoring_team_id,
-                home_team_defending_side: home_team_defending_side
+                scoring_team_id,
+                home_team_defending_side,
+                situation_code: g.situationCode.clone(),
             })
         }
     }
 
-    Ok(GameExportData { goals: goals, home_team_id: landing_resp.homeTeam.id })
+    Ok(GameExportData { goals, home_team_id: landing_resp.homeTeam.id })
 }
 
+/////////////////////
+//
+// Getting the roster
+//
+/////////////////////
+
+/// The roster part of the play-by-play response
+#[derive(Deserialize, Debug)]
+struct RosterResponse {
+    rosterSpots: Vec<RosterSpot>,
+}
+
+#[derive(Deserialize, Debug)]
+struct RosterSpot {
+    teamId: u16,
+    playerId: u32,
+    firstName: LocalizedName,
+    lastName: LocalizedName,
+    sweaterNumber: Option<u16>,
+    positionCode: String,
+}
+
+#[derive(Deserialize, Debug)]
+struct LocalizedName {
+    default: String,
+}
+
+/// A player dressed for a game, used to put names to the player ids in the
+/// tracking data
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RosterPlayer {
+    pub player_id: u32,
+    pub team_id: u16,
+    pub first_name: String,
+    pub last_name: String,
+    pub sweater_number: Option<u16>,
+    /// "C", "L", "R", "D" or "G"
+    pub position_code: String,
+}
+
+impl RosterPlayer {
+    pub fn full_name(&self) -> String {
+        format!("{} {}", self.first_name, self.last_name)
+    }
+
+    pub fn is_goalie(&self) -> bool {
+        self.position_code == "G"
+    }
+}
+
+/// Gets the players dressed for a game using the play-by-play endpoint
+/// Callers that already have the play-by-play response should use
+/// `parse_roster` on it instead.
+pub fn get_roster(client: &Client, game_id: &str) -> Result<Vec<RosterPlayer>> {
+    parse_roster(&get_pbp_text(client, game_id)?)
+}
+
+/// Gets the roster out of a play-by-play response
+pub fn parse_roster(pbp_text: &str) -> Result<Vec<RosterPlayer>> {
+    let roster_resp: RosterResponse = serde_json::from_str(pbp_text)?;
+    Ok(roster_resp
+        .rosterSpots
+        .into_iter()
+        .map(|spot| RosterPlayer {
+            player_id: spot.playerId,
+            team_id: spot.teamId,
+            first_name: spot.firstName.default,
+            last_name: spot.lastName.default,
+            sweater_number: spot.sweaterNumber,
+            position_code: spot.positionCode,
+        })
+        .collect())
+}
 
 #[cfg(test)]
 mod tests {
@@ -583,7 +745,7 @@ mod tests {
         let start_date = NaiveDate::from_ymd_opt(2024, 11, 11).unwrap();
         let end_date = NaiveDate::from_ymd_opt(2024, 11, 10).unwrap();
 
-        let wosp = WeekOrShorterPeriod::try_new(start_date, end_date).unwrap();
+        let _wosp = WeekOrShorterPeriod::try_new(start_date, end_date).unwrap();
     }
 
     // invalid WeekOrShorterPeriod: eight days
@@ -593,7 +755,7 @@ mod tests {
         let start_date = NaiveDate::from_ymd_opt(2024, 11, 11).unwrap();
         let end_date = NaiveDate::from_ymd_opt(2024, 11, 18).unwrap();
 
-        let wosp = WeekOrShorterPeriod::try_new(start_date, end_date).unwrap();
+        let _wosp = WeekOrShorterPeriod::try_new(start_date, end_date).unwrap();
     }
 
     // invalid WeekOrShorterPeriod: over a month
@@ -603,7 +765,7 @@ mod tests {
         let start_date = NaiveDate::from_ymd_opt(2024, 11, 11).unwrap();
         let end_date = NaiveDate::from_ymd_opt(2024, 12, 18).unwrap();
 
-        let wosp = WeekOrShorterPeriod::try_new(start_date, end_date).unwrap();
+        let _wosp = WeekOrShorterPeriod::try_new(start_date, end_date).unwrap();
     }
 
     /////////////////////////////////////////////
@@ -678,6 +840,7 @@ mod tests {
             event_id: 502,
             home_team_defending_side: IceSide::Left,
             ppt_replay_url: Some(String::from("https://nhl.com")),
+            situation_code: None,
         }];
         // let pbp = PbpInfo { game_id: 12, goals};
         let boxscore = BoxscoreInfo { home_team_id: 19 };
@@ -723,12 +886,13 @@ mod tests {
             eventId: 90,
             homeTeamDefendingSide: String::from("right"),
             pptReplayUrl: Some(String::from("nhl.com")),
+            situationCode: None,
             typeDescKey: String::from("shot"),
             periodDescriptor: PeriodInfo {
                 periodType: String::from("REG"),
             },
         }];
-        let pbp_info = PbpResponse { plays: plays, id: 1, season: 20252025, homeTeam: Team { id: 19 }, gameDate: String::from("2025-05-02") };
+        let pbp_info = PbpResponse { plays, id: 1, season: 20252025, homeTeam: Team { id: 19 }, gameDate: String::from("2025-05-02"), gameState: None };
 
         let actual_goal_details = parse_goal_data(pbp_info);
 
@@ -746,6 +910,7 @@ mod tests {
                 eventId: 89,
                 homeTeamDefendingSide: String::from("left"),
                 pptReplayUrl: Some(String::from("nhl.com")),
+                situationCode: None,
                 typeDescKey: String::from("shot"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
@@ -758,13 +923,14 @@ mod tests {
                 eventId: 90,
                 homeTeamDefendingSide: String::from("right"),
                 pptReplayUrl: Some(String::from("nhl.com")),
+                situationCode: Some(String::from("1541")),
                 typeDescKey: String::from("goal"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
                 },
             },
         ];
-        let pbp_info = PbpResponse { plays: plays, id: 1, season: 20252025, homeTeam: Team { id: 19 }, gameDate: String::from("2025-05-02") };
+        let pbp_info = PbpResponse { plays, id: 1, season: 20252025, homeTeam: Team { id: 19 }, gameDate: String::from("2025-05-02"), gameState: None };
 
         let actual_goal_details = parse_goal_data(pbp_info);
         let expected_goal_details = vec![GoalDetails {
@@ -772,6 +938,7 @@ mod tests {
             home_team_defending_side: IceSide::Right,
             ppt_replay_url: Some(String::from("nhl.com")),
             scoring_team_id: 1,
+            situation_code: Some(String::from("1541")),
         }];
 
         assert_eq!(actual_goal_details.goals, expected_goal_details);
@@ -788,6 +955,7 @@ mod tests {
                 eventId: 89,
                 homeTeamDefendingSide: String::from("left"),
                 pptReplayUrl: Some(String::from("nhl.com")),
+                situationCode: None,
                 typeDescKey: String::from("shot"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
@@ -800,6 +968,7 @@ mod tests {
                 eventId: 90,
                 homeTeamDefendingSide: String::from("right"),
                 pptReplayUrl: Some(String::from("nhl.com/ev90")),
+                situationCode: None,
                 typeDescKey: String::from("goal"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
@@ -812,6 +981,7 @@ mod tests {
                 eventId: 91,
                 homeTeamDefendingSide: String::from("right"),
                 pptReplayUrl: Some(String::from("nhl.com")),
+                situationCode: None,
                 typeDescKey: String::from("faceoff"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
@@ -824,6 +994,7 @@ mod tests {
                 eventId: 92,
                 homeTeamDefendingSide: String::from("left"),
                 pptReplayUrl: Some(String::from("nhl.com/ev92")),
+                situationCode: None,
                 typeDescKey: String::from("goal"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
@@ -836,13 +1007,14 @@ mod tests {
                 eventId: 93,
                 homeTeamDefendingSide: String::from("right"),
                 pptReplayUrl: Some(String::from("nhl.com/ev93")),
+                situationCode: None,
                 typeDescKey: String::from("goal"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
                 },
             },
         ];
-        let pbp_info = PbpResponse { plays: plays, id: 1, season: 20252025, homeTeam: Team { id: 19 }, gameDate: String::from("2025-05-02") };
+        let pbp_info = PbpResponse { plays, id: 1, season: 20252025, homeTeam: Team { id: 19 }, gameDate: String::from("2025-05-02"), gameState: None };
 
         let actual_goal_details = parse_goal_data(pbp_info);
         let expected_goal_details = vec![
@@ -851,18 +1023,21 @@ mod tests {
                 home_team_defending_side: IceSide::Right,
                 ppt_replay_url: Some(String::from("nhl.com/ev90")),
                 scoring_team_id: 1,
+                situation_code: None,
             },
             GoalDetails {
                 event_id: 92,
                 home_team_defending_side: IceSide::Left,
                 ppt_replay_url: Some(String::from("nhl.com/ev92")),
                 scoring_team_id: 19,
+                situation_code: None,
             },
             GoalDetails {
                 event_id: 93,
                 home_team_defending_side: IceSide::Right,
                 ppt_replay_url: Some(String::from("nhl.com/ev93")),
                 scoring_team_id: 1,
+                situation_code: None,
             },
         ];
 
@@ -880,6 +1055,7 @@ mod tests {
                 eventId: 89,
                 homeTeamDefendingSide: String::from("left"),
                 pptReplayUrl: Some(String::from("nhl.com")),
+                situationCode: None,
                 typeDescKey: String::from("shot"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
@@ -892,6 +1068,7 @@ mod tests {
                 eventId: 90,
                 homeTeamDefendingSide: String::from("right"),
                 pptReplayUrl: Some(String::from("nhl.com/ev90")),
+                situationCode: None,
                 typeDescKey: String::from("goal"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("SO"),
@@ -904,6 +1081,7 @@ mod tests {
                 eventId: 91,
                 homeTeamDefendingSide: String::from("right"),
                 pptReplayUrl: Some(String::from("nhl.com")),
+                situationCode: None,
                 typeDescKey: String::from("faceoff"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
@@ -916,6 +1094,7 @@ mod tests {
                 eventId: 92,
                 homeTeamDefendingSide: String::from("left"),
                 pptReplayUrl: Some(String::from("nhl.com/ev92")),
+                situationCode: None,
                 typeDescKey: String::from("goal"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("SO"),
@@ -928,13 +1107,14 @@ mod tests {
                 eventId: 93,
                 homeTeamDefendingSide: String::from("right"),
                 pptReplayUrl: Some(String::from("nhl.com/ev93")),
+                situationCode: None,
                 typeDescKey: String::from("goal"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("SO"),
                 },
             },
         ];
-        let pbp_info = PbpResponse { plays: plays, id: 1, season: 20252025, homeTeam: Team { id: 19 }, gameDate: String::from("2025-05-02") };
+        let pbp_info = PbpResponse { plays, id: 1, season: 20252025, homeTeam: Team { id: 19 }, gameDate: String::from("2025-05-02"), gameState: None };
 
         let actual_goal_details = parse_goal_data(pbp_info);
         let expected_goal_details = vec![];
@@ -954,6 +1134,7 @@ mod tests {
                 eventId: 89,
                 homeTeamDefendingSide: String::from("left"),
                 pptReplayUrl: Some(String::from("nhl.com")),
+                situationCode: None,
                 typeDescKey: String::from("shot"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
@@ -966,6 +1147,7 @@ mod tests {
                 eventId: 90,
                 homeTeamDefendingSide: String::from("right"),
                 pptReplayUrl: Some(String::from("nhl.com/ev90")),
+                situationCode: None,
                 typeDescKey: String::from("goal"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("SO"),
@@ -978,6 +1160,7 @@ mod tests {
                 eventId: 91,
                 homeTeamDefendingSide: String::from("right"),
                 pptReplayUrl: Some(String::from("nhl.com")),
+                situationCode: None,
                 typeDescKey: String::from("faceoff"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
@@ -990,6 +1173,7 @@ mod tests {
                 eventId: 92,
                 homeTeamDefendingSide: String::from("left"),
                 pptReplayUrl: Some(String::from("nhl.com/ev92")),
+                situationCode: None,
                 typeDescKey: String::from("goal"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("SO"),
@@ -1002,13 +1186,14 @@ mod tests {
                 eventId: 93,
                 homeTeamDefendingSide: String::from("right"),
                 pptReplayUrl: Some(String::from("nhl.com/ev93")),
+                situationCode: None,
                 typeDescKey: String::from("goal"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
                 },
             },
         ];
-        let pbp_info = PbpResponse { plays: plays, id: 1, season: 20252025, homeTeam: Team { id: 19 }, gameDate: String::from("2025-05-02") };
+        let pbp_info = PbpResponse { plays, id: 1, season: 20252025, homeTeam: Team { id: 19 }, gameDate: String::from("2025-05-02"), gameState: None };
 
         let actual_goal_details = parse_goal_data(pbp_info);
         let expected_goal_details = vec![GoalDetails {
@@ -1016,6 +1201,7 @@ mod tests {
             home_team_defending_side: IceSide::Right,
             ppt_replay_url: Some(String::from("nhl.com/ev93")),
             scoring_team_id: 1,
+            situation_code: None,
         }];
 
         assert_eq!(actual_goal_details.goals, expected_goal_details);
@@ -1032,6 +1218,7 @@ mod tests {
                 eventId: 89,
                 homeTeamDefendingSide: String::from("left"),
                 pptReplayUrl: Some(String::from("nhl.com")),
+                situationCode: None,
                 typeDescKey: String::from("shot"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
@@ -1044,6 +1231,7 @@ mod tests {
                 eventId: 90,
                 homeTeamDefendingSide: String::from("right"),
                 pptReplayUrl: Some(String::from("nhl.com/ev90")),
+                situationCode: None,
                 typeDescKey: String::from("goal"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
@@ -1056,6 +1244,7 @@ mod tests {
                 eventId: 91,
                 homeTeamDefendingSide: String::from("right"),
                 pptReplayUrl: Some(String::from("nhl.com")),
+                situationCode: None,
                 typeDescKey: String::from("faceoff"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
@@ -1068,6 +1257,7 @@ mod tests {
                 eventId: 92,
                 homeTeamDefendingSide: String::from("left"),
                 pptReplayUrl: Some(String::from("nhl.com/ev92")),
+                situationCode: None,
                 typeDescKey: String::from("goal"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
@@ -1080,13 +1270,14 @@ mod tests {
                 eventId: 93,
                 homeTeamDefendingSide: String::from("right"),
                 pptReplayUrl: Some(String::from("nhl.com/ev93")),
+                situationCode: None,
                 typeDescKey: String::from("goal"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("OT"),
                 },
             },
         ];
-        let pbp_info = PbpResponse { plays: plays, id: 1, season: 20252025, homeTeam: Team { id: 19 }, gameDate: String::from("2025-05-02") };
+        let pbp_info = PbpResponse { plays, id: 1, season: 20252025, homeTeam: Team { id: 19 }, gameDate: String::from("2025-05-02"), gameState: None };
 
         let actual_goal_details = parse_goal_data(pbp_info);
         let expected_goal_details = vec![
@@ -1095,18 +1286,21 @@ mod tests {
                 home_team_defending_side: IceSide::Right,
                 ppt_replay_url: Some(String::from("nhl.com/ev90")),
                 scoring_team_id: 1,
+                situation_code: None,
             },
             GoalDetails {
                 event_id: 92,
                 home_team_defending_side: IceSide::Left,
                 ppt_replay_url: Some(String::from("nhl.com/ev92")),
                 scoring_team_id: 19,
+                situation_code: None,
             },
             GoalDetails {
                 event_id: 93,
                 home_team_defending_side: IceSide::Right,
                 ppt_replay_url: Some(String::from("nhl.com/ev93")),
                 scoring_team_id: 1,
+                situation_code: None,
             },
         ];
 
@@ -1125,6 +1319,7 @@ mod tests {
                 eventId: 89,
                 homeTeamDefendingSide: String::from("left"),
                 pptReplayUrl: Some(String::from("nhl.com")),
+                situationCode: None,
                 typeDescKey: String::from("shot"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
@@ -1137,6 +1332,7 @@ mod tests {
                 eventId: 90,
                 homeTeamDefendingSide: String::from("right"),
                 pptReplayUrl: Some(String::from("nhl.com/ev90")),
+                situationCode: None,
                 typeDescKey: String::from("goal"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
@@ -1149,6 +1345,7 @@ mod tests {
                 eventId: 91,
                 homeTeamDefendingSide: String::from("right"),
                 pptReplayUrl: Some(String::from("nhl.com")),
+                situationCode: None,
                 typeDescKey: String::from("faceoff"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
@@ -1161,6 +1358,7 @@ mod tests {
                 eventId: 92,
                 homeTeamDefendingSide: String::from("left"),
                 pptReplayUrl: None,
+                situationCode: None,
                 typeDescKey: String::from("goal"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("REG"),
@@ -1173,13 +1371,14 @@ mod tests {
                 eventId: 93,
                 homeTeamDefendingSide: String::from("right"),
                 pptReplayUrl: None,
+                situationCode: None,
                 typeDescKey: String::from("goal"),
                 periodDescriptor: PeriodInfo {
                     periodType: String::from("OT"),
                 },
             },
         ];
-        let pbp_info = PbpResponse { plays: plays, id: 1, season: 20252025, homeTeam: Team { id: 19 }, gameDate: String::from("2025-05-02") };
+        let pbp_info = PbpResponse { plays, id: 1, season: 20252025, homeTeam: Team { id: 19 }, gameDate: String::from("2025-05-02"), gameState: None };
 
         let actual_goal_details = parse_goal_data(pbp_info);
         let expected_goal_details = vec![
@@ -1188,18 +1387,21 @@ mod tests {
                 home_team_defending_side: IceSide::Right,
                 ppt_replay_url: Some(String::from("nhl.com/ev90")),
                 scoring_team_id: 1,
+                situation_code: None,
             },
             GoalDetails {
                 event_id: 92,
                 home_team_defending_side: IceSide::Left,
                 ppt_replay_url: None,
                 scoring_team_id: 19,
+                situation_code: None,
             },
             GoalDetails {
                 event_id: 93,
                 home_team_defending_side: IceSide::Right,
                 ppt_replay_url: None,
                 scoring_team_id: 1,
+                situation_code: None,
             },
         ];
 
@@ -1219,7 +1421,7 @@ mod tests {
             periodDescriptor: PeriodDetails { 
                 periodType: String::from("REG"),
             },
-            goals: vec![GoalInfo { eventId: 12, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("right"), isHome: false}]
+            goals: vec![GoalInfo { eventId: 12, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("right"), isHome: false, situationCode: None }]
         };
         let period_2 = Period { 
             periodDescriptor: PeriodDetails { 
@@ -1232,8 +1434,8 @@ mod tests {
                 periodType: String::from("REG"),
             },
             goals: vec![
-                GoalInfo { eventId: 120, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("right"), isHome: false},
-                GoalInfo { eventId: 170, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("left"), isHome: true},
+                GoalInfo { eventId: 120, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("right"), isHome: false, situationCode: None },
+                GoalInfo { eventId: 170, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("left"), isHome: true, situationCode: None },
             ]
         };
         let summary = Summary { 
@@ -1244,8 +1446,8 @@ mod tests {
             ]
         };
         let landing_resp = LandingResponse { 
-            id: 2024000201, season: 20242025, gameDate: String::from("2024-10-29"), 
-            homeTeam: Team { id: 10 }, awayTeam: Team { id: 19 }, summary: summary };
+            id: 2024000201, season: 20242025, gameDate: String::from("2024-10-29"), gameState: None,
+            homeTeam: Team { id: 10 }, awayTeam: Team { id: 19 }, summary };
         
         let actual_game_export = extract_export_game_data(&landing_resp).unwrap();
         let expected_game_export = GameExportData {
@@ -1255,19 +1457,22 @@ mod tests {
                     event_id: 12,
                     ppt_replay_url: Some(String::from("nhl.com")),
                     scoring_team_id: 19,
-                    home_team_defending_side: IceSide::Right
+                    home_team_defending_side: IceSide::Right,
+                    situation_code: None,
                 },
                 GoalDetails {
                     event_id: 120,
                     ppt_replay_url: Some(String::from("nhl.com")),
                     scoring_team_id: 19,
-                    home_team_defending_side: IceSide::Right
+                    home_team_defending_side: IceSide::Right,
+                    situation_code: None,
                 },
                 GoalDetails {
                     event_id: 170,
                     ppt_replay_url: Some(String::from("nhl.com")),
                     scoring_team_id: 10,
-                    home_team_defending_side: IceSide::Left
+                    home_team_defending_side: IceSide::Left,
+                    situation_code: None,
                 },
             ]
         };
@@ -1307,7 +1512,7 @@ mod tests {
                 periodType: String::from("SO"),
             },
             goals: vec![
-                GoalInfo { eventId: 486, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("right"), isHome: false}
+                GoalInfo { eventId: 486, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("right"), isHome: false, situationCode: None }
             ]
         };
         let summary = Summary { 
@@ -1320,8 +1525,8 @@ mod tests {
             ]
         };
         let landing_resp = LandingResponse { 
-            id: 2024000201, season: 20242025, gameDate: String::from("2024-10-29"), 
-            homeTeam: Team { id: 10 }, awayTeam: Team { id: 19 }, summary: summary };
+            id: 2024000201, season: 20242025, gameDate: String::from("2024-10-29"), gameState: None,
+            homeTeam: Team { id: 10 }, awayTeam: Team { id: 19 }, summary };
         
         let actual_game_export = extract_export_game_data(&landing_resp).unwrap();
         let expected_game_export = GameExportData {
@@ -1341,7 +1546,7 @@ mod tests {
                 periodType: String::from("REG"),
             },
             goals: vec![
-                GoalInfo { eventId: 12, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("right"), isHome: false}
+                GoalInfo { eventId: 12, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("right"), isHome: false, situationCode: None }
             ]
         };
         let period_2 = Period { 
@@ -1367,7 +1572,7 @@ mod tests {
                 periodType: String::from("SO"),
             },
             goals: vec![
-                GoalInfo { eventId: 486, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("right"), isHome: false}
+                GoalInfo { eventId: 486, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("right"), isHome: false, situationCode: None }
             ]
         };
         let summary = Summary { 
@@ -1380,8 +1585,8 @@ mod tests {
             ]
         };
         let landing_resp = LandingResponse { 
-            id: 2024000201, season: 20242025, gameDate: String::from("2024-10-29"), 
-            homeTeam: Team { id: 10 }, awayTeam: Team { id: 19 }, summary: summary };
+            id: 2024000201, season: 20242025, gameDate: String::from("2024-10-29"), gameState: None,
+            homeTeam: Team { id: 10 }, awayTeam: Team { id: 19 }, summary };
         
         let actual_game_export = extract_export_game_data(&landing_resp).unwrap();
         let expected_game_export = GameExportData {
@@ -1391,7 +1596,8 @@ mod tests {
                     event_id: 12,
                     ppt_replay_url: Some(String::from("nhl.com")),
                     scoring_team_id: 19,
-                    home_team_defending_side: IceSide::Right
+                    home_team_defending_side: IceSide::Right,
+                    situation_code: None,
                 },
             ]
         };
@@ -1407,7 +1613,7 @@ mod tests {
                 periodType: String::from("REG"),
             },
             goals: vec![
-                GoalInfo { eventId: 12, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("right"), isHome: false}
+                GoalInfo { eventId: 12, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("right"), isHome: false, situationCode: None }
             ]
         };
         let period_2 = Period { 
@@ -1415,7 +1621,7 @@ mod tests {
                 periodType: String::from("REG"),
             },
             goals: vec![
-                GoalInfo { eventId: 200, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("left"), isHome: false}
+                GoalInfo { eventId: 200, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("left"), isHome: false, situationCode: None }
             ]
         };
         let period_3 = Period { 
@@ -1423,8 +1629,8 @@ mod tests {
                 periodType: String::from("REG"),
             },
             goals: vec![
-                GoalInfo { eventId: 312, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("right"), isHome: true},
-                GoalInfo { eventId: 351, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("right"), isHome: true}
+                GoalInfo { eventId: 312, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("right"), isHome: true, situationCode: None },
+                GoalInfo { eventId: 351, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("right"), isHome: true, situationCode: None }
             ]
         };
         let ot = Period { 
@@ -1432,7 +1638,7 @@ mod tests {
                 periodType: String::from("OT"),
             },
             goals: vec![
-                GoalInfo { eventId: 1114, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("left"), isHome: true}
+                GoalInfo { eventId: 1114, pptReplayUrl: Some(String::from("nhl.com")), homeTeamDefendingSide: String::from("left"), isHome: true, situationCode: None }
             ]
         };
         let summary = Summary { 
@@ -1444,8 +1650,8 @@ mod tests {
             ]
         };
         let landing_resp = LandingResponse { 
-            id: 2024000201, season: 20242025, gameDate: String::from("2024-10-29"), 
-            homeTeam: Team { id: 10 }, awayTeam: Team { id: 19 }, summary: summary };
+            id: 2024000201, season: 20242025, gameDate: String::from("2024-10-29"), gameState: None,
+            homeTeam: Team { id: 10 }, awayTeam: Team { id: 19 }, summary };
         
         let actual_game_export = extract_export_game_data(&landing_resp).unwrap();
         let expected_game_export = GameExportData {
@@ -1455,35 +1661,91 @@ mod tests {
                     event_id: 12,
                     ppt_replay_url: Some(String::from("nhl.com")),
                     scoring_team_id: 19,
-                    home_team_defending_side: IceSide::Right
+                    home_team_defending_side: IceSide::Right,
+                    situation_code: None,
                 },
                 GoalDetails {
                     event_id: 200,
                     ppt_replay_url: Some(String::from("nhl.com")),
                     scoring_team_id: 19,
-                    home_team_defending_side: IceSide::Left
+                    home_team_defending_side: IceSide::Left,
+                    situation_code: None,
                 },
                 GoalDetails {
                     event_id: 312,
                     ppt_replay_url: Some(String::from("nhl.com")),
                     scoring_team_id: 10,
-                    home_team_defending_side: IceSide::Right
+                    home_team_defending_side: IceSide::Right,
+                    situation_code: None,
                 },
                 GoalDetails {
                     event_id: 351,
                     ppt_replay_url: Some(String::from("nhl.com")),
                     scoring_team_id: 10,
-                    home_team_defending_side: IceSide::Right
+                    home_team_defending_side: IceSide::Right,
+                    situation_code: None,
                 },
                 GoalDetails {
                     event_id: 1114,
                     ppt_replay_url: Some(String::from("nhl.com")),
                     scoring_team_id: 10,
-                    home_team_defending_side: IceSide::Left
+                    home_team_defending_side: IceSide::Left,
+                    situation_code: None,
                 },
             ]
         };
 
         assert_eq!(actual_game_export, expected_game_export);
     }
+
+    //////////////////////////////////////////
+    //
+    // parse_roster() tests
+    //
+    //////////////////////////////////////////
+
+    // only the roster gets pulled out of the play-by-play response
+    #[test]
+    fn parse_roster_valid() {
+        let pbp_text = r#"{
+            "id": 2025020140,
+            "plays": [],
+            "rosterSpots": [
+                {"teamId": 1, "playerId": 8479407, "firstName": {"default": "Jesper"}, "lastName": {"default": "Bratt"}, "sweaterNumber": 63, "positionCode": "L", "headshot": ""},
+                {"teamId": 21, "playerId": 8480069, "firstName": {"default": "Cale"}, "lastName": {"default": "Makar"}, "sweaterNumber": 8, "positionCode": "D", "headshot": ""}
+            ]
+        }"#;
+        let roster = parse_roster(pbp_text).unwrap();
+        assert_eq!(roster.len(), 2);
+        assert_eq!(
+            roster[0],
+            RosterPlayer {
+                player_id: 8479407,
+                team_id: 1,
+                first_name: String::from("Jesper"),
+                last_name: String::from("Bratt"),
+                sweater_number: Some(63),
+                position_code: String::from("L"),
+            }
+        );
+        assert_eq!(roster[1].full_name(), "Cale Makar");
+        assert!(!roster[1].is_goalie());
+    }
+
+    //////////////////////////////////////////
+    //
+    // check_tracking() tests
+    //
+    //////////////////////////////////////////
+
+    // empty and cut off responses aren't saved
+    #[test]
+    fn check_tracking_outcomes() {
+        let tracking = r#"[{"timeStamp":100,"onIce":{"1":{"id":1,"playerId":"","x":1200,"y":510}}}]"#;
+        assert!(check_tracking(tracking).is_saved());
+        assert_eq!(check_tracking(""), TrackingOutcome::Empty);
+        assert_eq!(check_tracking(" [] \n"), TrackingOutcome::Empty);
+        assert!(matches!(check_tracking(&tracking[..40]), TrackingOutcome::Invalid(_)));
+        assert!(matches!(check_tracking("[          ]"), TrackingOutcome::Invalid(_)));
+    }
 }
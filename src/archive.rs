@@ -0,0 +1,406 @@
+use anyhow::{anyhow, Context, Result};
+
+use clap::ValueEnum;
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeSet;
+use std::fs::{read_dir, remove_dir, remove_dir_all, remove_file, rename, File};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Name of the index file written as the first entry of every archive
+pub const INDEX_FILENAME: &str = "index.json";
+
+/// Extension used for the archives
+pub const ARCHIVE_EXTENSION: &str = "zip";
+
+/// How the game folders get bundled into archives
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum ArchiveMode {
+    /// one archive per game: folder/game_date/game_id.zip
+    Game,
+    /// one archive per date: folder/game_date.zip
+    Date,
+}
+
+/// The index stored in an archive so individual goals can be looked up
+/// without going through every entry
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ArchiveIndex {
+    pub date: String,
+    pub games: Vec<ArchivedGame>,
+}
+
+/// The files belonging to a single game within an archive
+/// `prefix` is prepended to the file names to get the entry names; it's empty
+/// for per-game archives and "game_id/" for per-date archives
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct ArchivedGame {
+    pub game_id: u32,
+    pub prefix: String,
+    pub goals: Vec<u32>,
+    pub files: Vec<String>,
+}
+
+/// Bundles a game folder (folder/game_date/game_id) into
+/// folder/game_date/game_id.zip and removes the folder
+pub fn archive_game_folder<P>(game_path: P) -> Result<PathBuf>
+where
+    P: AsRef<Path>,
+{
+    let game_path = game_path.as_ref();
+    let (game_id, date) = game_id_and_date(game_path)?;
+    let archive_path = game_path.with_extension(ARCHIVE_EXTENSION);
+
+    let files = list_files(game_path)?;
+    let index = ArchiveIndex {
+        date,
+        games: vec![archived_game(game_id, String::new(), &files)],
+    };
+
+    write_archive(&archive_path, &index, &mut [], |zip, options| {
+        add_files(zip, options, game_path, "", &files)
+    })?;
+    remove_dir_all(game_path)
+        .with_context(|| format!("Failed to remove game folder {}", game_path.display()))?;
+    Ok(archive_path)
+}
+
+/// Bundles a date folder (folder/game_date) and all of its game folders into
+/// folder/game_date.zip and removes the folder
+/// Per-game archives in the folder, left by archiving by game, are folded in.
+/// If the date archive already exists, the games in it that aren't in the
+/// folder are kept so that re-running a single game doesn't drop the other
+/// games of the day.
+/// Nothing is archived if the folder has anything besides game folders and
+/// per-game archives, so that it isn't lost when the folder's removed.
+pub fn archive_date_folder<P>(date_path: P) -> Result<PathBuf>
+where
+    P: AsRef<Path>,
+{
+    let date_path = date_path.as_ref();
+    let date = file_name(date_path)?;
+    let archive_path = date_path.with_extension(ARCHIVE_EXTENSION);
+
+    // the game folders and per-game archives that are about to be archived
+    let mut game_folders = vec![];
+    let mut game_archives = vec![];
+    for entry in read_dir(date_path)? {
+        let path = entry?.path();
+        let game_id = path.file_stem().and_then(|s| s.to_str()).and_then(|s| s.parse::<u32>().ok());
+        match game_id {
+            Some(game_id) if path.is_dir() => game_folders.push((game_id, path)),
+            Some(game_id) if path.extension().is_some_and(|e| e == ARCHIVE_EXTENSION) => {
+                game_archives.push((game_id, path))
+            }
+            _ => {
+                return Err(anyhow!(
+                    "Not archiving {}: {} isn't a game folder or a game archive",
+                    date_path.display(),
+                    path.display()
+                ))
+            }
+        }
+    }
+    game_folders.sort();
+    game_archives.sort();
+
+    let mut games = vec![];
+    let mut folder_files = vec![];
+    for (game_id, path) in &game_folders {
+        let files = list_files(path)?;
+        games.push(archived_game(*game_id, format!("{}/", game_id), &files));
+        folder_files.push(files);
+    }
+
+    // fold in the per-game archives of games that haven't been downloaded
+    // again since, then carry over the games that are only in the existing
+    // date archive
+    let mut archived_ids: BTreeSet<u32> = games.iter().map(|g| g.game_id).collect();
+    let mut carry_over = vec![];
+    let old_archive_path = archive_path.exists().then_some(archive_path.as_path());
+    for path in game_archives.iter().map(|(_, p)| p.as_path()).chain(old_archive_path) {
+        let mut archive = open_archive(path)?;
+        let mut entries = vec![];
+        for g in read_index(&mut archive)?.games {
+            if archived_ids.insert(g.game_id) {
+                let prefix = format!("{}/", g.game_id);
+                entries.extend(
+                    g.files
+                        .iter()
+                        .map(|f| (format!("{}{}", g.prefix, f), format!("{}{}", prefix, f))),
+                );
+                games.push(ArchivedGame { prefix, ..g });
+            }
+        }
+        carry_over.push(CarryOver { archive, entries });
+    }
+    games.sort_by_key(|g| g.game_id);
+    let index = ArchiveIndex { date, games };
+
+    write_archive(&archive_path, &index, &mut carry_over, |zip, options| {
+        for ((game_id, path), files) in game_folders.iter().zip(&folder_files) {
+            add_files(zip, options, path, &format!("{}/", game_id), files)?;
+        }
+        Ok(())
+    })?;
+
+    // only what went into the archive is removed
+    for (_, path) in &game_folders {
+        remove_dir_all(path)
+            .with_context(|| format!("Failed to remove game folder {}", path.display()))?;
+    }
+    for (_, path) in &game_archives {
+        remove_file(path)
+            .with_context(|| format!("Failed to remove game archive {}", path.display()))?;
+    }
+    remove_dir(date_path)
+        .with_context(|| format!("Failed to remove date folder {}", date_path.display()))?;
+    Ok(archive_path)
+}
+
+/// Opens an archive made by `archive_game_folder` or `archive_date_folder`
+pub fn open_archive<P>(path: P) -> Result<ZipArchive<File>>
+where
+    P: AsRef<Path>,
+{
+    let path = path.as_ref();
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open archive {}", path.display()))?;
+    ZipArchive::new(file).with_context(|| format!("Invalid archive {}", path.display()))
+}
+
+/// Reads the index out of an archive
+pub fn read_index(archive: &mut ZipArchive<File>) -> Result<ArchiveIndex> {
+    let index_string = read_entry(archive, INDEX_FILENAME)?
+        .ok_or(anyhow!("Archive is missing {}", INDEX_FILENAME))?;
+    Ok(serde_json::from_str(&index_string)?)
+}
+
+/// Reads a single entry out of an archive without extracting the rest
+/// Returns None if the archive doesn't have the entry
+pub fn read_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<Option<String>> {
+    let mut entry = match archive.by_name(name) {
+        Ok(entry) => entry,
+        Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+        Err(e) => return Err(anyhow!("Error reading {} from archive: {}", name, e)),
+    };
+    let mut contents = String::new();
+    entry.read_to_string(&mut contents)?;
+    Ok(Some(contents))
+}
+
+/// Entries to copy out of an existing archive without decompressing them
+struct CarryOver {
+    archive: ZipArchive<File>,
+    /// the entry names in the existing archive and in the new one
+    entries: Vec<(String, String)>,
+}
+
+/// Writes an archive to a temporary file first and then moves it into place,
+/// so an existing archive isn't clobbered if something goes wrong
+fn write_archive<F>(
+    archive_path: &Path,
+    index: &ArchiveIndex,
+    carry_over: &mut [CarryOver],
+    add_new: F,
+) -> Result<()>
+where
+    F: FnOnce(&mut ZipWriter<File>, SimpleFileOptions) -> Result<()>,
+{
+    let tmp_path = archive_path.with_extension(format!("{}.tmp", ARCHIVE_EXTENSION));
+    let tmp_file = File::create(&tmp_path)
+        .with_context(|| format!("Failed to create archive {}", tmp_path.display()))?;
+    let mut zip = ZipWriter::new(tmp_file);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+
+    zip.start_file(INDEX_FILENAME, options)?;
+    zip.write_all(serde_json::to_string(index)?.as_bytes())?;
+
+    for carried in carry_over {
+        for (old_name, new_name) in &carried.entries {
+            let i = carried
+                .archive
+                .index_for_name(old_name)
+                .ok_or(anyhow!("Archive is missing {}", old_name))?;
+            zip.raw_copy_file_rename(carried.archive.by_index_raw(i)?, new_name)?;
+        }
+    }
+
+    add_new(&mut zip, options)?;
+    zip.finish()?;
+    rename(&tmp_path, archive_path)
+        .with_context(|| format!("Failed to move archive into {}", archive_path.display()))?;
+    Ok(())
+}
+
+/// Adds the files in a folder to an archive, with `prefix` prepended to their
+/// names
+fn add_files(
+    zip: &mut ZipWriter<File>,
+    options: SimpleFileOptions,
+    folder: &Path,
+    prefix: &str,
+    files: &[String],
+) -> Result<()> {
+    for f in files {
+        let mut contents = vec![];
+        File::open(folder.join(f))?.read_to_end(&mut contents)?;
+        zip.start_file(format!("{}{}", prefix, f), options)?;
+        zip.write_all(&contents)?;
+    }
+    Ok(())
+}
+
+/// Builds the index entry for a game
+/// The goal tracking files are the ones named by their event id.
+fn archived_game(game_id: u32, prefix: String, files: &[String]) -> ArchivedGame {
+    let mut goals: Vec<u32> = files.iter().filter_map(|f| f.parse().ok()).collect();
+    goals.sort();
+    ArchivedGame {
+        game_id,
+        prefix,
+        goals,
+        files: files.to_vec(),
+    }
+}
+
+/// Lists the names of the files directly inside a folder, sorted
+fn list_files(folder: &Path) -> Result<Vec<String>> {
+    let mut files = vec![];
+    for entry in read_dir(folder)
+        .with_context(|| format!("Failed to read folder {}", folder.display()))?
+    {
+        let path = entry?.path();
+        if path.is_file() {
+            files.push(file_name(&path)?);
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// Gets the game id and the date from a game folder's path, which looks like
+/// folder/game_date/game_id
+fn game_id_and_date(game_path: &Path) -> Result<(u32, String)> {
+    let game_id = file_name(game_path)?
+        .parse()
+        .with_context(|| format!("Invalid game folder: {}", game_path.display()))?;
+    let date_path = game_path
+        .parent()
+        .ok_or(anyhow!("Game folder {} has no date folder", game_path.display()))?;
+    Ok((game_id, file_name(date_path)?))
+}
+
+fn file_name(path: &Path) -> Result<String> {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .map(|n| n.to_string())
+        .ok_or(anyhow!("Invalid path: {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::temp_folder;
+
+    use std::fs::{create_dir_all, write};
+
+    fn make_game(date_path: &Path, game_id: u32, goals: &[u32]) -> PathBuf {
+        let game_path = date_path.join(game_id.to_string());
+        create_dir_all(&game_path).unwrap();
+        write(game_path.join("pbp_boxscore.json"), r#"{"goals":[],"home_team_id":1}"#).unwrap();
+        for g in goals {
+            write(game_path.join(g.to_string()), format!("[{}]", g)).unwrap();
+        }
+        game_path
+    }
+
+    // a game archive has the index plus every file in the game folder
+    #[test]
+    fn archive_game_folder_round_trip() {
+        let root = temp_folder("archive_game");
+        let game_path = make_game(&root.join("2025-10-26"), 2025020140, &[663, 52]);
+
+        let archive_path = archive_game_folder(&game_path).unwrap();
+        assert_eq!(archive_path, root.join("2025-10-26").join("2025020140.zip"));
+        assert!(!game_path.exists());
+
+        let mut archive = open_archive(&archive_path).unwrap();
+        let index = read_index(&mut archive).unwrap();
+        assert_eq!(
+            index,
+            ArchiveIndex {
+                date: String::from("2025-10-26"),
+                games: vec![ArchivedGame {
+                    game_id: 2025020140,
+                    prefix: String::new(),
+                    goals: vec![52, 663],
+                    files: vec![String::from("52"), String::from("663"), String::from("pbp_boxscore.json")],
+                }],
+            }
+        );
+        assert_eq!(read_entry(&mut archive, "663").unwrap(), Some(String::from("[663]")));
+        assert_eq!(read_entry(&mut archive, "664").unwrap(), None);
+        remove_dir_all(root).unwrap();
+    }
+
+    // re-archiving a date keeps the games that were already in the archive
+    #[test]
+    fn archive_date_folder_keeps_existing_games() {
+        let root = temp_folder("archive_date");
+        let date_path = root.join("2025-10-29");
+        make_game(&date_path, 2025020150, &[10]);
+        make_game(&date_path, 2025020151, &[20, 21]);
+        archive_date_folder(&date_path).unwrap();
+
+        // re-run one of the games with an extra goal, plus a new game
+        make_game(&date_path, 2025020151, &[20, 21, 22]);
+        make_game(&date_path, 2025020152, &[]);
+        let archive_path = archive_date_folder(&date_path).unwrap();
+        assert!(!date_path.exists());
+
+        let mut archive = open_archive(&archive_path).unwrap();
+        let index = read_index(&mut archive).unwrap();
+        let game_ids: Vec<u32> = index.games.iter().map(|g| g.game_id).collect();
+        assert_eq!(game_ids, vec![2025020150, 2025020151, 2025020152]);
+        assert_eq!(index.games[1].goals, vec![20, 21, 22]);
+        assert_eq!(read_entry(&mut archive, "2025020150/10").unwrap(), Some(String::from("[10]")));
+        assert_eq!(read_entry(&mut archive, "2025020151/22").unwrap(), Some(String::from("[22]")));
+        remove_dir_all(root).unwrap();
+    }
+
+    // a per-game archive from archiving by game is folded into the date
+    // archive alongside the game folders, and anything else stops archiving
+    #[test]
+    fn archive_date_folder_folds_in_game_archives() {
+        let root = temp_folder("archive_mixed");
+        let date_path = root.join("2025-10-29");
+        archive_game_folder(make_game(&date_path, 2025020150, &[10, 11])).unwrap();
+        let game_path = make_game(&date_path, 2025020151, &[20]);
+
+        write(date_path.join("notes.txt"), "keep me").unwrap();
+        assert!(archive_date_folder(&date_path).is_err());
+        assert!(game_path.exists() && date_path.join("2025020150.zip").exists());
+        remove_file(date_path.join("notes.txt")).unwrap();
+
+        let archive_path = archive_date_folder(&date_path).unwrap();
+        assert!(!date_path.exists());
+        let mut archive = open_archive(&archive_path).unwrap();
+        let index = read_index(&mut archive).unwrap();
+        let games: Vec<(u32, &str, Vec<u32>)> =
+            index.games.iter().map(|g| (g.game_id, g.prefix.as_str(), g.goals.clone())).collect();
+        assert_eq!(
+            games,
+            vec![(2025020150, "2025020150/", vec![10, 11]), (2025020151, "2025020151/", vec![20])]
+        );
+        assert_eq!(read_entry(&mut archive, "2025020150/11").unwrap(), Some(String::from("[11]")));
+        assert_eq!(read_entry(&mut archive, "2025020151/20").unwrap(), Some(String::from("[20]")));
+        remove_dir_all(root).unwrap();
+    }
+}
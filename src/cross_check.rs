@@ -0,0 +1,149 @@
+use clap::ValueEnum;
+
+use std::fmt;
+
+use crate::api_calls::{GameExportData, GoalDetails};
+
+/// What to do when the landing and play-by-play endpoints disagree about a
+/// game
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum CrossCheckMode {
+    /// print the differences, and save the game from the landing endpoint
+    Report,
+    /// print the differences, and don't save the game
+    Fail,
+}
+
+/// A difference between the goal details from the two endpoints
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mismatch {
+    HomeTeam { landing: u16, play_by_play: u16 },
+    OnlyInLanding(u32),
+    OnlyInPlayByPlay(u32),
+    /// a goal from both endpoints, with a field that differs
+    Goal {
+        event_id: u32,
+        field: &'static str,
+        landing: String,
+        play_by_play: String,
+    },
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mismatch::HomeTeam { landing, play_by_play } => {
+                write!(f, "home team is {} from landing, {} from play-by-play", landing, play_by_play)
+            }
+            Mismatch::OnlyInLanding(event_id) => write!(f, "goal {} is only in landing", event_id),
+            Mismatch::OnlyInPlayByPlay(event_id) => write!(f, "goal {} is only in play-by-play", event_id),
+            Mismatch::Goal { event_id, field, landing, play_by_play } => write!(
+                f,
+                "goal {} has {} {} from landing, {} from play-by-play",
+                event_id, field, landing, play_by_play
+            ),
+        }
+    }
+}
+
+/// Compares the goal details extracted from the landing endpoint with the
+/// ones from the play-by-play endpoint, goal by goal
+pub fn cross_check(landing: &GameExportData, play_by_play: &GameExportData) -> Vec<Mismatch> {
+    let mut mismatches = vec![];
+    if landing.home_team_id != play_by_play.home_team_id {
+        mismatches.push(Mismatch::HomeTeam {
+            landing: landing.home_team_id,
+            play_by_play: play_by_play.home_team_id,
+        });
+    }
+
+    for goal in &landing.goals {
+        match play_by_play.goals.iter().find(|g| g.event_id == goal.event_id) {
+            Some(pbp_goal) => mismatches.extend(goal_mismatches(goal, pbp_goal)),
+            None => mismatches.push(Mismatch::OnlyInLanding(goal.event_id)),
+        }
+    }
+    for goal in &play_by_play.goals {
+        if !landing.goals.iter().any(|g| g.event_id == goal.event_id) {
+            mismatches.push(Mismatch::OnlyInPlayByPlay(goal.event_id));
+        }
+    }
+    mismatches
+}
+
+fn goal_mismatches(landing: &GoalDetails, play_by_play: &GoalDetails) -> Vec<Mismatch> {
+    let fields = [
+        ("scoring team", landing.scoring_team_id.to_string(), play_by_play.scoring_team_id.to_string()),
+        (
+            "home defending side",
+            format!("{:?}", landing.home_team_defending_side),
+            format!("{:?}", play_by_play.home_team_defending_side),
+        ),
+        ("replay URL", format!("{:?}", landing.ppt_replay_url), format!("{:?}", play_by_play.ppt_replay_url)),
+        ("situation code", format!("{:?}", landing.situation_code), format!("{:?}", play_by_play.situation_code)),
+    ];
+    fields
+        .into_iter()
+        .filter(|(_, from_landing, from_play_by_play)| from_landing != from_play_by_play)
+        .map(|(field, from_landing, from_play_by_play)| Mismatch::Goal {
+            event_id: landing.event_id,
+            field,
+            landing: from_landing,
+            play_by_play: from_play_by_play,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::api_calls::IceSide;
+
+    fn goal(event_id: u32, home_team_defending_side: IceSide) -> GoalDetails {
+        GoalDetails {
+            event_id,
+            ppt_replay_url: Some(format!("https://wsr.nhle.com/{}", event_id)),
+            scoring_team_id: 1,
+            home_team_defending_side,
+            situation_code: Some(String::from("1551")),
+        }
+    }
+
+    #[test]
+    fn cross_check_agreeing_endpoints() {
+        let game_data = GameExportData {
+            goals: vec![goal(663, IceSide::Left), goal(700, IceSide::Right)],
+            home_team_id: 1,
+        };
+        assert_eq!(cross_check(&game_data, &game_data.clone()), vec![]);
+    }
+
+    #[test]
+    fn cross_check_finds_mismatches() {
+        let landing = GameExportData {
+            goals: vec![goal(663, IceSide::Left), goal(700, IceSide::Right)],
+            home_team_id: 1,
+        };
+        let play_by_play = GameExportData {
+            goals: vec![goal(663, IceSide::Right), goal(701, IceSide::Right)],
+            home_team_id: 21,
+        };
+        let mismatches = cross_check(&landing, &play_by_play);
+        assert_eq!(
+            mismatches,
+            vec![
+                Mismatch::HomeTeam { landing: 1, play_by_play: 21 },
+                Mismatch::Goal {
+                    event_id: 663,
+                    field: "home defending side",
+                    landing: String::from("Left"),
+                    play_by_play: String::from("Right"),
+                },
+                Mismatch::OnlyInLanding(700),
+                Mismatch::OnlyInPlayByPlay(701),
+            ]
+        );
+        assert_eq!(mismatches[1].to_string(), "goal 663 has home defending side Left from landing, Right from play-by-play");
+    }
+}
@@ -0,0 +1,670 @@
+use anyhow::{Context, Result};
+
+use serde::Serialize;
+
+use std::fs::create_dir_all;
+use std::path::Path;
+
+use crate::api_calls::{GoalDetails, RosterPlayer};
+use crate::kinematics::{
+    compute_kinematics, summarize_kinematics, Kinematics, KinematicsOptions, FT_PER_S_TO_MPH,
+};
+use crate::normalize::{normalize_frames, SPRITE_UNITS_PER_FOOT};
+use crate::reader::OutputReader;
+use crate::possession::{goal_pass_chain, possession_timeline, Pass, Zone};
+use crate::resample::{resample_frames, ResampleOptions};
+use crate::screen::{measure_screen, Screen};
+use crate::shot::{detect_shot, ShotRelease};
+use crate::strength::{check_strength, StrengthCheck};
+use crate::team_shape::summarize_team_shapes;
+use crate::tracking::Frame;
+use crate::zone_entry::{classify_zone_entry, EntryType, ZoneEntry};
+
+/// Name of the file with one row per entity per frame
+pub const FRAMES_FILENAME: &str = "frames.csv";
+
+/// Name of the file with one row per goal
+pub const GOALS_FILENAME: &str = "goals.csv";
+
+/// Name of the file with one row per entity per goal summarizing its motion
+pub const KINEMATICS_FILENAME: &str = "kinematics.csv";
+
+/// Name of the file with the passes leading up to each goal
+pub const PASSES_FILENAME: &str = "passes.csv";
+
+/// Name of the file with the possession timeline of each goal
+pub const POSSESSION_FILENAME: &str = "possession.csv";
+
+/// Name of the file with one row per team per goal summarizing its shape
+pub const TEAM_SHAPE_FILENAME: &str = "team_shape.csv";
+
+/// What to export, and how to transform the tracking data first
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// only export this game
+    pub game_id: Option<u32>,
+    /// only export this goal
+    pub event_id: Option<u32>,
+    /// convert the frames to the canonical frame of reference (see
+    /// `normalize_frames`)
+    pub normalize: bool,
+    /// resample the frames to a fixed rate (see `resample_frames`)
+    pub resample: Option<ResampleOptions>,
+    /// add speed, acceleration, heading and distance to the frames, and
+    /// write a summary per entity per goal
+    pub kinematics: bool,
+    /// moving average window used to smooth positions for the kinematics
+    pub smoothing_window: usize,
+    /// write the timeline of who had the puck during each goal
+    pub possession: bool,
+    /// write a summary of each team's shape during each goal
+    pub team_shape: bool,
+}
+
+/// A row of the frames table
+#[derive(Debug, Serialize)]
+struct FrameRow<'a> {
+    game_id: u32,
+    event_id: u32,
+    frame: usize,
+    seconds: f64,
+    entity_id: u32,
+    player_id: Option<u32>,
+    team_id: Option<u16>,
+    team_abbrev: Option<&'a str>,
+    sweater_number: Option<u16>,
+    is_puck: bool,
+    x: f64,
+    y: f64,
+    interpolated: bool,
+    speed_ft_s: Option<f64>,
+    speed_mph: Option<f64>,
+    acceleration_ft_s2: Option<f64>,
+    heading_deg: Option<f64>,
+    distance_ft: Option<f64>,
+}
+
+/// A row of the goal summary table
+/// The shot fields are in the canonical frame of reference, whether or not
+/// the frames table is normalized.
+#[derive(Debug, Serialize)]
+struct GoalRow {
+    game_id: u32,
+    event_id: u32,
+    scoring_team_id: u16,
+    num_frames: usize,
+    release_frame: Option<usize>,
+    release_seconds: Option<f64>,
+    goal_line_frame: Option<usize>,
+    shooter_id: Option<u32>,
+    release_x: Option<f64>,
+    release_y: Option<f64>,
+    shot_distance_ft: Option<f64>,
+    shot_angle_deg: Option<f64>,
+    release_speed_ft_s: Option<f64>,
+    release_speed_mph: Option<f64>,
+    num_passes: usize,
+    entry_type: Option<EntryType>,
+    entry_frame: Option<usize>,
+    entry_seconds: Option<f64>,
+    entry_player_id: Option<u32>,
+    screen_skaters_in_triangle: Option<usize>,
+    screen_attackers_in_triangle: Option<usize>,
+    screen_defenders_in_triangle: Option<usize>,
+    nearest_defender_ft: Option<f64>,
+    goalie_id: Option<u32>,
+    teammate_in_sightline: Option<bool>,
+    situation_code: Option<String>,
+    tracked_situation_code: Option<String>,
+    away_goalie_on_ice: Option<bool>,
+    away_skaters: Option<usize>,
+    home_skaters: Option<usize>,
+    home_goalie_on_ice: Option<bool>,
+    strength_mismatch: Option<bool>,
+}
+
+/// A row of the pass chain table, in the canonical frame of reference
+#[derive(Debug, Serialize)]
+struct PassRow {
+    game_id: u32,
+    event_id: u32,
+    pass_number: usize,
+    team_id: Option<u16>,
+    passer_id: u32,
+    passer_name: Option<String>,
+    passer_sweater_number: Option<u16>,
+    receiver_id: u32,
+    receiver_name: Option<String>,
+    receiver_sweater_number: Option<u16>,
+    start_frame: usize,
+    end_frame: usize,
+    start_seconds: f64,
+    end_seconds: f64,
+    start_x: f64,
+    start_y: f64,
+    end_x: f64,
+    end_y: f64,
+    distance_ft: f64,
+}
+
+/// A row of the possession timeline table
+/// The zone is from the scoring team's point of view.
+#[derive(Debug, Serialize)]
+struct PossessionRow {
+    game_id: u32,
+    event_id: u32,
+    segment: usize,
+    start_frame: usize,
+    end_frame: usize,
+    start_seconds: f64,
+    end_seconds: f64,
+    loose: bool,
+    player_id: Option<u32>,
+    player_name: Option<String>,
+    sweater_number: Option<u16>,
+    team_id: Option<u16>,
+    zone: Zone,
+    confidence: f64,
+}
+
+/// A row of the team shape table, in the canonical frame of reference
+/// The `goal_` columns are the team's shape in the frame the puck crossed the
+/// goal line (or the last frame, if it wasn't tracked that far).
+#[derive(Debug, Serialize)]
+struct TeamShapeRow {
+    game_id: u32,
+    event_id: u32,
+    team_id: u16,
+    scoring_team: bool,
+    mean_hull_area_ft2: f64,
+    max_hull_area_ft2: f64,
+    mean_inter_player_distance_ft: f64,
+    mean_compactness_ft: f64,
+    goal_num_skaters: Option<usize>,
+    goal_centroid_x: Option<f64>,
+    goal_centroid_y: Option<f64>,
+    goal_hull_area_ft2: Option<f64>,
+    goal_inter_player_distance_ft: Option<f64>,
+    goal_compactness_ft: Option<f64>,
+}
+
+/// A row of the kinematics summary table
+#[derive(Debug, Serialize)]
+struct KinematicsRow<'a> {
+    game_id: u32,
+    event_id: u32,
+    entity_id: u32,
+    player_id: Option<u32>,
+    team_id: Option<u16>,
+    team_abbrev: Option<&'a str>,
+    sweater_number: Option<u16>,
+    is_puck: bool,
+    max_speed_ft_s: f64,
+    max_speed_mph: f64,
+    mean_speed_ft_s: f64,
+    max_acceleration_ft_s2: f64,
+    distance_ft: f64,
+}
+
+/// Exports the tracking data in an output folder (or archive) as tables
+/// Goals whose tracking data can't be read are skipped.
+pub fn export<P>(reader: &OutputReader, options: &ExportOptions, output_folder: P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let output_folder = output_folder.as_ref();
+    create_dir_all(output_folder)
+        .with_context(|| format!("Failed to create folder {}", output_folder.display()))?;
+    let frames_path = output_folder.join(FRAMES_FILENAME);
+    let mut frames_writer = csv::Writer::from_path(&frames_path)
+        .with_context(|| format!("Failed to create {}", frames_path.display()))?;
+    let goals_path = output_folder.join(GOALS_FILENAME);
+    let mut goals_writer = csv::Writer::from_path(&goals_path)
+        .with_context(|| format!("Failed to create {}", goals_path.display()))?;
+    let passes_path = output_folder.join(PASSES_FILENAME);
+    let mut passes_writer = csv::Writer::from_path(&passes_path)
+        .with_context(|| format!("Failed to create {}", passes_path.display()))?;
+    let mut possession_writer = if options.possession {
+        let possession_path = output_folder.join(POSSESSION_FILENAME);
+        Some(
+            csv::Writer::from_path(&possession_path)
+                .with_context(|| format!("Failed to create {}", possession_path.display()))?,
+        )
+    } else {
+        None
+    };
+    let mut team_shape_writer = if options.team_shape {
+        let team_shape_path = output_folder.join(TEAM_SHAPE_FILENAME);
+        Some(
+            csv::Writer::from_path(&team_shape_path)
+                .with_context(|| format!("Failed to create {}", team_shape_path.display()))?,
+        )
+    } else {
+        None
+    };
+    let mut kinematics_writer = if options.kinematics {
+        let kinematics_path = output_folder.join(KINEMATICS_FILENAME);
+        Some(
+            csv::Writer::from_path(&kinematics_path)
+                .with_context(|| format!("Failed to create {}", kinematics_path.display()))?,
+        )
+    } else {
+        None
+    };
+
+    for game in reader.games() {
+        if options.game_id.is_some_and(|id| id != game.game_id) {
+            continue;
+        }
+        let game_data = match reader.read_game_data(game.game_id) {
+            Ok(game_data) => game_data,
+            Err(e) => {
+                println!("Skipping game {}: {}", game.game_id, e);
+                continue;
+            }
+        };
+        // names are nice to have, but the passes can still be written without them
+        let roster = reader.read_roster(game.game_id).unwrap_or_else(|e| {
+            println!("Unable to read the roster for game {}: {}", game.game_id, e);
+            vec![]
+        });
+        let goalie_ids: Vec<u32> = roster.iter().filter(|p| p.is_goalie()).map(|p| p.player_id).collect();
+
+        for goal in &game_data.goals {
+            if options.event_id.is_some_and(|id| id != goal.event_id) {
+                continue;
+            }
+            let frames = match reader
+                .read_frames(game.game_id, goal.event_id)
+                .and_then(|frames| prepare_frames(frames, goal, game_data.home_team_id, options))
+            {
+                Ok(frames) => frames,
+                Err(e) => {
+                    println!("Skipping game {}, goal {}: {}", game.game_id, goal.event_id, e);
+                    continue;
+                }
+            };
+            let kinematics = kinematics_writer
+                .as_ref()
+                .map(|_| compute_kinematics(&frames, &kinematics_options(options)));
+            write_frames(&mut frames_writer, game.game_id, goal.event_id, &frames, kinematics.as_deref())?;
+            if let (Some(writer), Some(kinematics)) = (kinematics_writer.as_mut(), &kinematics) {
+                write_kinematics_summary(writer, game.game_id, goal.event_id, &frames, kinematics)?;
+            }
+
+            // the goal summary always works off of normalized frames
+            let normalized = if options.normalize {
+                frames
+            } else {
+                normalize_frames(&frames, goal, game_data.home_team_id)
+            };
+            let shot = detect_shot(&normalized, goal.scoring_team_id);
+            // without a release, every pass until the end of the sequence counts
+            let release_frame = shot.as_ref().map_or(normalized.len(), |s| s.release_frame);
+            let passes = goal_pass_chain(&normalized, goal.scoring_team_id, release_frame);
+            let entry = classify_zone_entry(&normalized, goal.scoring_team_id, &goalie_ids, release_frame);
+            let screen = shot
+                .as_ref()
+                .map(|shot| measure_screen(&normalized, shot, goal.scoring_team_id, &goalie_ids));
+            // the frame the puck went in, or as close as the tracking got
+            let goal_frame = shot
+                .as_ref()
+                .and_then(|s| s.goal_line_frame)
+                .unwrap_or(normalized.len().saturating_sub(1));
+            // without the roster there's no telling goalies from skaters
+            let strength = normalized.get(goal_frame).filter(|_| !goalie_ids.is_empty()).map(|frame| {
+                check_strength(frame, game_data.home_team_id, &goalie_ids, goal.situation_code.as_deref())
+            });
+            let summary = GoalSummary {
+                shot: shot.as_ref(),
+                num_passes: passes.len(),
+                entry: entry.as_ref(),
+                screen: screen.as_ref(),
+                strength: strength.as_ref(),
+            };
+            write_goal_summary(&mut goals_writer, game.game_id, goal, &normalized, &summary)?;
+            write_passes(&mut passes_writer, game.game_id, goal.event_id, &passes, &roster)?;
+            if let Some(writer) = possession_writer.as_mut() {
+                write_possession(writer, game.game_id, goal.event_id, &normalized, &roster)?;
+            }
+            if let Some(writer) = team_shape_writer.as_mut() {
+                write_team_shape(writer, game.game_id, goal, &normalized, &goalie_ids, goal_frame)?;
+            }
+        }
+    }
+    frames_writer.flush()?;
+    goals_writer.flush()?;
+    passes_writer.flush()?;
+    if let Some(writer) = possession_writer.as_mut() {
+        writer.flush()?;
+    }
+    if let Some(writer) = team_shape_writer.as_mut() {
+        writer.flush()?;
+    }
+    if let Some(writer) = kinematics_writer.as_mut() {
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// Applies the resampling and normalization in the options to a goal's
+/// frames
+pub fn prepare_frames(
+    mut frames: Vec<Frame>,
+    goal: &GoalDetails,
+    home_team_id: u16,
+    options: &ExportOptions,
+) -> Result<Vec<Frame>> {
+    if let Some(resample_options) = &options.resample {
+        frames = resample_frames(&frames, resample_options)?;
+    }
+    if options.normalize {
+        frames = normalize_frames(&frames, goal, home_team_id);
+    }
+    Ok(frames)
+}
+
+/// The kinematics settings for the export: normalized frames are already in
+/// feet, while raw frames are in sprite units
+pub fn kinematics_options(options: &ExportOptions) -> KinematicsOptions {
+    KinematicsOptions {
+        units_per_foot: if options.normalize { 1.0 } else { SPRITE_UNITS_PER_FOOT },
+        smoothing_window: options.smoothing_window,
+    }
+}
+
+/// Writes a goal's frames to the frames table, along with their kinematics
+/// if there are any
+fn write_frames<W>(
+    writer: &mut csv::Writer<W>,
+    game_id: u32,
+    event_id: u32,
+    frames: &[Frame],
+    kinematics: Option<&[Vec<Kinematics>]>,
+) -> Result<()>
+where
+    W: std::io::Write,
+{
+    let first = match frames.first() {
+        Some(first) => first,
+        None => return Ok(()),
+    };
+    for (i, frame) in frames.iter().enumerate() {
+        for (j, e) in frame.entities.iter().enumerate() {
+            let k = kinematics.map(|k| k[i][j]);
+            writer.serialize(FrameRow {
+                game_id,
+                event_id,
+                frame: i,
+                seconds: frame.seconds_since(first),
+                entity_id: e.id,
+                player_id: e.player_id,
+                team_id: e.team_id,
+                team_abbrev: e.team_abbrev.as_deref(),
+                sweater_number: e.sweater_number,
+                is_puck: e.is_puck(),
+                x: e.x,
+                y: e.y,
+                interpolated: e.interpolated,
+                speed_ft_s: k.map(|k| k.speed_ft_s),
+                speed_mph: k.map(|k| k.speed_mph()),
+                acceleration_ft_s2: k.map(|k| k.acceleration_ft_s2),
+                heading_deg: k.map(|k| k.heading_deg),
+                distance_ft: k.map(|k| k.distance_ft),
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// What was detected about a goal's build up, for the goal summary
+struct GoalSummary<'a> {
+    shot: Option<&'a ShotRelease>,
+    num_passes: usize,
+    entry: Option<&'a ZoneEntry>,
+    screen: Option<&'a Screen>,
+    strength: Option<&'a StrengthCheck>,
+}
+
+/// Writes the summary of a goal, using its normalized frames
+fn write_goal_summary<W>(
+    writer: &mut csv::Writer<W>,
+    game_id: u32,
+    goal: &GoalDetails,
+    normalized: &[Frame],
+    summary: &GoalSummary,
+) -> Result<()>
+where
+    W: std::io::Write,
+{
+    let shot = summary.shot;
+    let entry = summary.entry;
+    let screen = summary.screen;
+    let tracked = summary.strength.map(|s| s.tracked);
+    let release_seconds = shot
+        .as_ref()
+        .map(|s| normalized[s.release_frame].seconds_since(&normalized[0]));
+    writer.serialize(GoalRow {
+        game_id,
+        event_id: goal.event_id,
+        scoring_team_id: goal.scoring_team_id,
+        num_frames: normalized.len(),
+        release_frame: shot.map(|s| s.release_frame),
+        release_seconds,
+        goal_line_frame: shot.and_then(|s| s.goal_line_frame),
+        shooter_id: shot.map(|s| s.shooter_id),
+        release_x: shot.map(|s| s.release_x),
+        release_y: shot.map(|s| s.release_y),
+        shot_distance_ft: shot.map(|s| s.distance_ft),
+        shot_angle_deg: shot.map(|s| s.angle_deg),
+        release_speed_ft_s: shot.map(|s| s.release_speed_ft_s),
+        release_speed_mph: shot.map(|s| s.release_speed_ft_s * FT_PER_S_TO_MPH),
+        num_passes: summary.num_passes,
+        entry_type: entry.map(|e| e.entry_type),
+        entry_frame: entry.map(|e| e.frame),
+        entry_seconds: entry.map(|e| e.seconds),
+        entry_player_id: entry.and_then(|e| e.player_id),
+        screen_skaters_in_triangle: screen.map(|s| s.skaters_in_triangle),
+        screen_attackers_in_triangle: screen.map(|s| s.attackers_in_triangle),
+        screen_defenders_in_triangle: screen.map(|s| s.defenders_in_triangle),
+        nearest_defender_ft: screen.and_then(|s| s.nearest_defender_ft),
+        goalie_id: screen.and_then(|s| s.goalie_id),
+        teammate_in_sightline: screen.map(|s| s.teammate_in_sightline),
+        situation_code: goal.situation_code.clone(),
+        tracked_situation_code: tracked.map(|s| s.situation_code()),
+        away_goalie_on_ice: tracked.map(|s| s.away_goalie),
+        away_skaters: tracked.map(|s| s.away_skaters),
+        home_skaters: tracked.map(|s| s.home_skaters),
+        home_goalie_on_ice: tracked.map(|s| s.home_goalie),
+        strength_mismatch: summary.strength.and_then(|s| s.mismatch()),
+    })?;
+    Ok(())
+}
+
+/// Writes the passes leading up to a goal, with the players' names from the
+/// roster
+fn write_passes<W>(
+    writer: &mut csv::Writer<W>,
+    game_id: u32,
+    event_id: u32,
+    passes: &[Pass],
+    roster: &[RosterPlayer],
+) -> Result<()>
+where
+    W: std::io::Write,
+{
+    let find_player = |id: u32| roster.iter().find(|p| p.player_id == id);
+    for (i, pass) in passes.iter().enumerate() {
+        let passer = find_player(pass.passer_id);
+        let receiver = find_player(pass.receiver_id);
+        writer.serialize(PassRow {
+            game_id,
+            event_id,
+            pass_number: i + 1,
+            team_id: pass.team_id,
+            passer_id: pass.passer_id,
+            passer_name: passer.map(|p| p.full_name()),
+            passer_sweater_number: passer.and_then(|p| p.sweater_number),
+            receiver_id: pass.receiver_id,
+            receiver_name: receiver.map(|p| p.full_name()),
+            receiver_sweater_number: receiver.and_then(|p| p.sweater_number),
+            start_frame: pass.start_frame,
+            end_frame: pass.end_frame,
+            start_seconds: pass.start_seconds,
+            end_seconds: pass.end_seconds,
+            start_x: pass.start_x,
+            start_y: pass.start_y,
+            end_x: pass.end_x,
+            end_y: pass.end_y,
+            distance_ft: pass.distance_ft,
+        })?;
+    }
+    Ok(())
+}
+
+/// Writes the possession timeline of a goal, using its normalized frames
+fn write_possession<W>(
+    writer: &mut csv::Writer<W>,
+    game_id: u32,
+    event_id: u32,
+    normalized: &[Frame],
+    roster: &[RosterPlayer],
+) -> Result<()>
+where
+    W: std::io::Write,
+{
+    for (i, segment) in possession_timeline(normalized).iter().enumerate() {
+        let player = segment
+            .player_id
+            .and_then(|id| roster.iter().find(|p| p.player_id == id));
+        writer.serialize(PossessionRow {
+            game_id,
+            event_id,
+            segment: i + 1,
+            start_frame: segment.start_frame,
+            end_frame: segment.end_frame,
+            start_seconds: segment.start_seconds,
+            end_seconds: segment.end_seconds,
+            loose: segment.is_loose(),
+            player_id: segment.player_id,
+            player_name: player.map(|p| p.full_name()),
+            sweater_number: player.and_then(|p| p.sweater_number),
+            team_id: segment.team_id,
+            zone: segment.zone,
+            confidence: segment.confidence,
+        })?;
+    }
+    Ok(())
+}
+
+/// Writes the summary of each team's shape during a goal, using its
+/// normalized frames
+fn write_team_shape<W>(
+    writer: &mut csv::Writer<W>,
+    game_id: u32,
+    goal: &GoalDetails,
+    normalized: &[Frame],
+    goalie_ids: &[u32],
+    goal_frame: usize,
+) -> Result<()>
+where
+    W: std::io::Write,
+{
+    for summary in summarize_team_shapes(normalized, goalie_ids, goal_frame) {
+        let at_goal = summary.at_goal.as_ref();
+        writer.serialize(TeamShapeRow {
+            game_id,
+            event_id: goal.event_id,
+            team_id: summary.team_id,
+            scoring_team: summary.team_id == goal.scoring_team_id,
+            mean_hull_area_ft2: summary.mean_hull_area_ft2,
+            max_hull_area_ft2: summary.max_hull_area_ft2,
+            mean_inter_player_distance_ft: summary.mean_inter_player_distance_ft,
+            mean_compactness_ft: summary.mean_compactness_ft,
+            goal_num_skaters: at_goal.map(|s| s.num_skaters),
+            goal_centroid_x: at_goal.map(|s| s.centroid_x),
+            goal_centroid_y: at_goal.map(|s| s.centroid_y),
+            goal_hull_area_ft2: at_goal.map(|s| s.hull_area_ft2),
+            goal_inter_player_distance_ft: at_goal.map(|s| s.inter_player_distance_ft),
+            goal_compactness_ft: at_goal.map(|s| s.compactness_ft),
+        })?;
+    }
+    Ok(())
+}
+
+/// Writes the summary of each entity's motion during a goal
+fn write_kinematics_summary<W>(
+    writer: &mut csv::Writer<W>,
+    game_id: u32,
+    event_id: u32,
+    frames: &[Frame],
+    kinematics: &[Vec<Kinematics>],
+) -> Result<()>
+where
+    W: std::io::Write,
+{
+    for summary in summarize_kinematics(frames, kinematics) {
+        let e = match frames
+            .iter()
+            .flat_map(|f| &f.entities)
+            .find(|e| e.id == summary.entity_id)
+        {
+            Some(e) => e,
+            None => continue,
+        };
+        writer.serialize(KinematicsRow {
+            game_id,
+            event_id,
+            entity_id: e.id,
+            player_id: e.player_id,
+            team_id: e.team_id,
+            team_abbrev: e.team_abbrev.as_deref(),
+            sweater_number: e.sweater_number,
+            is_puck: e.is_puck(),
+            max_speed_ft_s: summary.max_speed_ft_s,
+            max_speed_mph: summary.max_speed_ft_s * FT_PER_S_TO_MPH,
+            mean_speed_ft_s: summary.mean_speed_ft_s,
+            max_acceleration_ft_s2: summary.max_acceleration_ft_s2,
+            distance_ft: summary.distance_ft,
+        })?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::tracking::Entity;
+
+    // one row per entity per frame, with the time relative to the first frame
+    #[test]
+    fn write_frames_rows() {
+        let entity = |id, player_id: Option<u32>, x| Entity {
+            id,
+            player_id,
+            team_id: player_id.map(|_| 22),
+            team_abbrev: player_id.map(|_| String::from("EDM")),
+            sweater_number: player_id.map(|_| 97),
+            x,
+            y: 1.0,
+            interpolated: false,
+        };
+        let frames = vec![
+            Frame { time_stamp: 100.0, entities: vec![entity(1, None, 5.0), entity(8478402, Some(8478402), 6.0)] },
+            Frame { time_stamp: 105.0, entities: vec![entity(1, None, 7.5)] },
+        ];
+
+        let mut writer = csv::Writer::from_writer(vec![]);
+        write_frames(&mut writer, 2025020140, 663, &frames, None).unwrap();
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(
+            output,
+            "game_id,event_id,frame,seconds,entity_id,player_id,team_id,team_abbrev,sweater_number,is_puck,x,y,interpolated,\
+             speed_ft_s,speed_mph,acceleration_ft_s2,heading_deg,distance_ft\n\
+             2025020140,663,0,0.0,1,,,,,true,5.0,1.0,false,,,,,\n\
+             2025020140,663,0,0.0,8478402,8478402,22,EDM,97,false,6.0,1.0,false,,,,,\n\
+             2025020140,663,1,0.5,1,,,,,true,7.5,1.0,false,,,,,\n"
+        );
+    }
+}
@@ -0,0 +1,396 @@
+use anyhow::{Context, Result};
+
+use clap::ValueEnum;
+
+use serde::Serialize;
+
+use std::fmt::Write;
+use std::fs::create_dir_all;
+use std::path::Path;
+
+use crate::normalize::{normalize_frames, RINK_LENGTH_FT, RINK_WIDTH_FT};
+use crate::possession::goal_pass_chain;
+use crate::reader::OutputReader;
+use crate::render::{escape, rink_svg, svg_document, to_svg, RenderOptions};
+use crate::shot::detect_shot;
+use crate::strength::{check_strength, Strength};
+
+/// Default size of a heatmap bin, in feet
+pub const DEFAULT_BIN_FT: f64 = 5.0;
+
+/// Name of the file with one row per heatmap bin
+pub const HEATMAP_CSV_FILENAME: &str = "heatmap.csv";
+
+/// Name of the heatmap drawn over a rink
+pub const HEATMAP_SVG_FILENAME: &str = "heatmap.svg";
+
+const RELEASE_COLOR: &str = "#d7301f";
+const PASS_ORIGIN_COLOR: &str = "#2b6cb0";
+
+/// Which goals to count, by the scoring team's strength when the goal was
+/// scored
+/// Strength is by the number of skaters, so a goal with the goalie pulled
+/// for an extra attacker counts as a power play.
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum StrengthFilter {
+    Even,
+    PowerPlay,
+    ShortHanded,
+    /// the defending team's net was empty
+    EmptyNet,
+}
+
+impl StrengthFilter {
+    pub fn matches(&self, strength: &Strength, scoring_team_is_home: bool) -> bool {
+        let (skaters_for, skaters_against, goalie_against) = if scoring_team_is_home {
+            (strength.home_skaters, strength.away_skaters, strength.away_goalie)
+        } else {
+            (strength.away_skaters, strength.home_skaters, strength.home_goalie)
+        };
+        match self {
+            StrengthFilter::Even => skaters_for == skaters_against,
+            StrengthFilter::PowerPlay => skaters_for > skaters_against,
+            StrengthFilter::ShortHanded => skaters_for < skaters_against,
+            StrengthFilter::EmptyNet => !goalie_against,
+        }
+    }
+}
+
+/// What to put in a heatmap
+#[derive(Debug, Clone)]
+pub struct HeatmapOptions {
+    /// width and height of each bin
+    pub bin_ft: f64,
+    /// also count where the passes leading up to each goal came from
+    pub passes: bool,
+    /// only count goals by this team, by its abbreviation (e.g. "TOR")
+    pub team: Option<String>,
+    /// only count shots by this player, and passes made by them
+    pub player_id: Option<u32>,
+    pub strength: Option<StrengthFilter>,
+}
+
+impl Default for HeatmapOptions {
+    fn default() -> Self {
+        HeatmapOptions {
+            bin_ft: DEFAULT_BIN_FT,
+            passes: false,
+            team: None,
+            player_id: None,
+            strength: None,
+        }
+    }
+}
+
+/// What a heatmap's counting
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HeatmapLayer {
+    /// where the shots were released
+    Release,
+    /// where the passes before the shots were made from
+    PassOrigin,
+}
+
+/// Counts of locations binned over the whole rink, in the canonical frame
+/// of reference, so the net being shot at is on the right
+#[derive(Debug, Clone, PartialEq)]
+pub struct Heatmap {
+    pub bin_ft: f64,
+    pub columns: usize,
+    pub rows: usize,
+    /// counts for each bin, row by row from the bottom left (-x, -y)
+    pub releases: Vec<u32>,
+    pub pass_origins: Vec<u32>,
+    /// goals that passed the filters
+    pub num_goals: usize,
+}
+
+impl Heatmap {
+    pub fn new(bin_ft: f64) -> Heatmap {
+        let columns = (RINK_LENGTH_FT / bin_ft).ceil() as usize;
+        let rows = (RINK_WIDTH_FT / bin_ft).ceil() as usize;
+        Heatmap {
+            bin_ft,
+            columns,
+            rows,
+            releases: vec![0; columns * rows],
+            pass_origins: vec![0; columns * rows],
+            num_goals: 0,
+        }
+    }
+
+    /// Index of the bin a point falls in; None if it's off the rink
+    pub fn bin(&self, x: f64, y: f64) -> Option<usize> {
+        let column = ((x + RINK_LENGTH_FT / 2.0) / self.bin_ft).floor();
+        let row = ((y + RINK_WIDTH_FT / 2.0) / self.bin_ft).floor();
+        if column < 0.0 || row < 0.0 || column as usize >= self.columns || row as usize >= self.rows {
+            return None;
+        }
+        Some(row as usize * self.columns + column as usize)
+    }
+
+    /// Counts a location, ignoring ones off the rink
+    pub fn add(&mut self, layer: HeatmapLayer, x: f64, y: f64) {
+        if let Some(i) = self.bin(x, y) {
+            self.layer_mut(layer)[i] += 1;
+        }
+    }
+
+    pub fn layer(&self, layer: HeatmapLayer) -> &[u32] {
+        match layer {
+            HeatmapLayer::Release => &self.releases,
+            HeatmapLayer::PassOrigin => &self.pass_origins,
+        }
+    }
+
+    fn layer_mut(&mut self, layer: HeatmapLayer) -> &mut Vec<u32> {
+        match layer {
+            HeatmapLayer::Release => &mut self.releases,
+            HeatmapLayer::PassOrigin => &mut self.pass_origins,
+        }
+    }
+
+    /// The bottom left corner of a bin, in feet
+    fn bin_origin(&self, i: usize) -> (f64, f64) {
+        let (row, column) = (i / self.columns, i % self.columns);
+        (
+            column as f64 * self.bin_ft - RINK_LENGTH_FT / 2.0,
+            row as f64 * self.bin_ft - RINK_WIDTH_FT / 2.0,
+        )
+    }
+}
+
+/// Bins the release locations (and optionally pass origins) of every goal
+/// in the output folder that passes the filters, and writes the bins to a
+/// CSV and drawn over a rink as an SVG
+pub fn heatmap<P>(reader: &OutputReader, options: &HeatmapOptions, output_folder: P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let output_folder = output_folder.as_ref();
+    create_dir_all(output_folder).with_context(|| format!("Failed to create {}", output_folder.display()))?;
+
+    let heatmap = build_heatmap(reader, options);
+
+    let csv_path = output_folder.join(HEATMAP_CSV_FILENAME);
+    let mut writer =
+        csv::Writer::from_path(&csv_path).with_context(|| format!("Failed to create {}", csv_path.display()))?;
+    write_heatmap(&mut writer, &heatmap, options.passes)?;
+    writer.flush()?;
+
+    let svg_path = output_folder.join(HEATMAP_SVG_FILENAME);
+    let title = format!("Goal heatmap: {} goals", heatmap.num_goals);
+    std::fs::write(&svg_path, heatmap_svg(&heatmap, &title, &RenderOptions::default()))
+        .with_context(|| format!("Failed to write {}", svg_path.display()))?;
+
+    println!("Binned {} goals to {}", heatmap.num_goals, output_folder.display());
+    Ok(())
+}
+
+/// Bins the goals in the output folder that pass the filters
+/// Goals that can't be read, or where the shot can't be found, are skipped.
+pub fn build_heatmap(reader: &OutputReader, options: &HeatmapOptions) -> Heatmap {
+    let mut heatmap = Heatmap::new(options.bin_ft);
+    for game in reader.games() {
+        let game_data = match reader.read_game_data(game.game_id) {
+            Ok(game_data) => game_data,
+            Err(e) => {
+                println!("Skipping game {}: {}", game.game_id, e);
+                continue;
+            }
+        };
+        let goalie_ids: Vec<u32> = match reader.read_roster(game.game_id) {
+            Ok(roster) => roster.iter().filter(|p| p.is_goalie()).map(|p| p.player_id).collect(),
+            Err(e) => {
+                println!("Unable to read the roster for game {}: {}", game.game_id, e);
+                vec![]
+            }
+        };
+
+        for goal in &game_data.goals {
+            let frames = match reader.read_frames(game.game_id, goal.event_id) {
+                Ok(frames) => normalize_frames(&frames, goal, game_data.home_team_id),
+                Err(e) => {
+                    println!("Skipping game {}, goal {}: {}", game.game_id, goal.event_id, e);
+                    continue;
+                }
+            };
+
+            if let Some(team) = &options.team {
+                let abbrev = frames
+                    .iter()
+                    .flat_map(|f| f.players())
+                    .find(|p| p.team_id == Some(goal.scoring_team_id))
+                    .and_then(|p| p.team_abbrev.as_deref());
+                if !abbrev.is_some_and(|abbrev| abbrev.eq_ignore_ascii_case(team)) {
+                    continue;
+                }
+            }
+            let Some(shot) = detect_shot(&frames, goal.scoring_team_id) else {
+                continue;
+            };
+            if let Some(filter) = options.strength {
+                // the NHL's situation code, or failing that what the tracking
+                // shows, which needs the roster to find the goalies
+                let goal_frame = shot.goal_line_frame.unwrap_or(frames.len() - 1);
+                let check = check_strength(
+                    &frames[goal_frame],
+                    game_data.home_team_id,
+                    &goalie_ids,
+                    goal.situation_code.as_deref(),
+                );
+                let strength = check.reported.or((!goalie_ids.is_empty()).then_some(check.tracked));
+                let is_home = goal.scoring_team_id == game_data.home_team_id;
+                if !strength.is_some_and(|strength| filter.matches(&strength, is_home)) {
+                    continue;
+                }
+            }
+
+            let passes = if options.passes {
+                goal_pass_chain(&frames, goal.scoring_team_id, shot.release_frame)
+            } else {
+                vec![]
+            };
+            // the shot's shooter is by entity id, the option by player id
+            let shooter_id = frames[shot.release_frame]
+                .players()
+                .find(|p| p.id == shot.shooter_id)
+                .and_then(|p| p.player_id);
+            let shooter_matches = options.player_id.is_none_or(|id| Some(id) == shooter_id);
+            let passes: Vec<_> = passes
+                .iter()
+                .filter(|pass| options.player_id.is_none_or(|id| id == pass.passer_id))
+                .collect();
+            if !shooter_matches && passes.is_empty() {
+                continue;
+            }
+
+            heatmap.num_goals += 1;
+            if shooter_matches {
+                heatmap.add(HeatmapLayer::Release, shot.release_x, shot.release_y);
+            }
+            for pass in passes {
+                heatmap.add(HeatmapLayer::PassOrigin, pass.start_x, pass.start_y);
+            }
+        }
+    }
+    heatmap
+}
+
+#[derive(Debug, Serialize)]
+struct HeatmapRow {
+    layer: HeatmapLayer,
+    x_min_ft: f64,
+    x_max_ft: f64,
+    y_min_ft: f64,
+    y_max_ft: f64,
+    count: u32,
+}
+
+/// Writes one row per bin, including the empty ones, so the rows make up
+/// the whole grid
+fn write_heatmap<W>(writer: &mut csv::Writer<W>, heatmap: &Heatmap, passes: bool) -> Result<()>
+where
+    W: std::io::Write,
+{
+    let layers: &[HeatmapLayer] = if passes {
+        &[HeatmapLayer::Release, HeatmapLayer::PassOrigin]
+    } else {
+        &[HeatmapLayer::Release]
+    };
+    for &layer in layers {
+        for (i, &count) in heatmap.layer(layer).iter().enumerate() {
+            let (x, y) = heatmap.bin_origin(i);
+            writer.serialize(HeatmapRow {
+                layer,
+                x_min_ft: x,
+                x_max_ft: x + heatmap.bin_ft,
+                y_min_ft: y,
+                y_max_ft: y + heatmap.bin_ft,
+                count,
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Draws a heatmap over a rink, the more goals in a bin the darker
+pub fn heatmap_svg(heatmap: &Heatmap, title: &str, options: &RenderOptions) -> String {
+    let mut body = rink_svg();
+    let _ = writeln!(body, r#"<g clip-path="url(#rink)">"#);
+    for (layer, color) in [
+        (HeatmapLayer::PassOrigin, PASS_ORIGIN_COLOR),
+        (HeatmapLayer::Release, RELEASE_COLOR),
+    ] {
+        let counts = heatmap.layer(layer);
+        let max = counts.iter().copied().max().unwrap_or(0);
+        for (i, &count) in counts.iter().enumerate().filter(|(_, &count)| count > 0) {
+            let (x, y) = heatmap.bin_origin(i);
+            // the top left corner, as SVG's y is down
+            let (x, y) = to_svg(x, y + heatmap.bin_ft);
+            let _ = writeln!(
+                body,
+                r#"<rect x="{:.2}" y="{:.2}" width="{}" height="{}" fill="{}" fill-opacity="{:.2}"><title>{}</title></rect>"#,
+                x,
+                y,
+                heatmap.bin_ft,
+                heatmap.bin_ft,
+                color,
+                0.15 + 0.7 * count as f64 / max as f64,
+                count
+            );
+        }
+    }
+    let _ = writeln!(body, "</g>");
+    let _ = writeln!(
+        body,
+        r##"<text x="2" y="{:.2}" font-size="3" font-family="Helvetica, Arial, DejaVu Sans, sans-serif" fill="#333333">{}</text>"##,
+        RINK_WIDTH_FT - 2.0,
+        escape(title)
+    );
+    svg_document(&body, title, options)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn heatmap_bins() {
+        let mut heatmap = Heatmap::new(5.0);
+        assert_eq!((heatmap.columns, heatmap.rows), (40, 17));
+        assert_eq!(heatmap.bin(-100.0, -42.5), Some(0));
+        assert_eq!(heatmap.bin(99.9, 42.4), Some(40 * 17 - 1));
+        assert_eq!(heatmap.bin(100.0, 0.0), None);
+        assert_eq!(heatmap.bin(0.0, -43.0), None);
+
+        heatmap.add(HeatmapLayer::Release, 70.0, 2.0);
+        heatmap.add(HeatmapLayer::Release, 74.9, -2.5);
+        heatmap.add(HeatmapLayer::PassOrigin, 60.0, -20.0);
+        heatmap.add(HeatmapLayer::PassOrigin, 300.0, 0.0);
+        let i = heatmap.bin(70.0, 2.0).unwrap();
+        assert_eq!(heatmap.releases[i], 2);
+        assert_eq!(heatmap.bin_origin(i), (70.0, -2.5));
+        assert_eq!(heatmap.pass_origins.iter().sum::<u32>(), 1);
+
+        let mut writer = csv::Writer::from_writer(vec![]);
+        write_heatmap(&mut writer, &heatmap, true).unwrap();
+        let csv = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(csv.lines().count(), 1 + 2 * 40 * 17);
+        assert!(csv.contains("release,70.0,75.0,-2.5,2.5,2\n"));
+        assert!(csv.contains("pass-origin,60.0,65.0,-22.5,-17.5,1\n"));
+    }
+
+    #[test]
+    fn strength_filter_matches() {
+        // the home team's goalie pulled for an extra attacker
+        let strength = Strength::from_situation_code("1560").unwrap();
+        assert!(StrengthFilter::PowerPlay.matches(&strength, true));
+        assert!(StrengthFilter::ShortHanded.matches(&strength, false));
+        assert!(StrengthFilter::EmptyNet.matches(&strength, false));
+        assert!(!StrengthFilter::EmptyNet.matches(&strength, true));
+        assert!(!StrengthFilter::Even.matches(&strength, true));
+        assert!(StrengthFilter::Even.matches(&Strength::from_situation_code("1551").unwrap(), false));
+    }
+}
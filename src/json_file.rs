@@ -0,0 +1,66 @@
+use anyhow::{Context, Result};
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use std::fs;
+use std::path::Path;
+
+/// Reads a value saved as JSON in a file, or the default value if there isn't
+/// one
+/// `what` names the value in the error for a file that can't be parsed.
+pub fn load_json_or_default<P, T>(path: P, what: &str) -> Result<T>
+where
+    P: AsRef<Path>,
+    T: DeserializeOwned + Default,
+{
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    let text = fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    serde_json::from_str(&text).with_context(|| format!("Invalid {} in {}", what, path.display()))
+}
+
+/// Saves a value as pretty JSON
+/// It's written to a temporary file first, so stopping the program mid-write
+/// can't leave a broken file behind.
+pub fn save_json_atomic<P, T>(path: P, value: &T) -> Result<()>
+where
+    P: AsRef<Path>,
+    T: Serialize,
+{
+    let path = path.as_ref();
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, serde_json::to_string_pretty(value)?)
+        .with_context(|| format!("Failed to write {}", temp_path.display()))?;
+    fs::rename(&temp_path, path).with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::temp_folder;
+
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn save_and_load_json() {
+        let folder = temp_folder("json_file");
+        let path = folder.join("state.json");
+        let loaded: BTreeMap<String, u32> = load_json_or_default(&path, "state").unwrap();
+        assert!(loaded.is_empty());
+
+        let state = BTreeMap::from([(String::from("2025020140"), 663)]);
+        save_json_atomic(&path, &state).unwrap();
+        assert!(!path.with_extension("json.tmp").exists());
+        assert_eq!(load_json_or_default::<_, BTreeMap<String, u32>>(&path, "state").unwrap(), state);
+
+        fs::write(&path, "{").unwrap();
+        let e = load_json_or_default::<_, BTreeMap<String, u32>>(&path, "state").unwrap_err();
+        assert_eq!(e.to_string(), format!("Invalid state in {}", path.display()));
+        fs::remove_dir_all(folder).unwrap();
+    }
+}
@@ -0,0 +1,298 @@
+use std::collections::BTreeMap;
+
+use crate::tracking::Frame;
+
+/// 1 ft/s in mph: 3600 s/h over 5280 ft/mi
+pub const FT_PER_S_TO_MPH: f64 = 3600.0 / 5280.0;
+
+/// Settings for computing kinematics
+#[derive(Debug, Clone, Copy)]
+pub struct KinematicsOptions {
+    /// how many coordinate units make up a foot: 1 for normalized frames,
+    /// `SPRITE_UNITS_PER_FOOT` for raw sprite frames
+    pub units_per_foot: f64,
+    /// number of frames in the centered moving average applied to the
+    /// positions before differentiating them; 1 turns smoothing off
+    pub smoothing_window: usize,
+}
+
+/// Motion of an entity at a single frame
+/// The heading is the direction of travel in degrees, counterclockwise from
+/// the positive x axis of the frames' coordinates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Kinematics {
+    pub speed_ft_s: f64,
+    pub acceleration_ft_s2: f64,
+    pub heading_deg: f64,
+    /// distance travelled since the entity's first frame in the sequence
+    pub distance_ft: f64,
+}
+
+impl Kinematics {
+    pub fn speed_mph(&self) -> f64 {
+        self.speed_ft_s * FT_PER_S_TO_MPH
+    }
+}
+
+/// Summary of an entity's motion over a whole goal sequence
+#[derive(Debug, Clone, PartialEq)]
+pub struct KinematicsSummary {
+    pub entity_id: u32,
+    pub max_speed_ft_s: f64,
+    pub mean_speed_ft_s: f64,
+    pub max_acceleration_ft_s2: f64,
+    pub distance_ft: f64,
+}
+
+/// Computes the kinematics of every entity in every frame
+/// The result has the same shape as the frames: one entry per entity, in the
+/// same order as `frame.entities`.  An entity's track restarts whenever it's
+/// missing from a frame, and a track of a single frame has no motion.
+pub fn compute_kinematics(frames: &[Frame], options: &KinematicsOptions) -> Vec<Vec<Kinematics>> {
+    let mut kinematics: Vec<Vec<Kinematics>> = frames
+        .iter()
+        .map(|f| {
+            vec![
+                Kinematics {
+                    speed_ft_s: 0.0,
+                    acceleration_ft_s2: 0.0,
+                    heading_deg: 0.0,
+                    distance_ft: 0.0,
+                };
+                f.entities.len()
+            ]
+        })
+        .collect();
+
+    for track in build_tracks(frames) {
+        let times: Vec<f64> = track
+            .iter()
+            .map(|&(frame_i, _)| frames[frame_i].seconds_since(&frames[0]))
+            .collect();
+        let positions: Vec<(f64, f64)> = track
+            .iter()
+            .map(|&(frame_i, entity_i)| {
+                let e = &frames[frame_i].entities[entity_i];
+                (e.x / options.units_per_foot, e.y / options.units_per_foot)
+            })
+            .collect();
+        let positions = smooth(&positions, options.smoothing_window);
+
+        let velocities: Vec<(f64, f64)> = (0..positions.len())
+            .map(|k| {
+                let (a, b) = neighbours(k, positions.len());
+                let dt = times[b] - times[a];
+                if dt > 0.0 {
+                    ((positions[b].0 - positions[a].0) / dt, (positions[b].1 - positions[a].1) / dt)
+                } else {
+                    (0.0, 0.0)
+                }
+            })
+            .collect();
+        let speeds: Vec<f64> = velocities.iter().map(|v| v.0.hypot(v.1)).collect();
+
+        let mut distance = 0.0;
+        for k in 0..track.len() {
+            if k > 0 {
+                distance += (positions[k].0 - positions[k - 1].0).hypot(positions[k].1 - positions[k - 1].1);
+            }
+            let (a, b) = neighbours(k, speeds.len());
+            let dt = times[b] - times[a];
+            let acceleration = if dt > 0.0 { (speeds[b] - speeds[a]) / dt } else { 0.0 };
+
+            let (frame_i, entity_i) = track[k];
+            kinematics[frame_i][entity_i] = Kinematics {
+                speed_ft_s: speeds[k],
+                acceleration_ft_s2: acceleration,
+                heading_deg: velocities[k].1.atan2(velocities[k].0).to_degrees(),
+                distance_ft: distance,
+            };
+        }
+    }
+    kinematics
+}
+
+/// Summarizes each entity's kinematics over the whole sequence, in entity id
+/// order
+pub fn summarize_kinematics(frames: &[Frame], kinematics: &[Vec<Kinematics>]) -> Vec<KinematicsSummary> {
+    let mut summaries: BTreeMap<u32, (KinematicsSummary, usize)> = BTreeMap::new();
+    let mut last_distance: BTreeMap<u32, f64> = BTreeMap::new();
+
+    for (frame, frame_kinematics) in frames.iter().zip(kinematics) {
+        for (e, k) in frame.entities.iter().zip(frame_kinematics) {
+            let (summary, count) = summaries.entry(e.id).or_insert((
+                KinematicsSummary {
+                    entity_id: e.id,
+                    max_speed_ft_s: 0.0,
+                    mean_speed_ft_s: 0.0,
+                    max_acceleration_ft_s2: 0.0,
+                    distance_ft: 0.0,
+                },
+                0,
+            ));
+            summary.max_speed_ft_s = summary.max_speed_ft_s.max(k.speed_ft_s);
+            summary.max_acceleration_ft_s2 = summary.max_acceleration_ft_s2.max(k.acceleration_ft_s2);
+            summary.mean_speed_ft_s += k.speed_ft_s;
+            *count += 1;
+
+            // the distance restarts with every track, so add up the
+            // distance covered within each of them
+            let previous = last_distance.insert(e.id, k.distance_ft).unwrap_or(0.0);
+            if k.distance_ft >= previous {
+                summary.distance_ft += k.distance_ft - previous;
+            } else {
+                summary.distance_ft += k.distance_ft;
+            }
+        }
+    }
+
+    summaries
+        .into_values()
+        .map(|(mut summary, count)| {
+            summary.mean_speed_ft_s /= count as f64;
+            summary
+        })
+        .collect()
+}
+
+/// Splits the frames into tracks: runs of consecutive frames with the same
+/// entity in them, as (frame index, entity index) pairs
+fn build_tracks(frames: &[Frame]) -> Vec<Vec<(usize, usize)>> {
+    let mut finished = vec![];
+    let mut current: BTreeMap<u32, Vec<(usize, usize)>> = BTreeMap::new();
+
+    for (frame_i, frame) in frames.iter().enumerate() {
+        let mut next = BTreeMap::new();
+        for (entity_i, e) in frame.entities.iter().enumerate() {
+            let mut track = current.remove(&e.id).unwrap_or_default();
+            track.push((frame_i, entity_i));
+            next.insert(e.id, track);
+        }
+        // anything left wasn't in this frame, so its track is over
+        finished.extend(current.into_values());
+        current = next;
+    }
+    finished.extend(current.into_values());
+    finished
+}
+
+/// Centered moving average, with the window shrinking at the ends
+fn smooth(positions: &[(f64, f64)], window: usize) -> Vec<(f64, f64)> {
+    let half = window.max(1) / 2;
+    (0..positions.len())
+        .map(|k| {
+            let half = half.min(k).min(positions.len() - 1 - k);
+            let points = &positions[k - half..=k + half];
+            let n = points.len() as f64;
+            (
+                points.iter().map(|p| p.0).sum::<f64>() / n,
+                points.iter().map(|p| p.1).sum::<f64>() / n,
+            )
+        })
+        .collect()
+}
+
+/// Indices to take a central difference around k, falling back to forward or
+/// backward differences at the ends
+fn neighbours(k: usize, len: usize) -> (usize, usize) {
+    if len < 2 {
+        (k, k)
+    } else if k == 0 {
+        (0, 1)
+    } else if k == len - 1 {
+        (k - 1, k)
+    } else {
+        (k - 1, k + 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::entity;
+
+    fn options(units_per_foot: f64, smoothing_window: usize) -> KinematicsOptions {
+        KinematicsOptions { units_per_foot, smoothing_window }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    // constant speed straight up, in sprite units
+    #[test]
+    fn compute_kinematics_constant_speed() {
+        // 12 units (1 ft) every tenth of a second: 10 ft/s
+        let frames: Vec<Frame> = (0..4)
+            .map(|i| Frame { time_stamp: i as f64, entities: vec![entity(1, None, 0.0, 12.0 * i as f64)] })
+            .collect();
+        let kinematics = compute_kinematics(&frames, &options(12.0, 1));
+
+        for (i, k) in kinematics.iter().enumerate() {
+            assert_close(k[0].speed_ft_s, 10.0);
+            assert_close(k[0].acceleration_ft_s2, 0.0);
+            assert_close(k[0].heading_deg, 90.0);
+            assert_close(k[0].distance_ft, i as f64);
+        }
+        assert_close(kinematics[0][0].speed_mph(), 10.0 * 3600.0 / 5280.0);
+    }
+
+    // speeding up: 0, 1, 3, 6 ft over 0.1 s steps
+    #[test]
+    fn compute_kinematics_accelerating() {
+        let xs = [0.0, 1.0, 3.0, 6.0];
+        let frames: Vec<Frame> = xs
+            .iter()
+            .enumerate()
+            .map(|(i, x)| Frame { time_stamp: i as f64, entities: vec![entity(1, None, *x, 0.0)] })
+            .collect();
+        let kinematics = compute_kinematics(&frames, &options(1.0, 1));
+
+        let speeds: Vec<f64> = kinematics.iter().map(|k| k[0].speed_ft_s).collect();
+        assert_close(speeds[0], 10.0);
+        assert_close(speeds[1], 15.0);
+        assert_close(speeds[2], 25.0);
+        assert_close(speeds[3], 30.0);
+        assert_close(kinematics[1][0].acceleration_ft_s2, 75.0);
+        assert_close(kinematics[3][0].distance_ft, 6.0);
+    }
+
+    // a player missing from a frame starts a new track
+    #[test]
+    fn compute_kinematics_gap_restarts_track() {
+        let frames = vec![
+            Frame { time_stamp: 0.0, entities: vec![entity(1, None, 0.0, 0.0), entity(8478402, None, 0.0, 0.0)] },
+            Frame { time_stamp: 1.0, entities: vec![entity(1, None, 1.0, 0.0), entity(8478402, None, 2.0, 0.0)] },
+            Frame { time_stamp: 2.0, entities: vec![entity(1, None, 2.0, 0.0)] },
+            Frame { time_stamp: 3.0, entities: vec![entity(1, None, 3.0, 0.0), entity(8478402, None, 50.0, 0.0)] },
+            Frame { time_stamp: 4.0, entities: vec![entity(1, None, 4.0, 0.0), entity(8478402, None, 51.0, 0.0)] },
+        ];
+        let kinematics = compute_kinematics(&frames, &options(1.0, 1));
+        assert_close(kinematics[1][1].speed_ft_s, 20.0);
+        assert_close(kinematics[3][1].speed_ft_s, 10.0);
+        assert_close(kinematics[3][1].distance_ft, 0.0);
+        assert_close(kinematics[4][1].distance_ft, 1.0);
+
+        let summaries = summarize_kinematics(&frames, &kinematics);
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].entity_id, 1);
+        assert_close(summaries[0].distance_ft, 4.0);
+        assert_close(summaries[0].max_speed_ft_s, 10.0);
+        assert_eq!(summaries[1].entity_id, 8478402);
+        assert_close(summaries[1].distance_ft, 3.0);
+        assert_close(summaries[1].max_speed_ft_s, 20.0);
+    }
+
+    // smoothing evens out a jittery path
+    #[test]
+    fn smooth_moving_average() {
+        let positions = vec![(0.0, 0.0), (1.0, 3.0), (2.0, 0.0), (3.0, 3.0), (4.0, 0.0)];
+        let smoothed = smooth(&positions, 3);
+        assert_eq!(smoothed[0], (0.0, 0.0));
+        assert_eq!(smoothed[1], (1.0, 1.0));
+        assert_eq!(smoothed[2], (2.0, 2.0));
+        assert_eq!(smoothed[4], (4.0, 0.0));
+        assert_eq!(smooth(&positions, 1), positions);
+    }
+}
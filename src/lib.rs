@@ -0,0 +1,31 @@
+pub mod animate;
+pub mod api_calls;
+pub mod archive;
+pub mod cross_check;
+pub mod export;
+pub mod heatmap;
+pub mod json_file;
+pub mod kinematics;
+pub mod merge;
+pub mod mirror;
+pub mod normalize;
+pub mod possession;
+pub mod reader;
+pub mod render;
+pub mod replay;
+pub mod resample;
+pub mod retry;
+pub mod schema;
+pub mod screen;
+pub mod server;
+pub mod shot;
+pub mod strength;
+pub mod sync;
+pub mod team_shape;
+#[cfg(test)]
+mod test_util;
+pub mod tracking;
+pub mod validate;
+pub mod viewer;
+pub mod watch;
+pub mod zone_entry;
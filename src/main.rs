@@ -1,66 +1,134 @@
-use crate::api_calls::{GameExportData, GoalDetails};
-use crate::api_calls::{
-    get_game_ids_period, get_pbp_data, parse_goal_data,
+use pulling_goal_loc_data::api_calls::{GameExportData, GoalDetails, LandingResponse, PbpResponse, TrackingOutcome};
+use pulling_goal_loc_data::api_calls::{
+    get_game_ids_period, get_pbp_data, get_pbp_text, parse_goal_data, get_roster, parse_roster, RosterPlayer,
     save_goal_data, week_or_shorter_period::WeekOrShorterPeriod, get_game_info,
     extract_export_game_data
 };
+use pulling_goal_loc_data::animate::{
+    animate, AnimateOptions, AnimationFormat, DEFAULT_FPS, DEFAULT_TRAIL_SECONDS, DEFAULT_WIDTH,
+};
+use pulling_goal_loc_data::archive::{archive_date_folder, archive_game_folder, ArchiveMode};
+use pulling_goal_loc_data::cross_check::{cross_check, CrossCheckMode};
+use pulling_goal_loc_data::export::{export, ExportOptions};
+use pulling_goal_loc_data::heatmap::{heatmap, HeatmapOptions, StrengthFilter, DEFAULT_BIN_FT};
+use pulling_goal_loc_data::merge::{merge_game_data, Source, PROVENANCE_FILENAME};
+use pulling_goal_loc_data::mirror::season_of;
+use pulling_goal_loc_data::reader::{GameRef, OutputReader, PBP_BOXSCORE_FILENAME, ROSTER_FILENAME};
+use pulling_goal_loc_data::render::{render, RenderOptions, DEFAULT_SCALE};
+use pulling_goal_loc_data::replay::replay;
+use pulling_goal_loc_data::retry::{RetryQueue, RETRY_QUEUE_FILENAME};
+use pulling_goal_loc_data::sync::{game_status, GameStatus, SyncState, SYNC_STATE_FILENAME};
+use pulling_goal_loc_data::validate::{goal_count_issue, validate, Issue, Report, Severity};
+use pulling_goal_loc_data::viewer::write_viewers;
+use pulling_goal_loc_data::watch::{
+    is_final, WatchState, WatchedGame, DEFAULT_CORRECTION_HOURS, DEFAULT_POLL_SECONDS, WATCH_STATE_FILENAME,
+};
+use pulling_goal_loc_data::resample::{Interpolation, ResampleOptions};
+use pulling_goal_loc_data::schema::{recording, start_recording, SchemaLog, SCHEMA_LOG_FILENAME};
+use pulling_goal_loc_data::server::{serve, DEFAULT_ADDRESS};
 use anyhow::{anyhow, Context, Result};
-use chrono::{DateTime, Days, FixedOffset, NaiveDate, TimeDelta};
-use clap::{Parser};
-use reqwest;
+use chrono::{DateTime, Days, FixedOffset, Local, NaiveDate, TimeDelta, Utc};
+use clap::{Parser, Subcommand};
 use reqwest::blocking::Client;
 use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
-use serde_json;
-
-mod api_calls;
 
 use std::fmt::Display;
 use std::fs::create_dir_all;
+use std::fs::remove_file;
 use std::fs::File;
+use std::io::ErrorKind;
 use std::io::Write;
 use std::ops::Add;
 use std::path::Path;
 use std::str::FromStr;
 
 fn main() -> Result<()> {
+    let args = Args::parse();
 
-    let client = Client::new();
-    let mut headers = HeaderMap::new();
-    headers.insert("Origin", HeaderValue::from_static("https://www.nhl.com"));
-    headers.insert("Referer", HeaderValue::from_static("https://www.nhl.com"));
-    headers.insert("sec-fetch-mode", HeaderValue::from_static("cors"));
-    headers.insert("Sec-Fetch-Site", HeaderValue::from_static("cross-site"));
-    headers.insert(USER_AGENT, HeaderValue::from_static("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/138.0.0.0 Safari/537.36"));
+    // subcommands work off of data that's already been downloaded
+    if let Some(command) = args.command {
+        return run_command(command);
+    }
 
-    let args = Args::parse();
+    let (client, headers) = nhl_client();
 
     // use the correct mode as specified by the user's arg
     // one of game/dates exists because the program will exit
     // if one of them is not provided
-    match args.mode.game {
+    let options = args.download_options();
+    let output = args.output.expect("No output folder");
+    if args.strict_schema {
+        start_schema_log(&output)?;
+    }
+    let result = match args.mode.game {
         Some(id) => {
             println!("**** Running single game: {id} ****");            
-            run_game(&id, args.output, &client, headers)?;
+            run_game(&id, &output, &client, headers, options).and_then(|game_path| match options.archive {
+                Some(ArchiveMode::Date) => archive_games(&output, &[game_path], ArchiveMode::Date),
+                _ => Ok(()),
+            })
         },
         None => {
             let (start_date, end_date) = args.mode.dates.expect("Invalid dates");
             println!("**** Running period {start_date} to {end_date} ****");
-            run_period(start_date, end_date, args.output, &client, headers)?;
+            run_period(start_date, end_date, &output, &client, headers, options)
         }
-    }
+    };
+    save_schema_log(&output)?;
 
-    Ok(())
+    result
 }
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     #[command(flatten)]
     mode: Mode,
 
     /// folder to save the output to
+    #[arg(long, required = true)]
+    output: Option<String>,
+
+    /// bundle the saved files into one zip archive per game or per date
+    #[arg(long, value_enum)]
+    archive: Option<ArchiveMode>,
+
+    /// log the fields in the NHL's responses to the output folder, to catch
+    /// changes to the API (see `schema-report`)
     #[arg(long)]
-    output: String,
+    strict_schema: bool,
+
+    /// also get each game's goals from the play-by-play endpoint and compare
+    /// them with the landing endpoint's; `fail` skips games that disagree
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "report")]
+    cross_check: Option<CrossCheckMode>,
+
+    /// get each game's goals from both the landing and play-by-play endpoints
+    /// and merge them field by field, preferring the given one's values
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "landing")]
+    merge: Option<Source>,
+}
+
+impl Args {
+    fn download_options(&self) -> DownloadOptions {
+        DownloadOptions {
+            archive: self.archive,
+            cross_check: self.cross_check,
+            merge: self.merge,
+        }
+    }
+}
+
+/// How games get downloaded, shared by the download and `sync`
+#[derive(Debug, Clone, Copy)]
+struct DownloadOptions {
+    archive: Option<ArchiveMode>,
+    cross_check: Option<CrossCheckMode>,
+    merge: Option<Source>,
 }
 
 #[derive(Parser, Debug)]
@@ -77,47 +145,659 @@ struct Mode {
     dates: Option<(NaiveDate, NaiveDate)>,
 }
 
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Export downloaded tracking data as CSV tables
+    Export(ExportArgs),
+    /// Draw a goal on a rink as an SVG image
+    Render(RenderArgs),
+    /// Animate a goal as a GIF or animated PNG
+    Animate(AnimateArgs),
+    /// Play a goal back in the terminal
+    Replay(ReplayArgs),
+    /// Write a self-contained HTML replay viewer for each game
+    Viewer(ViewerArgs),
+    /// Summarize goals across every game in an output folder
+    Aggregate(AggregateArgs),
+    /// Serve downloaded data over a local HTTP API
+    Serve(ServeArgs),
+    /// Keep polling today's games and download goals as they're posted
+    Watch(WatchArgs),
+    /// Download the games missing from an output folder since a date
+    Sync(SyncArgs),
+    /// Try the goals whose tracking data came back empty or invalid again
+    RetryMissing(RetryMissingArgs),
+    /// Check an output folder (or archive) for missing or malformed data
+    Validate(ValidateArgs),
+    /// Summarize the changes to the NHL's responses logged with --strict-schema
+    SchemaReport(SchemaReportArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct AggregateArgs {
+    #[command(subcommand)]
+    command: AggregateCommand,
+}
+
+#[derive(Subcommand, Debug)]
+enum AggregateCommand {
+    /// Bin where goals were shot from, as a CSV grid and an SVG rink
+    Heatmap(HeatmapArgs),
+}
+
+#[derive(clap::Args, Debug)]
+struct ExportArgs {
+    /// output folder (or a single archive) with the downloaded data
+    #[arg(long)]
+    input: String,
+
+    /// folder to save the tables to
+    #[arg(long)]
+    output: String,
+
+    /// only export this game id
+    #[arg(long)]
+    game: Option<u32>,
+
+    /// only export this goal: needs to be a goal's event id
+    #[arg(long)]
+    event: Option<u32>,
+
+    /// convert the coordinates so the scoring team attacks right, center ice
+    /// is the origin and the units are feet
+    #[arg(long)]
+    normalize: bool,
+
+    /// resample the frames to a fixed number of frames per second
+    #[arg(long, value_parser = parse_positive)]
+    resample: Option<f64>,
+
+    /// how to fill in positions when resampling
+    #[arg(long, value_enum, default_value = "linear", requires = "resample")]
+    interpolation: Interpolation,
+
+    /// add speed, acceleration, heading and distance travelled to the frames,
+    /// and summarize them per player per goal
+    #[arg(long)]
+    kinematics: bool,
+
+    /// number of frames to average positions over before computing the
+    /// kinematics; 1 means no smoothing
+    #[arg(long, default_value_t = 1, requires = "kinematics", value_parser = parse_nonzero)]
+    smoothing: usize,
+
+    /// write a timeline of who had the puck, when it was loose, and which
+    /// zone it was in
+    #[arg(long)]
+    possession: bool,
+
+    /// summarize each team's spacing: convex hull area, centroid, distance
+    /// between players and compactness
+    #[arg(long)]
+    team_shape: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct RenderArgs {
+    /// output folder (or a single archive) with the downloaded data
+    #[arg(long)]
+    input: String,
+
+    /// game id of the goal
+    #[arg(long)]
+    game: u32,
+
+    /// event id of the goal
+    #[arg(long)]
+    event: u32,
+
+    /// SVG file to save the image to
+    #[arg(long)]
+    output: String,
+
+    /// size of the image, in pixels per foot of rink
+    #[arg(long, default_value_t = DEFAULT_SCALE, value_parser = parse_positive)]
+    scale: f64,
+}
+
+#[derive(clap::Args, Debug)]
+struct AnimateArgs {
+    /// output folder (or a single archive) with the downloaded data
+    #[arg(long)]
+    input: String,
+
+    /// game id of the goal
+    #[arg(long)]
+    game: u32,
+
+    /// event id of the goal
+    #[arg(long)]
+    event: u32,
+
+    /// file to save the animation to
+    #[arg(long)]
+    output: String,
+
+    /// gif or apng; by default this comes from the output's extension
+    #[arg(long, value_enum)]
+    format: Option<AnimationFormat>,
+
+    /// frames per second
+    #[arg(long, default_value_t = DEFAULT_FPS, value_parser = parse_positive)]
+    fps: f64,
+
+    /// width of the animation in pixels
+    #[arg(long, default_value_t = DEFAULT_WIDTH)]
+    width: u32,
+
+    /// seconds of the puck's path to draw behind it
+    #[arg(long, default_value_t = DEFAULT_TRAIL_SECONDS)]
+    trail: f64,
+}
+
+#[derive(clap::Args, Debug)]
+struct ReplayArgs {
+    /// output folder (or a single archive) with the downloaded data
+    #[arg(long)]
+    input: String,
+
+    /// game id of the goal
+    #[arg(long)]
+    game: u32,
+
+    /// event id of the goal
+    #[arg(long)]
+    event: u32,
+}
+
+#[derive(clap::Args, Debug)]
+struct ViewerArgs {
+    /// output folder (or a single archive) with the downloaded data
+    #[arg(long)]
+    input: String,
+
+    /// folder to save the HTML files to, one per game
+    #[arg(long)]
+    output: String,
+
+    /// only write the viewer for this game id
+    #[arg(long)]
+    game: Option<u32>,
+}
+
+#[derive(clap::Args, Debug)]
+struct HeatmapArgs {
+    /// output folder (or a single archive) with the downloaded data
+    #[arg(long)]
+    input: String,
+
+    /// folder to save the heatmap to
+    #[arg(long)]
+    output: String,
+
+    /// width and height of each bin in feet
+    #[arg(long, default_value_t = DEFAULT_BIN_FT)]
+    bin: f64,
+
+    /// also bin where the passes leading up to each goal came from
+    #[arg(long)]
+    passes: bool,
+
+    /// only count goals scored by this team: needs to be its abbreviation,
+    /// e.g. TOR
+    #[arg(long)]
+    team: Option<String>,
+
+    /// only count shots by this player id, and passes made by them
+    #[arg(long)]
+    player: Option<u32>,
+
+    /// only count goals scored at this strength
+    #[arg(long, value_enum)]
+    strength: Option<StrengthFilter>,
+}
+
+#[derive(clap::Args, Debug)]
+struct ServeArgs {
+    /// output folder (or a single archive) with the downloaded data
+    #[arg(long)]
+    input: String,
+
+    /// address and port to listen on
+    #[arg(long, default_value = DEFAULT_ADDRESS)]
+    address: String,
+
+    /// imitate the NHL's endpoints (landing, play-by-play, boxscore,
+    /// schedule and sprites) instead of serving the local API
+    #[arg(long)]
+    mirror: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct WatchArgs {
+    /// folder to save the output to; the watcher's state is kept there too
+    #[arg(long)]
+    output: String,
+
+    /// seconds between polls
+    #[arg(long, default_value_t = DEFAULT_POLL_SECONDS)]
+    interval: u64,
+
+    /// hours to keep re-checking a finished game for corrections
+    #[arg(long, default_value_t = DEFAULT_CORRECTION_HOURS)]
+    correction_hours: i64,
+
+    /// poll once and exit, e.g. to run from cron
+    #[arg(long)]
+    once: bool,
+
+    /// log the fields in the NHL's responses, like the download does
+    #[arg(long)]
+    strict_schema: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct SyncArgs {
+    /// output folder to bring up to date
+    #[arg(long)]
+    output: String,
+
+    /// first date to sync, in YYYY-MM-DD format; by default the last date
+    /// synced before
+    #[arg(long)]
+    since: Option<NaiveDate>,
+
+    /// last date to sync, in YYYY-MM-DD format; today by default
+    #[arg(long)]
+    until: Option<NaiveDate>,
+
+    /// bundle newly downloaded games into archives, like the download does
+    #[arg(long, value_enum)]
+    archive: Option<ArchiveMode>,
+
+    /// log the fields in the NHL's responses, like the download does
+    #[arg(long)]
+    strict_schema: bool,
+
+    /// compare the landing and play-by-play endpoints, like the download does
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "report")]
+    cross_check: Option<CrossCheckMode>,
+
+    /// merge the landing and play-by-play endpoints, like the download does
+    #[arg(long, value_enum, num_args = 0..=1, default_missing_value = "landing")]
+    merge: Option<Source>,
+}
+
+#[derive(clap::Args, Debug)]
+struct SchemaReportArgs {
+    /// output folder with the schema log, or the log itself
+    path: String,
+
+    /// list every field the downloader doesn't read, not just new ones
+    #[arg(long)]
+    all: bool,
+}
+
+#[derive(clap::Args, Debug)]
+struct RetryMissingArgs {
+    /// output folder with the retry queue
+    #[arg(long)]
+    output: String,
+
+    /// bundle the games into archives once they have no goals left on the
+    /// queue, like the download does
+    #[arg(long, value_enum)]
+    archive: Option<ArchiveMode>,
+}
+
+#[derive(clap::Args, Debug)]
+struct ValidateArgs {
+    /// output folder or archive to check
+    path: String,
+
+    /// skip comparing each game's goal count with the NHL's landing summary,
+    /// which needs the API
+    #[arg(long)]
+    offline: bool,
+
+    /// print the report as JSON, for CI
+    #[arg(long)]
+    json: bool,
+
+    /// fail on warnings as well as errors
+    #[arg(long)]
+    strict: bool,
+}
+
+/// A client for the NHL's API, with the headers the tracking data needs
+fn nhl_client() -> (Client, HeaderMap) {
+    let client = Client::new();
+    let mut headers = HeaderMap::new();
+    headers.insert("Origin", HeaderValue::from_static("https://www.nhl.com"));
+    headers.insert("Referer", HeaderValue::from_static("https://www.nhl.com"));
+    headers.insert("sec-fetch-mode", HeaderValue::from_static("cors"));
+    headers.insert("Sec-Fetch-Site", HeaderValue::from_static("cross-site"));
+    headers.insert(USER_AGENT, HeaderValue::from_static("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/138.0.0.0 Safari/537.36"));
+    (client, headers)
+}
+
+/// Runs a subcommand
+fn run_command(command: Command) -> Result<()> {
+    match command {
+        Command::Export(args) => {
+            let reader = OutputReader::open(&args.input)?;
+            let options = ExportOptions {
+                game_id: args.game,
+                event_id: args.event,
+                normalize: args.normalize,
+                resample: args.resample.map(|hz| ResampleOptions::new(hz, args.interpolation)),
+                kinematics: args.kinematics,
+                smoothing_window: args.smoothing,
+                possession: args.possession,
+                team_shape: args.team_shape,
+            };
+            println!("**** Exporting {} to {} ****", args.input, args.output);
+            export(&reader, &options, &args.output)
+        },
+        Command::Render(args) => {
+            let reader = OutputReader::open(&args.input)?;
+            let options = RenderOptions { scale: args.scale };
+            println!("**** Rendering game {}, goal {} to {} ****", args.game, args.event, args.output);
+            render(&reader, args.game, args.event, &options, &args.output)
+        },
+        Command::Animate(args) => {
+            let reader = OutputReader::open(&args.input)?;
+            let format = args
+                .format
+                .or_else(|| AnimationFormat::from_path(&args.output))
+                .ok_or_else(|| anyhow!("Unable to tell the format of {}: use --format", args.output))?;
+            let options = AnimateOptions {
+                format,
+                fps: args.fps,
+                width: args.width,
+                trail_seconds: args.trail,
+            };
+            println!("**** Animating game {}, goal {} to {} ****", args.game, args.event, args.output);
+            animate(&reader, args.game, args.event, &options, &args.output)
+        },
+        Command::Replay(args) => {
+            let reader = OutputReader::open(&args.input)?;
+            replay(&reader, args.game, args.event)
+        },
+        Command::Viewer(args) => {
+            let reader = OutputReader::open(&args.input)?;
+            println!("**** Writing viewers for {} to {} ****", args.input, args.output);
+            write_viewers(&reader, args.game, &args.output)
+        },
+        Command::Aggregate(AggregateArgs { command: AggregateCommand::Heatmap(args) }) => {
+            if args.bin <= 0.0 {
+                return Err(anyhow!("The bin size needs to be positive: {}", args.bin));
+            }
+            let reader = OutputReader::open(&args.input)?;
+            let options = HeatmapOptions {
+                bin_ft: args.bin,
+                passes: args.passes,
+                team: args.team,
+                player_id: args.player,
+                strength: args.strength,
+            };
+            println!("**** Aggregating a heatmap of {} to {} ****", args.input, args.output);
+            heatmap(&reader, &options, &args.output)
+        },
+        Command::Serve(args) => {
+            let reader = OutputReader::open(&args.input)?;
+            println!("**** Serving {} ****", args.input);
+            serve(&reader, &args.address, args.mirror)
+        },
+        Command::Watch(args) => {
+            let (client, headers) = nhl_client();
+            println!("**** Watching games, saving to {} ****", args.output);
+            if args.strict_schema {
+                start_schema_log(&args.output)?;
+            }
+            run_watch(&args, &client, headers)
+        },
+        Command::Sync(args) => {
+            let (client, headers) = nhl_client();
+            if args.strict_schema {
+                start_schema_log(&args.output)?;
+            }
+            let result = run_sync(&args, &client, headers);
+            save_schema_log(&args.output)?;
+            result
+        },
+        Command::SchemaReport(args) => run_schema_report(&args),
+        Command::Validate(args) => {
+            let reader = OutputReader::open(&args.path)?;
+            run_validate(&args, &reader)
+        },
+        Command::RetryMissing(args) => {
+            let (client, headers) = nhl_client();
+            println!("**** Retrying queued goals in {} ****", args.output);
+            run_retry_missing(&args, &client, headers)
+        },
+    }
+}
+
 /// Saves all goal data for a single game to a specific folder, first by trying
 /// the landing endpoint and then if that fails, trying the play-by-play
 /// endpoint
+/// With per-game archives, the game folder is bundled into an archive once
+/// everything is saved; per-date archives are left to the caller, to make
+/// once all of the date's games are saved.  Returns the path of the game
+/// folder.  With a cross-check mode, both endpoints are
+/// compared first (see `cross_check_game`), and with a merge precedence both
+/// are used (see `run_game_merged`).
 fn run_game<P>(
     game_id: &str,
     output_folder: P, 
     client: &Client,
     headers: HeaderMap,
-) -> Result<()>
+    options: DownloadOptions,
+) -> Result<String>
 where
     P: AsRef<Path> + Display, 
 {
-    match run_game_landing(&game_id.to_string(), &output_folder, client, headers.clone()) {
-        Err(e) => {
-            println!("Error when using landing endpoint for game {}: {}.  Trying play-by-plan endpoint.", game_id, e);
+    // each endpoint is called once, and its response shared by the
+    // cross-check, the merge, the fallback and the roster
+    let landing = get_game_info(game_id, client);
+    let pbp_text = get_pbp_text(client, game_id);
+    let play_by_play = match &pbp_text {
+        Ok(text) => serde_json::from_str::<PbpResponse>(text).map_err(anyhow::Error::from),
+        Err(e) => Err(anyhow!("{}", e)),
+    };
+    if let Some(mode) = options.cross_check {
+        cross_check_game(game_id, landing.as_ref(), play_by_play.as_ref(), mode)?;
+    }
 
-            // try using pbp endpoint instead
-            match run_game_pbp(&game_id.to_string(), &output_folder, client, headers.clone()) {
-                Err(e) => {
-                    Err(anyhow!("Error when using play-by-play endpoint for game {}: {}", game_id, e))
-                },
-                Ok(_) => Ok(())
+    let game_path = if let Some(precedence) = options.merge {
+        run_game_merged(game_id, landing, play_by_play, &output_folder, client, headers.clone(), precedence)?
+    } else {
+        match landing.and_then(|landing_resp| run_game_landing(&landing_resp, &output_folder, client, headers.clone())) {
+            Err(e) => {
+                println!("Error when using landing endpoint for game {}: {}.  Trying play-by-plan endpoint.", game_id, e);
+
+                // try using pbp endpoint instead
+                match play_by_play.and_then(|pbp_info| run_game_pbp(pbp_info, &output_folder, client, headers.clone())) {
+                    Err(e) => {
+                        return Err(anyhow!("Error when using play-by-play endpoint for game {}: {}", game_id, e))
+                    },
+                    Ok(game_path) => game_path
+                }
+            },
+            Ok(game_path) => game_path,
+        }
+    };
+
+    // the roster is only needed to put names to the players, so the game's
+    // data is still good without it
+    let roster = pbp_text.and_then(|text| parse_roster(&text));
+    if let Err(e) = roster.and_then(|roster| save_roster(game_id, &game_path, &roster)) {
+        println!("Error when saving the roster for game {}: {}", game_id, e);
+    }
+
+    if options.archive == Some(ArchiveMode::Game) {
+        archive_games(&output_folder.to_string(), std::slice::from_ref(&game_path), ArchiveMode::Game)?;
+    }
+    Ok(game_path)
+}
+
+/// Bundles saved game folders into archives, one per game or one per date
+/// Games with goals on the retry queue are left in their folders so
+/// `retry-missing` can add to them, as are the other games of their date
+/// with per-date archives.
+fn archive_games(output_folder: &str, game_paths: &[String], mode: ArchiveMode) -> Result<()> {
+    let queue = RetryQueue::load(Path::new(output_folder).join(RETRY_QUEUE_FILENAME))?;
+    match mode {
+        ArchiveMode::Game => {
+            for game_path in game_paths {
+                let game_id: u32 = Path::new(game_path)
+                    .file_name()
+                    .and_then(|name| name.to_str()?.parse().ok())
+                    .ok_or(anyhow!("Invalid game folder: {}", game_path))?;
+                if queue.goals.iter().any(|q| q.game_id == game_id) {
+                    println!("Not archiving game {} yet: it has goals on the retry queue", game_id);
+                    continue;
+                }
+                archive_game_folder(game_path)?;
+            }
+        },
+        ArchiveMode::Date => {
+            let mut date_paths = vec![];
+            for game_path in game_paths {
+                let date_path = Path::new(game_path)
+                    .parent()
+                    .ok_or(anyhow!("Game folder {} has no date folder", game_path))?;
+                if !date_paths.contains(&date_path) {
+                    date_paths.push(date_path);
+                }
+            }
+            for date_path in date_paths {
+                let date = date_path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+                if queue.goals.iter().any(|q| q.game_date == date) {
+                    println!("Not archiving {} yet: it has goals on the retry queue", date);
+                    continue;
+                }
+                archive_date_folder(date_path)?;
             }
         },
-        Ok(_) => Ok(()),
     }
+    Ok(())
 }
+/// Compares a game's goals from the landing and play-by-play endpoints and
+/// prints where they disagree
+/// With `CrossCheckMode::Fail` a disagreement is an error, so the game isn't
+/// saved, and so is a game that can't be read from both endpoints, as it
+/// can't be checked.
+fn cross_check_game(
+    game_id: &str,
+    landing: Result<&LandingResponse, &anyhow::Error>,
+    play_by_play: Result<&PbpResponse, &anyhow::Error>,
+    mode: CrossCheckMode,
+) -> Result<()> {
+    let landing = landing.map_err(|e| anyhow!("{}", e)).and_then(extract_export_game_data);
+    let play_by_play = play_by_play.map(|pbp_info| parse_goal_data(pbp_info.clone()));
+    let (landing, play_by_play) = match (landing, play_by_play) {
+        (Ok(landing), Ok(play_by_play)) => (landing, play_by_play),
+        (Err(e), _) => return unchecked_game(game_id, &e, mode),
+        (_, Err(e)) => return unchecked_game(game_id, e, mode),
+    };
+
+    let mismatches = cross_check(&landing, &play_by_play);
+    if mismatches.is_empty() {
+        return Ok(());
+    }
+    println!("The landing and play-by-play endpoints disagree about game {}:", game_id);
+    for mismatch in &mismatches {
+        println!("  {}", mismatch);
+    }
+    match mode {
+        CrossCheckMode::Report => Ok(()),
+        CrossCheckMode::Fail => Err(anyhow!(
+            "{} differences between the landing and play-by-play endpoints for game {}",
+            mismatches.len(), game_id
+        )),
+    }
+}
+
+/// Reports a game that couldn't be cross-checked: only a warning, unless
+/// with `CrossCheckMode::Fail`
+fn unchecked_game(game_id: &str, e: &anyhow::Error, mode: CrossCheckMode) -> Result<()> {
+    match mode {
+        CrossCheckMode::Report => {
+            println!("Unable to cross-check game {}: {}", game_id, e);
+            Ok(())
+        },
+        CrossCheckMode::Fail => Err(anyhow!("Unable to cross-check game {}: {}", game_id, e)),
+    }
+}
+
+/// Saves all goal data for a single game to a specific folder using the
+/// responses from both the landing and play-by-play endpoints, merged field
+/// by field, and records where each field came from
+/// Only one of the endpoints needs to have the game.  Returns the path of the
+/// game folder.
+fn run_game_merged<P>(
+    game_id: &str,
+    landing: Result<LandingResponse>,
+    play_by_play: Result<PbpResponse>,
+    output_folder: P,
+    client: &Client,
+    headers: HeaderMap,
+    precedence: Source,
+) -> Result<String>
+where
+    P: AsRef<Path> + Display,
+{
+    let landing = landing.and_then(|landing_resp| {
+        let game_data = extract_export_game_data(&landing_resp)?;
+        Ok((game_data, landing_resp.season, landing_resp.gameDate))
+    });
+    if let Err(e) = &landing {
+        println!("Error when using landing endpoint for game {}: {}", game_id, e);
+    }
+    let play_by_play = play_by_play.map(|pbp_info| {
+        let (season, game_date) = (pbp_info.season, pbp_info.gameDate.clone());
+        (parse_goal_data(pbp_info), season, game_date)
+    });
+    if let Err(e) = &play_by_play {
+        println!("Error when using play-by-play endpoint for game {}: {}", game_id, e);
+    }
+
+    let (season, game_date) = match (&landing, &play_by_play) {
+        (Ok((_, season, game_date)), _) | (_, Ok((_, season, game_date))) => (*season, game_date.clone()),
+        _ => return Err(anyhow!("Unable to get game {} from either endpoint", game_id)),
+    };
+    let game_date = NaiveDate::parse_from_str(&game_date, "%Y-%m-%d")?;
+    let game_id_int: u32 = game_id.parse().with_context(|| format!("Invalid game id: {}", game_id))?;
+    let (game_data, provenance) = merge_game_data(
+        landing.as_ref().ok().map(|(game_data, _, _)| game_data),
+        play_by_play.as_ref().ok().map(|(game_data, _, _)| game_data),
+        precedence,
+    )
+    .ok_or(anyhow!("Unable to get game {} from either endpoint", game_id))?;
+
+    let game_path = make_game_folder(&output_folder, &game_date, game_id_int)?;
+    save_goals(&game_data.goals, season, game_id_int, &output_folder.to_string(), &game_date, client, headers);
+    save_game_data(&game_data, &game_path, season, game_id_int)?;
+    let provenance_path = format!("{}/{}", game_path, PROVENANCE_FILENAME);
+    std::fs::write(&provenance_path, serde_json::to_string(&provenance)?)
+        .with_context(|| format!("Failed to write {}", provenance_path))?;
+    Ok(game_path)
+}
+
 /// Saves all goal data for a single game to a specific folder using the 
-/// game landing endpoint
+/// game landing endpoint's response
+/// Returns the path of the game folder
 fn run_game_landing<P>(
-    game_id: &str,
+    landing_resp: &LandingResponse,
     output_folder: P, 
     client: &Client,
     headers: HeaderMap,
-) -> Result<()>
+) -> Result<String>
 where
     P: AsRef<Path> + Display, 
 {
-    // pull the info using the landing endpoint
-    let landing_resp = get_game_info(game_id, client)?;
-
     // make a folder for the game if necessary
     // the game folder will live in a folder for a specific day
     // let game_time_utc = format!("{} +0000", &game.startTimeUTC);
@@ -128,15 +808,15 @@ where
         }
     };
 
-    let game_path = make_game_folder(output_folder, landing_resp.season, &game_date, landing_resp.id)?;
+    let game_path = make_game_folder(&output_folder, &game_date, landing_resp.id)?;
 
-    let game_data = extract_export_game_data(&landing_resp)?;
-    save_goals(&game_data.goals, landing_resp.season, landing_resp.id, &game_path, client, headers);
+    let game_data = extract_export_game_data(landing_resp)?;
+    save_goals(&game_data.goals, landing_resp.season, landing_resp.id, &output_folder.to_string(), &game_date, client, headers);
 
     // save other game info, like pbp and boxscore info, together in
     // one file
     save_game_data(&game_data, &game_path, landing_resp.season, landing_resp.id)?;
-    Ok(())
+    Ok(game_path)
 }
 
 /// Saves all the goal JSON's for several days
@@ -146,6 +826,7 @@ fn run_period<P>(
     output_folder: P,
     client: &Client,
     headers: HeaderMap,
+    options: DownloadOptions,
 ) -> Result<()> 
 where
     P: AsRef<Path> + Display, 
@@ -182,66 +863,477 @@ where
                 println!("Invalid period: {}", e);
                 start_date = start_date
                     .checked_add_days(Days::new(NUM_DAYS_IN_WK))
-                    .expect(&format!("Error when adding days to {}.  Skipping period.", start_date));
+                    .unwrap_or_else(|| panic!("Error when adding days to {}.  Skipping period.", start_date));
                 continue;
             }
         };
 
         // get the game ids for the week
-        let game_rslt = get_game_ids_period(&client, &period);
+        let game_rslt = get_game_ids_period(client, &period);
         let games = match game_rslt {
             Ok(game_ids) => game_ids,
             Err(e) => {
                 println!("Error retrieving game ids from the schedule API endpoint: {}.  Skipping period: {}", e, &period);
                 start_date = start_date
                     .checked_add_days(Days::new(NUM_DAYS_IN_WK))
-                    .expect(&format!("Error when adding days to {}.  Skipping period {}.", start_date, &period));
+                    .unwrap_or_else(|| panic!("Error when adding days to {}.  Skipping period {}.", start_date, &period));
                 continue;
             }
         };
 
+        let mut game_paths = vec![];
         for game in &games {
-            match run_game(&game.id.to_string(), &output_folder, client, headers.clone()) {
-                Err(e) => {
-                    println!("Error when trying to save data for game {}: {}", game.id, e);
-                    continue;
-                },
-                Ok(_) => ()
+            match run_game(&game.id.to_string(), &output_folder, client, headers.clone(), options) {
+                Ok(game_path) => game_paths.push(game_path),
+                Err(e) => println!("Error when trying to save data for game {}: {}", game.id, e),
+            }
+        }
+
+        // every game of the week's dates is saved, so each date is archived
+        // once
+        if options.archive == Some(ArchiveMode::Date) {
+            if let Err(e) = archive_games(&output_folder.to_string(), &game_paths, ArchiveMode::Date) {
+                println!("Error when archiving the games from {}: {}", &period, e);
             }
         }
 
         start_date = start_date
             .checked_add_days(Days::new(NUM_DAYS_IN_WK))
-            .expect(&format!("Error when adding days to {}", start_date));
+            .unwrap_or_else(|| panic!("Error when adding days to {}", start_date));
     }
     Ok(())
 }
 
-/// Saves a game's goal JSON's using the play-by-play endpoint
+/// Saves a game's goal JSON's using the play-by-play endpoint's response
+/// Returns the path of the game folder
 fn run_game_pbp<P>(
-    game_id: &str,
+    pbp_info: PbpResponse,
     output_folder: P, 
     client: &Client,
     headers: HeaderMap,
-) -> Result<()>
+) -> Result<String>
 where
     P: AsRef<Path> + Display, 
 {
     // the play-by-play endpoint has all the info needed to pull goal JSON's
-    let pbp_info = get_pbp_data(client, game_id)?;
     let game_date = NaiveDate::parse_from_str(&pbp_info.gameDate, "%Y-%m-%d")?;
-    let game_path = make_game_folder(output_folder, pbp_info.season, &game_date, pbp_info.id)?;
+    let game_path = make_game_folder(&output_folder, &game_date, pbp_info.id)?;
     let game_id_int = pbp_info.id;
     let season_id = pbp_info.season;
 
     let game_export_data = parse_goal_data(pbp_info);
-    save_goals(&game_export_data.goals, season_id, game_id_int, &game_path, client, headers);
+    save_goals(&game_export_data.goals, season_id, game_id_int, &output_folder.to_string(), &game_date, client, headers);
     save_game_data(&game_export_data, &game_path, season_id, game_id_int)?;
+    Ok(game_path)
+}
+
+/// Polls the schedule for yesterday's and today's games until stopped (or
+/// once with `--once`), downloading goals as their replays are posted and
+/// re-checking finished games for corrections
+/// What's been saved is kept in the output folder's watch state, so a
+/// restarted watcher picks up where it left off.
+fn run_watch(args: &WatchArgs, client: &Client, headers: HeaderMap) -> Result<()> {
+    let state_path = Path::new(&args.output).join(WATCH_STATE_FILENAME);
+    create_dir_all(&args.output).with_context(|| format!("Failed to create {}", args.output))?;
+    let mut state = WatchState::load(&state_path)?;
+    let correction_window = TimeDelta::try_hours(args.correction_hours)
+        .ok_or(anyhow!("Invalid correction window: {} hours", args.correction_hours))?;
+
+    loop {
+        // yesterday too, for games that run past midnight
+        let today = Local::now().date_naive();
+        let yesterday = today.pred_opt().ok_or(anyhow!("Invalid date: {}", today))?;
+        match get_game_ids_period(client, &WeekOrShorterPeriod::try_new(yesterday, today)?) {
+            Ok(games) => {
+                for game in &games {
+                    state.track(game.id, game.gameState.as_deref().unwrap_or("FUT"), Utc::now());
+                }
+            },
+            Err(e) => println!("Error retrieving games from the schedule API endpoint: {}", e),
+        }
+
+        let game_ids: Vec<u32> = state.games.keys().copied().collect();
+        for game_id in game_ids {
+            let game = state.games.get_mut(&game_id).expect("Game missing from watch state");
+            if !game.needs_check(Utc::now(), correction_window) {
+                continue;
+            }
+            if let Err(e) = watch_game(game_id, game, &args.output, client, headers.clone()) {
+                println!("Error when checking game {}: {}", game_id, e);
+            }
+            state.save(&state_path)?;
+        }
+        state.save(&state_path)?;
+        save_schema_log(&args.output)?;
+
+        if args.once {
+            return Ok(());
+        }
+        std::thread::sleep(std::time::Duration::from_secs(args.interval));
+    }
+}
+
+/// Brings an output folder up to date with the schedule: downloads finished
+/// games that are missing, and retries goals whose tracking data is missing
+/// or came back empty
+/// The last date synced is remembered, so without `--since` a sync starts
+/// from there (the day itself included, for games that finished late).
+/// Stale goals are retried for every game in the folder, whatever its date.
+fn run_sync(args: &SyncArgs, client: &Client, headers: HeaderMap) -> Result<()> {
+    create_dir_all(&args.output).with_context(|| format!("Failed to create {}", args.output))?;
+    let state_path = Path::new(&args.output).join(SYNC_STATE_FILENAME);
+    let mut state = SyncState::load(&state_path)?;
+    let since = args
+        .since
+        .or_else(|| state.last_synced_date())
+        .ok_or(anyhow!("No --since given, and {} hasn't been synced before", args.output))?;
+    let until = args.until.unwrap_or_else(|| Local::now().date_naive());
+    if until < since {
+        return Err(anyhow!("The last date to sync, {}, comes before the first, {}", until, since));
+    }
+    println!("**** Syncing {} from {} to {} ****", args.output, since, until);
+
+    let reader = OutputReader::open(&args.output)?;
+    let mut num_retried = 0;
+    for game in reader.games() {
+        if let GameStatus::Stale(event_ids) = game_status(&reader, game.game_id) {
+            if retry_goals(&reader, &args.output, &game, &event_ids, client, headers.clone())? {
+                num_retried += 1;
+            }
+        }
+    }
+
+    let options = DownloadOptions {
+        archive: args.archive,
+        cross_check: args.cross_check,
+        merge: args.merge,
+    };
+    let mut num_downloaded = 0;
+    let mut start_date = since;
+    while start_date <= until {
+        let end_date = start_date
+            .checked_add_days(Days::new(6))
+            .map_or(until, |d| d.min(until));
+        let games = match get_game_ids_period(client, &WeekOrShorterPeriod::try_new(start_date, end_date)?) {
+            Ok(games) => games,
+            Err(e) => {
+                return Err(anyhow!("Error retrieving game ids from the schedule API endpoint for {} to {}: {}", start_date, end_date, e));
+            }
+        };
+
+        let mut game_paths = vec![];
+        for game in &games {
+            if game_status(&reader, game.id) != GameStatus::Missing {
+                continue;
+            }
+            if game.gameState.as_deref().is_some_and(|state| !is_final(state)) {
+                println!("Skipping game {}: it isn't over", game.id);
+                continue;
+            }
+            println!("Downloading game {}", game.id);
+            match run_game(&game.id.to_string(), &args.output, client, headers.clone(), options) {
+                Ok(game_path) => {
+                    game_paths.push(game_path);
+                    num_downloaded += 1;
+                },
+                Err(e) => println!("Error when trying to save data for game {}: {}", game.id, e),
+            }
+        }
+        if options.archive == Some(ArchiveMode::Date) {
+            if let Err(e) = archive_games(&args.output, &game_paths, ArchiveMode::Date) {
+                println!("Error when archiving the games from {} to {}: {}", start_date, end_date, e);
+            }
+        }
+
+        start_date = end_date
+            .checked_add_days(Days::new(1))
+            .ok_or(anyhow!("Invalid date after {}", end_date))?;
+    }
+
+    state.last_synced = Some(until.to_string());
+    state.save(&state_path)?;
+    println!("Downloaded {} games and retried goals for {} games", num_downloaded, num_retried);
+    Ok(())
+}
+
+/// Downloads the tracking data for some of a saved game's goals again
+/// Games in archives are skipped, as they can't be added to.  Returns whether
+/// the goals were tried.
+fn retry_goals(
+    reader: &OutputReader,
+    output_folder: &str,
+    game: &GameRef,
+    event_ids: &[u32],
+    client: &Client,
+    headers: HeaderMap,
+) -> Result<bool> {
+    if reader.game_folder(game.game_id).is_none() {
+        println!("Skipping game {}: goals {:?} need downloading again, but it's archived", game.game_id, event_ids);
+        return Ok(false);
+    }
+    let goals: Vec<GoalDetails> = reader
+        .read_game_data(game.game_id)?
+        .goals
+        .into_iter()
+        .filter(|g| event_ids.contains(&g.event_id))
+        .collect();
+    println!("Retrying goals {:?} for game {}", event_ids, game.game_id);
+    save_goals(&goals, season_of(game.game_id), game.game_id, output_folder, &game.date, client, headers);
+    Ok(true)
+}
+
+/// Tries the goals on the output folder's retry queue again
+/// With an archive mode, the games tried are archived once none of their
+/// goals are left on the queue.  Goals whose game is already archived are
+/// left on it.
+fn run_retry_missing(args: &RetryMissingArgs, client: &Client, headers: HeaderMap) -> Result<()> {
+    let queue_path = Path::new(&args.output).join(RETRY_QUEUE_FILENAME);
+    let queue = RetryQueue::load(&queue_path)?;
+    if queue.goals.is_empty() {
+        println!("No goals to retry in {}", args.output);
+        return Ok(());
+    }
+
+    let mut num_saved = 0;
+    let mut game_paths = vec![];
+    for queued in &queue.goals {
+        let game_date = NaiveDate::parse_from_str(&queued.game_date, "%Y-%m-%d")
+            .with_context(|| format!("Invalid date for game {} in the retry queue: {}", queued.game_id, queued.game_date))?;
+        let game_path = Path::new(&args.output).join(&queued.game_date).join(queued.game_id.to_string());
+        if !game_path.is_dir() {
+            println!("Skipping game {}, goal {}: the game folder's missing or archived", queued.game_id, queued.goal.event_id);
+            continue;
+        }
+        println!(
+            "Retrying game {}, goal {} (attempt {}, last: {})",
+            queued.game_id, queued.goal.event_id, queued.attempts + 1, queued.reason
+        );
+        let saved = save_goals(
+            std::slice::from_ref(&queued.goal),
+            queued.season,
+            queued.game_id,
+            &args.output,
+            &game_date,
+            client,
+            headers.clone(),
+        );
+        num_saved += saved.len();
+        let game_path = game_path.display().to_string();
+        if !game_paths.contains(&game_path) {
+            game_paths.push(game_path);
+        }
+    }
+    println!("Saved {} of {} queued goals", num_saved, queue.goals.len());
+
+    if let Some(mode) = args.archive {
+        archive_games(&args.output, &game_paths, mode)?;
+    }
+    Ok(())
+}
+
+/// Prints every issue found in an output folder, as text or as JSON, and
+/// fails if there are any errors (or any warnings, with `--strict`) so CI can
+/// catch them
+/// Goal counts are checked against the landing summary unless `--offline`.
+fn run_validate(args: &ValidateArgs, reader: &OutputReader) -> Result<()> {
+    if !args.json {
+        println!("**** Validating {} ****", args.path);
+    }
+    let mut issues = validate(reader);
+    if args.offline {
+        if !args.json {
+            println!("Skipping the goal count check against the landing summary (--offline)");
+        }
+    } else {
+        let (client, _) = nhl_client();
+        for game in reader.games() {
+            let Ok(game_data) = reader.read_game_data(game.game_id) else {
+                continue; // already an error
+            };
+            let landing = get_game_info(&game.game_id.to_string(), &client)
+                .and_then(|landing_resp| extract_export_game_data(&landing_resp));
+            match landing {
+                Ok(landing) => issues.extend(goal_count_issue(game.game_id, game_data.goals.len(), landing.goals.len())),
+                Err(e) => issues.push(Issue {
+                    severity: Severity::Warning,
+                    game_id: game.game_id,
+                    event_id: None,
+                    message: format!("Unable to get the landing summary: {}", e),
+                }),
+            }
+        }
+        issues.sort_by_key(|i| (i.game_id, i.event_id));
+    }
+
+    let report = Report::new(reader.games().len(), issues);
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+    } else {
+        for issue in &report.issues {
+            println!("{}", issue);
+        }
+        println!("Checked {} games: {} errors, {} warnings", report.games, report.errors, report.warnings);
+    }
+
+    if report.errors > 0 || (args.strict && report.warnings > 0) {
+        return Err(anyhow!("{} failed validation", args.path));
+    }
+    Ok(())
+}
+
+/// Starts logging the fields in the NHL's responses, adding to the output
+/// folder's schema log
+fn start_schema_log(output_folder: &str) -> Result<()> {
+    create_dir_all(output_folder).with_context(|| format!("Failed to create {}", output_folder))?;
+    let log = SchemaLog::load(Path::new(output_folder).join(SCHEMA_LOG_FILENAME))?;
+    start_recording(log);
+    Ok(())
+}
+
+/// Saves the schema log if the fields are being logged, and points out any
+/// drift
+fn save_schema_log(output_folder: &str) -> Result<()> {
+    let Some(log) = recording() else {
+        return Ok(());
+    };
+    log.save(Path::new(output_folder).join(SCHEMA_LOG_FILENAME))?;
+    let (mut num_missing, mut num_new) = (0, 0);
+    for (endpoint, endpoint_log) in &log.endpoints {
+        num_missing += endpoint_log.missing_fields(*endpoint).len();
+        num_new += endpoint_log.new_fields(*endpoint).len();
+    }
+    if num_missing + num_new > 0 {
+        println!(
+            "The NHL's responses have drifted: {} expected fields missing and {} new fields.  See schema-report {}",
+            num_missing, num_new, output_folder
+        );
+    }
+    Ok(())
+}
+
+/// Prints the drift in each endpoint's responses: expected fields that were
+/// missing, and fields that first showed up in the latest run
+fn run_schema_report(args: &SchemaReportArgs) -> Result<()> {
+    let path = Path::new(&args.path);
+    let log_path = if path.is_dir() { path.join(SCHEMA_LOG_FILENAME) } else { path.to_path_buf() };
+    if !log_path.is_file() {
+        return Err(anyhow!("No schema log at {}: download with --strict-schema first", log_path.display()));
+    }
+    let log = SchemaLog::load(&log_path)?;
+    println!("**** Schema drift in {} ****", log_path.display());
+
+    for (endpoint, endpoint_log) in &log.endpoints {
+        println!(
+            "{}: {} responses ({} in the latest run), {} unreadable",
+            endpoint,
+            endpoint_log.responses,
+            endpoint_log.responses - endpoint_log.run_start,
+            endpoint_log.unreadable
+        );
+        for field in endpoint_log.missing_fields(*endpoint) {
+            println!("  missing {}: from {} of {} responses", field.path, field.missing, field.applicable);
+        }
+        let unknown = endpoint_log.unknown_fields(*endpoint);
+        let new = endpoint_log.new_fields(*endpoint);
+        for (path, field) in &new {
+            println!(
+                "  new {}: first in response {} ({}), in {} of {} responses",
+                path, field.first_response, field.first_source, field.count, endpoint_log.responses
+            );
+        }
+        if args.all {
+            for (path, field) in unknown.iter().filter(|u| !new.contains(*u)) {
+                println!("  unused {}: in {} of {} responses", path, field.count, endpoint_log.responses);
+            }
+        } else if unknown.len() > new.len() {
+            println!("  {} other fields the downloader doesn't read (--all to list them)", unknown.len() - new.len());
+        }
+    }
+    Ok(())
+}
+
+/// Checks a game being watched: saves the tracking data for goals with new
+/// replays, and the game's goal details and roster whenever they change
+fn watch_game(game_id: u32, game: &mut WatchedGame, output_folder: &str, client: &Client, headers: HeaderMap) -> Result<()> {
+    let (game_data, season, game_date, game_state) = fetch_game(&game_id.to_string(), client)?;
+    if let Some(game_state) = game_state {
+        game.set_state(&game_state, Utc::now());
+    }
+    let game_path = make_game_folder(output_folder, &game_date, game_id)?;
+
+    let goals: Vec<GoalDetails> = game.goals_to_download(&game_data).into_iter().cloned().collect();
+    for event_id in save_goals(&goals, season, game_id, output_folder, &game_date, client, headers) {
+        println!("Saved game {}, goal {}", game_id, event_id);
+        game.saved_goals.insert(event_id);
+    }
+
+    if game.game_data.as_ref() != Some(&game_data) {
+        if game.game_data.is_some() {
+            println!("Goals changed for game {}: saving the corrected details", game_id);
+        }
+        let stale_goals = game.remove_stale_goals(&game_data);
+        if !stale_goals.is_empty() {
+            remove_goals(game_id, &stale_goals, output_folder, &game_path);
+        }
+        save_game_data(&game_data, &game_path, season, game_id)?;
+        let roster = get_roster(client, &game_id.to_string());
+        if let Err(e) = roster.and_then(|roster| save_roster(&game_id.to_string(), &game_path, &roster)) {
+            println!("Error when saving the roster for game {}: {}", game_id, e);
+        }
+        game.game_data = Some(game_data);
+    }
     Ok(())
 }
 
+/// Removes the tracking files of goals a correction took away, and takes them
+/// off the retry queue so they aren't downloaded again
+fn remove_goals(game_id: u32, event_ids: &[u32], output_folder: &str, game_path: &str) {
+    for event_id in event_ids {
+        match remove_file(Path::new(game_path).join(event_id.to_string())) {
+            Ok(()) => println!("Removed game {}, goal {}: it's no longer one of the game's goals", game_id, event_id),
+            Err(e) if e.kind() == ErrorKind::NotFound => (),
+            Err(e) => println!("Error when removing game {}, goal {}: {}", game_id, event_id, e),
+        }
+    }
+
+    let queue_path = Path::new(output_folder).join(RETRY_QUEUE_FILENAME);
+    let queue = RetryQueue::load(&queue_path).and_then(|mut queue| {
+        let mut removed = false;
+        for event_id in event_ids {
+            removed |= queue.remove(game_id, *event_id);
+        }
+        if removed {
+            queue.save(&queue_path)?;
+        }
+        Ok(())
+    });
+    if let Err(e) = queue {
+        println!("Unable to take game {}'s removed goals off the retry queue: {:#}", game_id, e);
+    }
+}
+
+/// Gets a game's goals, season, date and state, first from the landing
+/// endpoint and then, if that fails, from the play-by-play endpoint
+fn fetch_game(game_id: &str, client: &Client) -> Result<(GameExportData, u32, NaiveDate, Option<String>)> {
+    match get_game_info(game_id, client) {
+        Ok(landing_resp) => {
+            let game_date = NaiveDate::parse_from_str(&landing_resp.gameDate, "%Y-%m-%d")?;
+            let game_data = extract_export_game_data(&landing_resp)?;
+            Ok((game_data, landing_resp.season, game_date, landing_resp.gameState))
+        },
+        Err(e) => {
+            println!("Error when using landing endpoint for game {}: {}.  Trying play-by-play endpoint.", game_id, e);
+            let pbp_info = get_pbp_data(client, game_id)?;
+            let game_date = NaiveDate::parse_from_str(&pbp_info.gameDate, "%Y-%m-%d")?;
+            let (season, game_state) = (pbp_info.season, pbp_info.gameState.clone());
+            Ok((parse_goal_data(pbp_info), season, game_date, game_state))
+        },
+    }
+}
+
 /// Adjusts a game's start time in UTC to the local time
 /// By using the venue UTC offset given in the schedule API's response
+#[cfg_attr(not(test), allow(dead_code))]
 fn adjust_to_local_time(
     start_time_utc: DateTime<FixedOffset>,
     venue_offset: &str,
@@ -269,7 +1361,7 @@ fn parse_date_args(arg: &str) -> Result<(NaiveDate, NaiveDate)> {
         if dates.len() > 1 {
             return Err(anyhow!("Received too many arguments"));
         }
-        let date = NaiveDate::parse_from_str(&date, "%Y-%m-%d")?;
+        let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")?;
         dates.push(date);
     }
 
@@ -283,11 +1375,28 @@ fn parse_date_args(arg: &str) -> Result<(NaiveDate, NaiveDate)> {
     Ok((dates[0], dates[1]))
 }
 
+/// Reads in a number that has to be above zero, like a frame rate
+fn parse_positive(arg: &str) -> Result<f64> {
+    let value: f64 = arg.parse()?;
+    if !(value > 0.0 && value.is_finite()) {
+        return Err(anyhow!("{} isn't above zero", arg));
+    }
+    Ok(value)
+}
+
+/// Reads in a count that has to be at least 1, like a smoothing window
+fn parse_nonzero(arg: &str) -> Result<usize> {
+    let value: usize = arg.parse()?;
+    if value == 0 {
+        return Err(anyhow!("{} isn't at least 1", arg));
+    }
+    Ok(value)
+}
+
 /// Makes the folder for the game info, if not already made
 /// The game folder has the path: folder/game_date/game_id
 fn make_game_folder<P>(
     folder: P,
-    season: u32,
     game_date: &NaiveDate,
     game_id: u32,
 ) -> Result<String> 
@@ -308,27 +1417,67 @@ where
 }
 
 /// Goes through the goals for a game and save the tracking JSON's
-fn save_goals(goals: &[GoalDetails], season: u32, game_id: u32, game_path: &str, client: &Client, headers: HeaderMap) {
+/// Goals whose tracking data comes back empty or invalid go on the output
+/// folder's retry queue (see `retry-missing`), and goals that are saved come
+/// off it.  Returns the event ids of the goals saved.
+fn save_goals(
+    goals: &[GoalDetails],
+    season: u32,
+    game_id: u32,
+    output_folder: &str,
+    game_date: &NaiveDate,
+    client: &Client,
+    headers: HeaderMap,
+) -> Vec<u32> {
+    let queue_path = Path::new(output_folder).join(RETRY_QUEUE_FILENAME);
+    let mut queue = match RetryQueue::load(&queue_path) {
+        Ok(queue) => Some(queue),
+        Err(e) => {
+            println!("Unable to read the retry queue, so failed goals won't be queued: {:#}", e);
+            None
+        }
+    };
+    let mut queue_changed = false;
+
+    let mut saved = vec![];
     for goal in goals {
         // make path for the goal
-        let output_path = format!("{}/{}", game_path, goal.event_id);
-        match save_goal_data(client, headers.clone(), season, game_id, goal, &output_path) {
+        let output_path = format!("{}/{}/{}/{}", output_folder, game_date, game_id, goal.event_id);
+        let reason = match save_goal_data(client, headers.clone(), season, game_id, goal, &output_path) {
+            Ok(TrackingOutcome::Saved) => {
+                saved.push(goal.event_id);
+                if let Some(queue) = queue.as_mut() {
+                    queue_changed |= queue.remove(game_id, goal.event_id);
+                }
+                continue;
+            },
+            Ok(TrackingOutcome::Empty) => String::from("empty response"),
+            Ok(TrackingOutcome::Invalid(e)) => format!("invalid tracking data: {}", e),
             Err(e) => {
                 println!(
                     "Error saving goal data for game {}, goal {}, output filepath {}: {}",
                     game_id, goal.event_id, output_path, e
                 );
-            }
-            Ok(_) => (),
+                e.to_string()
+            },
+        };
+        if let Some(queue) = queue.as_mut() {
+            queue.push(game_id, season, game_date, goal, &reason, Utc::now());
+            queue_changed = true;
+        }
+    }
+
+    if let (Some(queue), true) = (queue, queue_changed) {
+        if let Err(e) = queue.save(&queue_path) {
+            println!("Unable to save the retry queue: {:#}", e);
         }
     }
+    saved
 }
 
 /// Saves the additional necessary game info: goal event id's, home defending
 /// sides for goals, scoring team id's, and the home team id
 fn save_game_data(game_data: &GameExportData, game_path: &str, season: u32, game_id: u32) -> Result<()> {
-    const PBP_BOXSCORE_FILENAME: &str = "pbp_boxscore.json";
-
     let pbp_boxscore_string = serde_json::to_string(&game_data)?;
     let pbp_boxscore_path = format!("{}/{}", game_path, PBP_BOXSCORE_FILENAME);
     let mut pbp_boxscore_file = File::create(pbp_boxscore_path).with_context(|| {
@@ -341,6 +1490,18 @@ fn save_game_data(game_data: &GameExportData, game_path: &str, season: u32, game
     Ok(())
 }
 
+/// Saves the players dressed for a game, so the player ids in the tracking
+/// data can be matched to names
+fn save_roster(game_id: &str, game_path: &str, roster: &[RosterPlayer]) -> Result<()> {
+    let roster_string = serde_json::to_string(roster)?;
+    let roster_path = format!("{}/{}", game_path, ROSTER_FILENAME);
+    let mut roster_file = File::create(roster_path).with_context(|| {
+        format!("Failed to write roster for game id: {}", game_id)
+    })?;
+    write!(roster_file, "{}", roster_string)?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -410,7 +1571,7 @@ mod tests {
             DateTime::parse_from_str("1934-12-31T14:00:00Z +0000", "%Y-%m-%dT%H:%M:%SZ %z")
                 .unwrap();
         let offset = "";
-        let adjusted_date = adjust_to_local_time(start_time_utc, offset).unwrap();
+        let _adjusted_date = adjust_to_local_time(start_time_utc, offset).unwrap();
     }
 
     ////////////////////////////
@@ -439,27 +1600,42 @@ mod tests {
     #[test]
     #[should_panic]
     fn parse_date_args_invalid_end_before() {
-        let (start_date, end_date) = parse_date_args("1982-04-30::1982-02-22").unwrap();
+        let (_start_date, _end_date) = parse_date_args("1982-04-30::1982-02-22").unwrap();
     }
 
     // invalid dates: invalid format
     #[test]
     #[should_panic]
     fn parse_date_args_invalid_format() {
-        let (start_date, end_date) = parse_date_args("1982-02-01_to_1982-02-22").unwrap();
+        let (_start_date, _end_date) = parse_date_args("1982-02-01_to_1982-02-22").unwrap();
     }
 
     // invalid dates: too many dates
     #[test]
     #[should_panic]
     fn parse_date_args_too_many() {
-        let (start_date, end_date) = parse_date_args("1982-04-30::1982-05-22::1982-06-22").unwrap();
+        let (_start_date, _end_date) = parse_date_args("1982-04-30::1982-05-22::1982-06-22").unwrap();
     }
 
     // invalid dates: date that doesn't exist
     #[test]
     #[should_panic]
     fn parse_date_args_invalid_date() {
-        let (start_date, end_date) = parse_date_args("1983-04-29::1983-04-31").unwrap();
+        let (_start_date, _end_date) = parse_date_args("1983-04-29::1983-04-31").unwrap();
+    }
+
+    #[test]
+    fn parse_positive_rejects_zero_and_below() {
+        assert_eq!(parse_positive("29.97").unwrap(), 29.97);
+        assert!(parse_positive("0").is_err());
+        assert!(parse_positive("-10").is_err());
+        assert!(parse_positive("NaN").is_err());
+    }
+
+    #[test]
+    fn parse_nonzero_rejects_zero() {
+        assert_eq!(parse_nonzero("5").unwrap(), 5);
+        assert!(parse_nonzero("0").is_err());
+        assert!(parse_nonzero("-1").is_err());
     }
 }
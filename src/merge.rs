@@ -0,0 +1,167 @@
+use clap::ValueEnum;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api_calls::{GameExportData, GoalDetails};
+
+/// Name of the file in a game folder recording where each goal field came
+/// from, for games downloaded with `--merge`
+pub const PROVENANCE_FILENAME: &str = "provenance.json";
+
+/// An endpoint goal details come from
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize, ValueEnum)]
+#[serde(rename_all = "kebab-case")]
+pub enum Source {
+    Landing,
+    PlayByPlay,
+}
+
+/// Where each field of a merged game came from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameProvenance {
+    /// the endpoint whose values won when both had one
+    pub precedence: Source,
+    pub home_team_id: Source,
+    pub goals: Vec<GoalProvenance>,
+}
+
+/// Where each field of a merged goal came from: None for optional fields
+/// neither endpoint had
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GoalProvenance {
+    pub event_id: u32,
+    pub ppt_replay_url: Option<Source>,
+    pub scoring_team_id: Source,
+    pub home_team_defending_side: Source,
+    pub situation_code: Option<Source>,
+}
+
+/// Builds a game's goal details from both endpoints, field by field
+/// Every goal either endpoint has is kept, in event id order.  Where both have
+/// a value, `precedence` picks it; optional fields like the replay URL come
+/// from the other endpoint when the preferred one is missing them.  Returns
+/// None if neither endpoint has the game.
+pub fn merge_game_data(
+    landing: Option<&GameExportData>,
+    play_by_play: Option<&GameExportData>,
+    precedence: Source,
+) -> Option<(GameExportData, GameProvenance)> {
+    let sources = match precedence {
+        Source::Landing => [(Source::Landing, landing), (Source::PlayByPlay, play_by_play)],
+        Source::PlayByPlay => [(Source::PlayByPlay, play_by_play), (Source::Landing, landing)],
+    };
+    // the endpoints that have the game, in order of precedence
+    let available: Vec<(Source, &GameExportData)> = sources
+        .into_iter()
+        .filter_map(|(source, game_data)| game_data.map(|d| (source, d)))
+        .collect();
+    let (home_team_source, preferred) = *available.first()?;
+
+    let mut event_ids: Vec<u32> = available
+        .iter()
+        .flat_map(|(_, game_data)| game_data.goals.iter().map(|g| g.event_id))
+        .collect();
+    event_ids.sort();
+    event_ids.dedup();
+
+    let mut goals = vec![];
+    let mut provenance = vec![];
+    for event_id in event_ids {
+        let versions: Vec<(Source, &GoalDetails)> = available
+            .iter()
+            .filter_map(|(source, game_data)| {
+                game_data.goals.iter().find(|g| g.event_id == event_id).map(|g| (*source, g))
+            })
+            .collect();
+        let (source, goal) = versions[0];
+        let first_some = |field: fn(&GoalDetails) -> &Option<String>| {
+            versions
+                .iter()
+                .find_map(|(source, g)| field(g).as_ref().map(|value| (value.clone(), *source)))
+        };
+        let ppt_replay_url = first_some(|g| &g.ppt_replay_url);
+        let situation_code = first_some(|g| &g.situation_code);
+
+        provenance.push(GoalProvenance {
+            event_id,
+            ppt_replay_url: ppt_replay_url.as_ref().map(|(_, source)| *source),
+            scoring_team_id: source,
+            home_team_defending_side: source,
+            situation_code: situation_code.as_ref().map(|(_, source)| *source),
+        });
+        goals.push(GoalDetails {
+            event_id,
+            ppt_replay_url: ppt_replay_url.map(|(value, _)| value),
+            scoring_team_id: goal.scoring_team_id,
+            home_team_defending_side: goal.home_team_defending_side,
+            situation_code: situation_code.map(|(value, _)| value),
+        });
+    }
+
+    let game_data = GameExportData { goals, home_team_id: preferred.home_team_id };
+    let provenance = GameProvenance { precedence, home_team_id: home_team_source, goals: provenance };
+    Some((game_data, provenance))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::api_calls::IceSide;
+
+    fn goal(event_id: u32, ppt_replay_url: Option<&str>, home_team_defending_side: IceSide) -> GoalDetails {
+        GoalDetails {
+            event_id,
+            ppt_replay_url: ppt_replay_url.map(String::from),
+            scoring_team_id: 1,
+            home_team_defending_side,
+            situation_code: Some(String::from("1551")),
+        }
+    }
+
+    #[test]
+    fn merge_fills_in_missing_fields() {
+        let landing = GameExportData {
+            goals: vec![goal(700, None, IceSide::Left), goal(663, Some("landing/663"), IceSide::Left)],
+            home_team_id: 1,
+        };
+        let play_by_play = GameExportData {
+            goals: vec![goal(663, Some("pbp/663"), IceSide::Right), goal(700, Some("pbp/700"), IceSide::Right), goal(710, None, IceSide::Right)],
+            home_team_id: 1,
+        };
+
+        let (game_data, provenance) = merge_game_data(Some(&landing), Some(&play_by_play), Source::Landing).unwrap();
+        let urls: Vec<Option<&str>> = game_data.goals.iter().map(|g| g.ppt_replay_url.as_deref()).collect();
+        assert_eq!(urls, vec![Some("landing/663"), Some("pbp/700"), None]);
+        assert_eq!(game_data.goals[1].home_team_defending_side, IceSide::Left);
+        assert_eq!(game_data.goals[2].home_team_defending_side, IceSide::Right);
+        assert_eq!(
+            provenance.goals[1],
+            GoalProvenance {
+                event_id: 700,
+                ppt_replay_url: Some(Source::PlayByPlay),
+                scoring_team_id: Source::Landing,
+                home_team_defending_side: Source::Landing,
+                situation_code: Some(Source::Landing),
+            }
+        );
+        assert_eq!(provenance.goals[2].ppt_replay_url, None);
+        assert_eq!(provenance.goals[2].scoring_team_id, Source::PlayByPlay);
+
+        // play-by-play first
+        let (game_data, provenance) = merge_game_data(Some(&landing), Some(&play_by_play), Source::PlayByPlay).unwrap();
+        assert_eq!(game_data.goals[0].ppt_replay_url.as_deref(), Some("pbp/663"));
+        assert_eq!(game_data.goals[0].home_team_defending_side, IceSide::Right);
+        assert_eq!(provenance.home_team_id, Source::PlayByPlay);
+    }
+
+    #[test]
+    fn merge_with_one_endpoint() {
+        let play_by_play = GameExportData { goals: vec![goal(663, None, IceSide::Left)], home_team_id: 1 };
+        let (game_data, provenance) = merge_game_data(None, Some(&play_by_play), Source::Landing).unwrap();
+        assert_eq!(game_data, play_by_play);
+        assert_eq!(provenance.home_team_id, Source::PlayByPlay);
+        assert_eq!(provenance.precedence, Source::Landing);
+        assert_eq!(merge_game_data(None, None, Source::Landing), None);
+    }
+}
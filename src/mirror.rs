@@ -0,0 +1,336 @@
+use chrono::{Days, NaiveDate};
+
+use serde_json::{json, Value};
+
+use crate::api_calls::{GameExportData, IceSide, RosterPlayer};
+use crate::reader::OutputReader;
+use crate::server::ApiResponse;
+
+/// Days in a schedule response, starting from the date asked for
+const SCHEDULE_DAYS: u64 = 7;
+
+/// Answers a request for one of the NHL's URLs from downloaded data
+/// The responses are rebuilt from what was saved, so they have the fields
+/// this crate reads (and a few neighbouring ones) rather than everything the
+/// NHL sends:
+///
+/// - `/v1/schedule/{date}`: the downloaded games in the week from `date`
+/// - `/v1/gamecenter/{game}/landing`, `/play-by-play` and `/boxscore`: the
+///   game's teams and goals, and its roster in the play-by-play
+/// - `/sprites/{season}/{game}/ev{event}.json`: the tracking data as it was
+///   downloaded
+///
+/// Replay URLs point back at `base_url`, so following them stays on the
+/// mirror.
+pub fn handle_mirror_request(reader: &OutputReader, base_url: &str, url: &str) -> ApiResponse {
+    let path = url.split_once('?').map_or(url, |(path, _)| path);
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    match segments[..] {
+        ["v1", "schedule", date] => match date.parse::<NaiveDate>() {
+            Ok(date) => ApiResponse::json(&schedule(reader, date)),
+            Err(_) => ApiResponse::error(400, "The date needs to be in YYYY-MM-DD format"),
+        },
+        ["v1", "gamecenter", game_id, endpoint @ ("landing" | "play-by-play" | "boxscore")] => {
+            let Some(game) = game_id.parse::<u32>().ok().and_then(|id| MirrorGame::read(reader, id)) else {
+                return ApiResponse::error(404, &format!("No game {}", game_id));
+            };
+            let response = match endpoint {
+                "landing" => game.landing(base_url),
+                "play-by-play" => game.play_by_play(base_url),
+                _ => game.boxscore(),
+            };
+            ApiResponse::json(&response)
+        }
+        ["sprites", season, game_id, file] => {
+            let ids = game_id.parse::<u32>().ok().zip(
+                file.strip_prefix("ev")
+                    .and_then(|f| f.strip_suffix(".json"))
+                    .and_then(|e| e.parse::<u32>().ok()),
+            );
+            match ids {
+                Some((game_id, event_id)) if season.parse() == Ok(season_of(game_id)) => {
+                    match reader.read_tracking(game_id, event_id) {
+                        Ok(tracking) => ApiResponse { status: 200, body: tracking },
+                        Err(e) => ApiResponse::error(404, &format!("{:#}", e)),
+                    }
+                }
+                _ => ApiResponse::error(404, &format!("No tracking data at {}", path)),
+            }
+        }
+        _ => ApiResponse::error(404, &format!("Unknown endpoint: {}", path)),
+    }
+}
+
+/// The season a game was played in, e.g. 20252026 for game 2025020140
+pub fn season_of(game_id: u32) -> u32 {
+    let start_year = game_id / 1_000_000;
+    start_year * 10_000 + start_year + 1
+}
+
+/// The schedule for the week starting on `start_date`
+fn schedule(reader: &OutputReader, start_date: NaiveDate) -> Value {
+    let games = reader.games();
+    let game_week: Vec<Value> = (0..SCHEDULE_DAYS)
+        .filter_map(|i| start_date.checked_add_days(Days::new(i)))
+        .map(|date| {
+            let day_games: Vec<Value> = games
+                .iter()
+                .filter(|g| g.date == date)
+                .map(|g| {
+                    // the start time isn't saved, so every game is in the
+                    // evening, on the right day in North America, and saved
+                    // games are over
+                    json!({
+                        "id": g.game_id,
+                        "season": season_of(g.game_id),
+                        "startTimeUTC": format!("{}T23:00:00Z", date),
+                        "venueUTCOffset": "-05:00",
+                        "gameState": "OFF",
+                    })
+                })
+                .collect();
+            json!({
+                "date": date.to_string(),
+                "numberOfGames": day_games.len(),
+                "games": day_games,
+            })
+        })
+        .collect();
+    json!({ "gameWeek": game_week })
+}
+
+/// What's saved for a game, enough to rebuild the game endpoints
+struct MirrorGame {
+    game_id: u32,
+    date: NaiveDate,
+    data: GameExportData,
+    away_team_id: Option<u16>,
+    roster: Vec<RosterPlayer>,
+}
+
+impl MirrorGame {
+    fn read(reader: &OutputReader, game_id: u32) -> Option<MirrorGame> {
+        let date = reader.games().into_iter().find(|g| g.game_id == game_id)?.date;
+        let data = reader.read_game_data(game_id).ok()?;
+        let roster = reader.read_roster(game_id).unwrap_or_default();
+
+        // the away team isn't saved, but shows up in its goals, the roster
+        // or the tracking data
+        let home_team_id = data.home_team_id;
+        let away_team_id = data
+            .goals
+            .iter()
+            .map(|g| g.scoring_team_id)
+            .chain(roster.iter().map(|p| p.team_id))
+            .find(|&id| id != home_team_id)
+            .or_else(|| {
+                data.goals.iter().find_map(|g| {
+                    let frames = reader.read_frames(game_id, g.event_id).ok()?;
+                    let away_team_id = frames
+                        .iter()
+                        .flat_map(|f| f.players())
+                        .filter_map(|p| p.team_id)
+                        .find(|&id| id != home_team_id);
+                    away_team_id
+                })
+            });
+
+        Some(MirrorGame {
+            game_id,
+            date,
+            data,
+            away_team_id,
+            roster,
+        })
+    }
+
+    fn replay_url(&self, base_url: &str, event_id: u32) -> String {
+        format!("{}/sprites/{}/{}/ev{}.json", base_url, season_of(self.game_id), self.game_id, event_id)
+    }
+
+    fn teams(&self) -> (Value, Value) {
+        (
+            json!({ "id": self.data.home_team_id }),
+            // 0 for a game where the away team never shows up
+            json!({ "id": self.away_team_id.unwrap_or(0) }),
+        )
+    }
+
+    fn landing(&self, base_url: &str) -> Value {
+        let (home_team, away_team) = self.teams();
+        let goals: Vec<Value> = self
+            .data
+            .goals
+            .iter()
+            .map(|g| {
+                json!({
+                    "eventId": g.event_id,
+                    "pptReplayUrl": self.replay_url(base_url, g.event_id),
+                    "homeTeamDefendingSide": side(g.home_team_defending_side),
+                    "isHome": g.scoring_team_id == self.data.home_team_id,
+                    "situationCode": g.situation_code,
+                })
+            })
+            .collect();
+        json!({
+            "id": self.game_id,
+            "season": season_of(self.game_id),
+            "gameDate": self.date.to_string(),
+            "gameState": "OFF",
+            "homeTeam": home_team,
+            "awayTeam": away_team,
+            // the period of each goal isn't saved, and shootout goals never
+            // are, so they all go in one regulation period
+            "summary": {
+                "scoring": [{ "periodDescriptor": { "periodType": "REG" }, "goals": goals }],
+            },
+        })
+    }
+
+    fn play_by_play(&self, base_url: &str) -> Value {
+        let (home_team, away_team) = self.teams();
+        let plays: Vec<Value> = self
+            .data
+            .goals
+            .iter()
+            .map(|g| {
+                json!({
+                    "eventId": g.event_id,
+                    "typeDescKey": "goal",
+                    "homeTeamDefendingSide": side(g.home_team_defending_side),
+                    "pptReplayUrl": self.replay_url(base_url, g.event_id),
+                    "situationCode": g.situation_code,
+                    "details": { "eventOwnerTeamId": g.scoring_team_id },
+                    "periodDescriptor": { "periodType": "REG" },
+                })
+            })
+            .collect();
+        let roster_spots: Vec<Value> = self
+            .roster
+            .iter()
+            .map(|p| {
+                json!({
+                    "teamId": p.team_id,
+                    "playerId": p.player_id,
+                    "firstName": { "default": p.first_name },
+                    "lastName": { "default": p.last_name },
+                    "sweaterNumber": p.sweater_number,
+                    "positionCode": p.position_code,
+                })
+            })
+            .collect();
+        json!({
+            "id": self.game_id,
+            "season": season_of(self.game_id),
+            "gameDate": self.date.to_string(),
+            "gameState": "OFF",
+            "homeTeam": home_team,
+            "awayTeam": away_team,
+            "plays": plays,
+            "rosterSpots": roster_spots,
+        })
+    }
+
+    fn boxscore(&self) -> Value {
+        let (home_team, away_team) = self.teams();
+        json!({
+            "id": self.game_id,
+            "season": season_of(self.game_id),
+            "gameDate": self.date.to_string(),
+            "gameState": "OFF",
+            "homeTeam": home_team,
+            "awayTeam": away_team,
+        })
+    }
+}
+
+/// A side of the ice the way the NHL writes it
+fn side(side: IceSide) -> &'static str {
+    match side {
+        IceSide::Left => "left",
+        IceSide::Right => "right",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::api_calls::{extract_export_game_data, parse_goal_data, parse_roster, LandingResponse, PbpResponse};
+    use crate::reader::{PBP_BOXSCORE_FILENAME, ROSTER_FILENAME};
+    use crate::test_util::{temp_folder, write_goal_details};
+
+    use std::fs::{read_to_string, write};
+    use std::path::PathBuf;
+
+    const BASE_URL: &str = "http://127.0.0.1:8080";
+
+    fn output_folder(name: &str) -> PathBuf {
+        let root = temp_folder(&format!("mirror_{}", name));
+        let game_path = root.join("2025-10-26").join("2025020140");
+        write_goal_details(&game_path, &[663]);
+        // plus a power play goal by the away team
+        let details_path = game_path.join(PBP_BOXSCORE_FILENAME);
+        let mut details: Value = serde_json::from_str(&read_to_string(&details_path).unwrap()).unwrap();
+        details["goals"].as_array_mut().unwrap().push(json!({
+            "event_id": 700,
+            "ppt_replay_url": null,
+            "scoring_team_id": 21,
+            "home_team_defending_side": "Right",
+            "situation_code": "1541",
+        }));
+        write(&details_path, details.to_string()).unwrap();
+        write(
+            game_path.join(ROSTER_FILENAME),
+            r#"[{"player_id":8478401,"team_id":1,"first_name":"Jack","last_name":"Hughes","sweater_number":86,"position_code":"C"}]"#,
+        )
+        .unwrap();
+        write(game_path.join("663"), r#"[{"timeStamp":100,"onIce":{}}]"#).unwrap();
+        root
+    }
+
+    // what the downloader gets from the mirror is what was saved, apart from
+    // the replay URLs
+    #[test]
+    fn game_endpoints_round_trip() {
+        let reader = OutputReader::open(output_folder("game")).unwrap();
+        let mut saved = reader.read_game_data(2025020140).unwrap();
+        for goal in &mut saved.goals {
+            goal.ppt_replay_url = Some(format!("{}/sprites/20252026/2025020140/ev{}.json", BASE_URL, goal.event_id));
+        }
+
+        let landing = handle_mirror_request(&reader, BASE_URL, "/v1/gamecenter/2025020140/landing");
+        assert_eq!(landing.status, 200);
+        let landing: LandingResponse = serde_json::from_str(&landing.body).unwrap();
+        assert_eq!((landing.season, landing.gameDate.as_str()), (20252026, "2025-10-26"));
+        assert_eq!(extract_export_game_data(&landing).unwrap(), saved);
+
+        let pbp = handle_mirror_request(&reader, BASE_URL, "/v1/gamecenter/2025020140/play-by-play");
+        let roster = parse_roster(&pbp.body).unwrap();
+        assert_eq!(roster, reader.read_roster(2025020140).unwrap());
+        let pbp: PbpResponse = serde_json::from_str(&pbp.body).unwrap();
+        assert_eq!(parse_goal_data(pbp), saved);
+
+        assert_eq!(handle_mirror_request(&reader, BASE_URL, "/v1/gamecenter/2025020141/landing").status, 404);
+    }
+
+    #[test]
+    fn schedule_and_sprites() {
+        let reader = OutputReader::open(output_folder("schedule")).unwrap();
+
+        let schedule = handle_mirror_request(&reader, BASE_URL, "/v1/schedule/2025-10-24");
+        let schedule: Value = serde_json::from_str(&schedule.body).unwrap();
+        let week = schedule["gameWeek"].as_array().unwrap();
+        assert_eq!(week.len(), 7);
+        assert_eq!(week[2]["date"], "2025-10-26");
+        assert_eq!(week[2]["games"][0]["id"], 2025020140);
+        assert_eq!(week[2]["games"][0]["season"], 20252026);
+        assert_eq!(week[3]["games"].as_array().unwrap().len(), 0);
+
+        let sprite = handle_mirror_request(&reader, BASE_URL, "/sprites/20252026/2025020140/ev663.json");
+        assert_eq!(sprite.body, r#"[{"timeStamp":100,"onIce":{}}]"#);
+        assert_eq!(handle_mirror_request(&reader, BASE_URL, "/sprites/20242025/2025020140/ev663.json").status, 404);
+        assert_eq!(handle_mirror_request(&reader, BASE_URL, "/sprites/20252026/2025020140/ev700.json").status, 404);
+    }
+}
@@ -0,0 +1,132 @@
+use crate::api_calls::{GoalDetails, IceSide};
+use crate::tracking::Frame;
+
+/// The sprite coordinates are 12 units per foot, on a 200 ft x 85 ft rink
+pub const SPRITE_UNITS_PER_FOOT: f64 = 12.0;
+pub const SPRITE_CENTER_X: f64 = 1200.0;
+pub const SPRITE_CENTER_Y: f64 = 510.0;
+
+pub const RINK_LENGTH_FT: f64 = 200.0;
+pub const RINK_WIDTH_FT: f64 = 85.0;
+
+/// Lines and nets in the canonical frame of reference, where the attacking
+/// net is on the right
+pub const GOAL_LINE_X_FT: f64 = 89.0;
+pub const BLUE_LINE_X_FT: f64 = 25.0;
+pub const NET_HALF_WIDTH_FT: f64 = 3.0;
+pub const NET_DEPTH_FT: f64 = 40.0 / 12.0;
+
+/// Whether the scoring team was attacking the net on the right side of the
+/// sprite coordinates when the goal was scored
+/// The home team attacks the net opposite the side it defends, and the away
+/// team attacks the net the home team defends.
+pub fn scoring_team_attacks_right(goal: &GoalDetails, home_team_id: u16) -> bool {
+    let home_attacks_right = goal.home_team_defending_side == IceSide::Left;
+    if goal.scoring_team_id == home_team_id {
+        home_attacks_right
+    } else {
+        !home_attacks_right
+    }
+}
+
+/// Converts a goal's frames into a canonical frame of reference:
+/// - the scoring team attacks the net on the right (positive x)
+/// - center ice is at the origin
+/// - units are feet, with positive y towards the top of the rink
+///
+/// When the scoring team attacked the left net, the rink is rotated 180
+/// degrees rather than mirrored, so each team's left and right stay the same.
+pub fn normalize_frames(frames: &[Frame], goal: &GoalDetails, home_team_id: u16) -> Vec<Frame> {
+    let flip = if scoring_team_attacks_right(goal, home_team_id) {
+        1.0
+    } else {
+        -1.0
+    };
+
+    frames
+        .iter()
+        .map(|frame| {
+            let mut frame = frame.clone();
+            for e in &mut frame.entities {
+                let (x, y) = to_feet(e.x, e.y);
+                e.x = flip * x;
+                e.y = flip * y;
+            }
+            frame
+        })
+        .collect()
+}
+
+/// Converts a point in sprite coordinates to feet from center ice
+/// The sprite's y axis points down, so it gets flipped to point up.
+pub fn to_feet(x: f64, y: f64) -> (f64, f64) {
+    (
+        (x - SPRITE_CENTER_X) / SPRITE_UNITS_PER_FOOT,
+        (SPRITE_CENTER_Y - y) / SPRITE_UNITS_PER_FOOT,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::tracking::Entity;
+
+    fn goal(scoring_team_id: u16, home_team_defending_side: IceSide) -> GoalDetails {
+        GoalDetails {
+            event_id: 663,
+            ppt_replay_url: None,
+            scoring_team_id,
+            home_team_defending_side,
+            situation_code: None,
+        }
+    }
+
+    fn puck_frame(x: f64, y: f64) -> Frame {
+        Frame {
+            time_stamp: 0.0,
+            entities: vec![Entity {
+                id: 1,
+                player_id: None,
+                team_id: None,
+                team_abbrev: None,
+                sweater_number: None,
+                x,
+                y,
+                interpolated: false,
+            }],
+        }
+    }
+
+    #[test]
+    fn scoring_team_attacks_right_all_cases() {
+        // home team scores while defending the left net
+        assert!(scoring_team_attacks_right(&goal(1, IceSide::Left), 1));
+        // home team scores while defending the right net
+        assert!(!scoring_team_attacks_right(&goal(1, IceSide::Right), 1));
+        // away team scores while the home team defends the left net
+        assert!(!scoring_team_attacks_right(&goal(2, IceSide::Left), 1));
+        // away team scores while the home team defends the right net
+        assert!(scoring_team_attacks_right(&goal(2, IceSide::Right), 1));
+    }
+
+    // already attacking right: only the units and origin change
+    #[test]
+    fn normalize_frames_no_flip() {
+        let frames = vec![puck_frame(2268.0, 510.0), puck_frame(1200.0, 0.0)];
+        let normalized = normalize_frames(&frames, &goal(1, IceSide::Left), 1);
+        let puck_0 = normalized[0].puck().unwrap();
+        assert_eq!((puck_0.x, puck_0.y), (89.0, 0.0));
+        let puck_1 = normalized[1].puck().unwrap();
+        assert_eq!((puck_1.x, puck_1.y), (0.0, 42.5));
+    }
+
+    // attacking left: the rink gets rotated so the goal is on the right
+    #[test]
+    fn normalize_frames_flip() {
+        let frames = vec![puck_frame(132.0, 450.0)];
+        let normalized = normalize_frames(&frames, &goal(2, IceSide::Left), 1);
+        let puck = normalized[0].puck().unwrap();
+        assert_eq!((puck.x, puck.y), (89.0, -5.0));
+    }
+}
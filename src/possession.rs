@@ -0,0 +1,390 @@
+use serde::Serialize;
+
+use crate::normalize::BLUE_LINE_X_FT;
+use crate::tracking::{Entity, Frame};
+
+/// How close (in feet) a player has to be to the puck to be in possession of
+/// it
+pub const POSSESSION_RADIUS_FT: f64 = 5.0;
+
+/// How many frames in a row a player has to be closest to the puck to count
+/// as having possession, so players skating past a loose puck don't count
+pub const MIN_POSSESSION_FRAMES: usize = 2;
+
+/// A player nearest the puck over a run of consecutive frames
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Possession {
+    /// player id of the player with the puck, as in the roster
+    pub player_id: u32,
+    pub team_id: Option<u16>,
+    pub start_frame: usize,
+    /// last frame of the possession, inclusive
+    pub end_frame: usize,
+}
+
+/// Where the puck is, from the scoring team's point of view
+/// In the canonical frame of reference the scoring team attacks the net on
+/// the right, so the offensive zone is past the blue line at positive x.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Zone {
+    Offensive,
+    Neutral,
+    Defensive,
+}
+
+impl Zone {
+    /// The zone an x coordinate (in feet, normalized) falls in
+    pub fn from_x(x: f64) -> Zone {
+        if x > BLUE_LINE_X_FT {
+            Zone::Offensive
+        } else if x < -BLUE_LINE_X_FT {
+            Zone::Defensive
+        } else {
+            Zone::Neutral
+        }
+    }
+}
+
+/// A stretch of consecutive frames with the puck in one zone, and either
+/// controlled by the same player or loose
+#[derive(Debug, Clone, PartialEq)]
+pub struct PossessionSegment {
+    /// player id of the player with the puck, None while it's loose
+    pub player_id: Option<u32>,
+    pub team_id: Option<u16>,
+    pub start_frame: usize,
+    /// last frame of the segment, inclusive
+    pub end_frame: usize,
+    pub start_seconds: f64,
+    pub end_seconds: f64,
+    pub zone: Zone,
+    /// how sure the call is, from 0 to 1 (see `frame_confidence`)
+    pub confidence: f64,
+}
+
+impl PossessionSegment {
+    pub fn is_loose(&self) -> bool {
+        self.player_id.is_none()
+    }
+}
+
+/// A pass between two players on the same team, in the frames' coordinates
+/// The passer and receiver are by player id, as in the roster.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Pass {
+    pub passer_id: u32,
+    pub receiver_id: u32,
+    pub team_id: Option<u16>,
+    /// last frame the passer had the puck
+    pub start_frame: usize,
+    /// first frame the receiver had the puck
+    pub end_frame: usize,
+    pub start_seconds: f64,
+    pub end_seconds: f64,
+    pub start_x: f64,
+    pub start_y: f64,
+    pub end_x: f64,
+    pub end_y: f64,
+    /// straight line distance from the start to the end
+    pub distance_ft: f64,
+}
+
+/// Finds the player in possession of the puck in each frame: the player
+/// nearest the puck within `POSSESSION_RADIUS_FT` of it
+/// The frames need to be in feet (see `normalize_frames`).  Players are by
+/// their player id, not their entity id.  Frames where the puck is loose or
+/// not tracked get None.
+pub fn possession_by_frame(frames: &[Frame]) -> Vec<Option<(u32, Option<u16>)>> {
+    frames
+        .iter()
+        .map(|frame| {
+            let (player, distance) = *nearest_players(frame)?.first()?;
+            if distance > POSSESSION_RADIUS_FT {
+                return None;
+            }
+            Some((player.player_id?, player.team_id))
+        })
+        .collect()
+}
+
+/// Groups the frames into possessions, dropping any shorter than
+/// `MIN_POSSESSION_FRAMES`
+pub fn find_possessions(frames: &[Frame]) -> Vec<Possession> {
+    let mut possessions: Vec<Possession> = vec![];
+    let mut current: Option<Possession> = None;
+
+    for (i, carrier) in possession_by_frame(frames).into_iter().enumerate() {
+        match (current.as_mut(), carrier) {
+            (Some(p), Some((player_id, _))) if p.player_id == player_id && p.end_frame + 1 == i => {
+                p.end_frame = i;
+            }
+            (_, carrier) => {
+                possessions.extend(current.take());
+                current = carrier.map(|(player_id, team_id)| Possession {
+                    player_id,
+                    team_id,
+                    start_frame: i,
+                    end_frame: i,
+                });
+            }
+        }
+    }
+    possessions.extend(current);
+    possessions.retain(|p| p.end_frame - p.start_frame + 1 >= MIN_POSSESSION_FRAMES);
+    possessions
+}
+
+/// Splits the frames into a timeline of possession segments
+/// Every frame with the puck tracked is in exactly one segment: either a
+/// player's possession (see `find_possessions`) or the puck being loose.  A
+/// new segment starts whenever the player with the puck or the zone changes,
+/// and frames without the puck end the segment they're in.  The frames need
+/// to be normalized (see `normalize_frames`) for the zones to be right.
+pub fn possession_timeline(frames: &[Frame]) -> Vec<PossessionSegment> {
+    let first = match frames.first() {
+        Some(first) => first,
+        None => return vec![],
+    };
+    let mut carriers: Vec<Option<(u32, Option<u16>)>> = vec![None; frames.len()];
+    for p in find_possessions(frames) {
+        for carrier in &mut carriers[p.start_frame..=p.end_frame] {
+            *carrier = Some((p.player_id, p.team_id));
+        }
+    }
+
+    // segments along with the number of frames in them, to average the
+    // confidence at the end
+    let mut segments: Vec<(PossessionSegment, usize)> = vec![];
+    for (i, frame) in frames.iter().enumerate() {
+        let puck = match frame.puck() {
+            Some(puck) => puck,
+            None => continue,
+        };
+        let carrier = carriers[i];
+        let zone = Zone::from_x(puck.x);
+        let confidence = frame_confidence(frame, carrier.map(|(player_id, _)| player_id));
+        let seconds = frame.seconds_since(first);
+
+        match segments.last_mut() {
+            Some((segment, count))
+                if segment.end_frame + 1 == i
+                    && segment.player_id == carrier.map(|(player_id, _)| player_id)
+                    && segment.zone == zone =>
+            {
+                segment.end_frame = i;
+                segment.end_seconds = seconds;
+                segment.confidence += confidence;
+                *count += 1;
+            }
+            _ => segments.push((
+                PossessionSegment {
+                    player_id: carrier.map(|(player_id, _)| player_id),
+                    team_id: carrier.and_then(|(_, team_id)| team_id),
+                    start_frame: i,
+                    end_frame: i,
+                    start_seconds: seconds,
+                    end_seconds: seconds,
+                    zone,
+                    confidence,
+                },
+                1,
+            )),
+        }
+    }
+
+    segments
+        .into_iter()
+        .map(|(mut segment, count)| {
+            segment.confidence /= count as f64;
+            segment
+        })
+        .collect()
+}
+
+/// How sure the possession call for a frame is, from 0 to 1
+/// With a player in possession, half of it is how close they are to the puck
+/// and half is how much closer they are than the next nearest player, both
+/// out of `POSSESSION_RADIUS_FT`.  With the puck loose, it's how far the
+/// nearest player is, out of twice the radius: a player just outside the
+/// radius, or one inside it too briefly to count, makes for a shaky call.
+fn frame_confidence(frame: &Frame, carrier: Option<u32>) -> f64 {
+    let nearest = match nearest_players(frame) {
+        Some(nearest) => nearest,
+        None => return 0.0,
+    };
+    let runner_up = nearest.get(1).map(|(_, d)| *d);
+    match (carrier, nearest.first().copied()) {
+        (Some(carrier), Some((player, distance))) if player.player_id == Some(carrier) => {
+            let closeness = 1.0 - distance / POSSESSION_RADIUS_FT;
+            let margin = runner_up.map_or(1.0, |d| (d - distance) / POSSESSION_RADIUS_FT);
+            0.5 * closeness.clamp(0.0, 1.0) + 0.5 * margin.clamp(0.0, 1.0)
+        }
+        // a carrier kept over from the possession runs, but not the nearest
+        // player in this frame
+        (Some(_), _) => 0.0,
+        (None, Some((_, distance))) => (distance / (2.0 * POSSESSION_RADIUS_FT)).min(1.0),
+        (None, None) => 1.0,
+    }
+}
+
+/// The players in a frame with their distances from the puck, nearest first
+/// Returns None if the puck isn't tracked in the frame.
+fn nearest_players(frame: &Frame) -> Option<Vec<(&Entity, f64)>> {
+    let puck = frame.puck()?;
+    let mut distances: Vec<(&Entity, f64)> = frame
+        .players()
+        .map(|p| (p, (p.x - puck.x).hypot(p.y - puck.y)))
+        .collect();
+    distances.sort_by(|a, b| a.1.total_cmp(&b.1));
+    Some(distances)
+}
+
+/// Finds the passes between possessions: one player's possession followed by
+/// a teammate's, with no one else having the puck in between
+pub fn detect_passes(frames: &[Frame], possessions: &[Possession]) -> Vec<Pass> {
+    let first = match frames.first() {
+        Some(first) => first,
+        None => return vec![],
+    };
+    possessions
+        .windows(2)
+        .filter(|w| w[0].team_id.is_some() && w[0].team_id == w[1].team_id && w[0].player_id != w[1].player_id)
+        .filter_map(|w| {
+            let (passer, receiver) = (w[0], w[1]);
+            let start = frames[passer.end_frame].puck()?;
+            let end = frames[receiver.start_frame].puck()?;
+            Some(Pass {
+                passer_id: passer.player_id,
+                receiver_id: receiver.player_id,
+                team_id: passer.team_id,
+                start_frame: passer.end_frame,
+                end_frame: receiver.start_frame,
+                start_seconds: frames[passer.end_frame].seconds_since(first),
+                end_seconds: frames[receiver.start_frame].seconds_since(first),
+                start_x: start.x,
+                start_y: start.y,
+                end_x: end.x,
+                end_y: end.y,
+                distance_ft: (end.x - start.x).hypot(end.y - start.y),
+            })
+        })
+        .collect()
+}
+
+/// Reconstructs the passes leading up to a goal: the scoring team's passes
+/// since it last gained possession, up to the frame the shot was released
+pub fn goal_pass_chain(frames: &[Frame], scoring_team_id: u16, release_frame: usize) -> Vec<Pass> {
+    let end = release_frame.min(frames.len().saturating_sub(1));
+    let possessions: Vec<Possession> = find_possessions(frames)
+        .into_iter()
+        .filter(|p| p.start_frame <= end)
+        .collect();
+
+    // only the possessions since the other team last had the puck
+    let start = possessions
+        .iter()
+        .rposition(|p| p.team_id != Some(scoring_team_id))
+        .map_or(0, |i| i + 1);
+    detect_passes(frames, &possessions[start..])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::{entity, player};
+
+    /// The puck goes from an opponent (3) to player 10, who passes to
+    /// teammate 20 at frame 6, who passes to 30 (player id 8478402) at frame
+    /// 12, with the puck loose while it's travelling
+    fn passing_frames() -> Vec<Frame> {
+        (0..16)
+            .map(|i| {
+                let puck = match i {
+                    0..=2 => (0.0, 0.0),
+                    3..=5 => (20.0, 0.0),
+                    6..=9 => (20.0 + 5.0 * (i - 5) as f64, 5.0 * (i - 5) as f64),
+                    _ => (40.0, 20.0 - 5.0 * (i - 11).clamp(0, 4) as f64),
+                };
+                Frame {
+                    time_stamp: i as f64,
+                    entities: vec![
+                        entity(1, None, puck.0, puck.1),
+                        entity(3, Some(15), 0.0, 1.0),
+                        entity(10, Some(22), 20.0, 1.0),
+                        entity(20, Some(22), 40.0, 21.0),
+                        player(30, 8478402, Some(22), 40.0, 1.0),
+                    ],
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn find_possessions_runs() {
+        let possessions = find_possessions(&passing_frames());
+        let carriers: Vec<(u32, usize, usize)> = possessions
+            .iter()
+            .map(|p| (p.player_id, p.start_frame, p.end_frame))
+            .collect();
+        assert_eq!(carriers, vec![(3, 0, 2), (10, 3, 5), (20, 9, 11), (8478402, 14, 15)]);
+    }
+
+    #[test]
+    fn goal_pass_chain_passes() {
+        let frames = passing_frames();
+        let chain = goal_pass_chain(&frames, 22, 15);
+        assert_eq!(chain.len(), 2);
+        assert_eq!((chain[0].passer_id, chain[0].receiver_id), (10, 20));
+        assert_eq!((chain[0].start_frame, chain[0].end_frame), (5, 9));
+        assert_eq!((chain[0].start_x, chain[0].start_y), (20.0, 0.0));
+        assert_eq!((chain[0].end_x, chain[0].end_y), (40.0, 20.0));
+        assert!((chain[0].end_seconds - 0.9).abs() < 1e-9);
+        assert!((chain[0].distance_ft - 20.0_f64.hypot(20.0)).abs() < 1e-9);
+        assert_eq!((chain[1].passer_id, chain[1].receiver_id), (20, 8478402));
+
+        // stopping before the second pass is received
+        assert_eq!(goal_pass_chain(&frames, 22, 12).len(), 1);
+        // the other team never passed
+        assert!(goal_pass_chain(&frames, 15, 15).is_empty());
+    }
+
+    #[test]
+    fn possession_timeline_segments() {
+        let timeline = possession_timeline(&passing_frames());
+        let segments: Vec<(Option<u32>, usize, usize, Zone)> = timeline
+            .iter()
+            .map(|s| (s.player_id, s.start_frame, s.end_frame, s.zone))
+            .collect();
+        assert_eq!(
+            segments,
+            vec![
+                (Some(3), 0, 2, Zone::Neutral),
+                (Some(10), 3, 5, Zone::Neutral),
+                (None, 6, 6, Zone::Neutral),
+                (None, 7, 8, Zone::Offensive),
+                (Some(20), 9, 11, Zone::Offensive),
+                (None, 12, 13, Zone::Offensive),
+                (Some(8478402), 14, 15, Zone::Offensive),
+            ]
+        );
+        assert_eq!(timeline[1].team_id, Some(22));
+        assert!(timeline[2].is_loose());
+        assert!((timeline[4].start_seconds - 0.9).abs() < 1e-9);
+
+        // 10 is 1 ft from the puck, with 20 over 20 ft away
+        assert!((timeline[1].confidence - 0.9).abs() < 1e-9);
+        for segment in &timeline {
+            assert!((0.0..=1.0).contains(&segment.confidence));
+        }
+    }
+
+    #[test]
+    fn zone_from_x() {
+        assert_eq!(Zone::from_x(60.0), Zone::Offensive);
+        assert_eq!(Zone::from_x(0.0), Zone::Neutral);
+        assert_eq!(Zone::from_x(-25.0), Zone::Neutral);
+        assert_eq!(Zone::from_x(-60.0), Zone::Defensive);
+    }
+}
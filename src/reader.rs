@@ -0,0 +1,255 @@
+use anyhow::{anyhow, Context, Result};
+
+use chrono::NaiveDate;
+
+use std::collections::BTreeMap;
+use std::fs::{read_dir, read_to_string};
+use std::path::{Path, PathBuf};
+
+use crate::api_calls::{GameExportData, RosterPlayer};
+use crate::archive::{open_archive, read_entry, read_index, ARCHIVE_EXTENSION};
+use crate::tracking::{parse_tracking, Frame};
+
+/// Name of the file with the extra game info saved alongside the tracking
+/// JSON's
+pub const PBP_BOXSCORE_FILENAME: &str = "pbp_boxscore.json";
+
+/// Name of the file with the players dressed for the game
+pub const ROSTER_FILENAME: &str = "roster.json";
+
+/// A game that's been downloaded to an output folder
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameRef {
+    pub game_id: u32,
+    pub date: NaiveDate,
+}
+
+/// Where a game's files live: either a plain game folder, or inside an
+/// archive with every entry name starting with `prefix`
+#[derive(Debug, Clone, PartialEq)]
+enum GameLocation {
+    Folder(PathBuf),
+    Archive { path: PathBuf, prefix: String },
+}
+
+/// Reads the data saved by the downloader
+/// The input can be an output folder (folder/game_date/game_id, with any of
+/// the game or date folders bundled into archives), or a single archive.
+#[derive(Debug)]
+pub struct OutputReader {
+    games: BTreeMap<u32, (NaiveDate, GameLocation)>,
+}
+
+impl OutputReader {
+    pub fn open<P>(input: P) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let input = input.as_ref();
+        let mut reader = OutputReader { games: BTreeMap::new() };
+
+        if is_archive(input) {
+            reader.add_archive(input)?;
+        } else if input.is_dir() {
+            for entry in read_dir(input)
+                .with_context(|| format!("Failed to read folder {}", input.display()))?
+            {
+                let path = entry?.path();
+                if path.is_dir() {
+                    reader.add_date_folder(&path)?;
+                } else if is_archive(&path) {
+                    reader.add_archive(&path)?;
+                }
+            }
+        } else {
+            return Err(anyhow!("{} is not a folder or an archive", input.display()));
+        }
+        Ok(reader)
+    }
+
+    /// All the games that were found, in game id order
+    pub fn games(&self) -> Vec<GameRef> {
+        self.games
+            .iter()
+            .map(|(game_id, (date, _))| GameRef { game_id: *game_id, date: *date })
+            .collect()
+    }
+
+    /// Reads the goal details and home team saved for a game
+    pub fn read_game_data(&self, game_id: u32) -> Result<GameExportData> {
+        let game_data = self
+            .read_file(game_id, PBP_BOXSCORE_FILENAME)?
+            .ok_or(anyhow!("No {} for game {}", PBP_BOXSCORE_FILENAME, game_id))?;
+        serde_json::from_str(&game_data)
+            .with_context(|| format!("Invalid {} for game {}", PBP_BOXSCORE_FILENAME, game_id))
+    }
+
+    /// Reads the roster saved for a game
+    /// Returns an empty roster for games downloaded before rosters were saved.
+    pub fn read_roster(&self, game_id: u32) -> Result<Vec<RosterPlayer>> {
+        match self.read_file(game_id, ROSTER_FILENAME)? {
+            Some(roster) => serde_json::from_str(&roster)
+                .with_context(|| format!("Invalid {} for game {}", ROSTER_FILENAME, game_id)),
+            None => Ok(vec![]),
+        }
+    }
+
+    /// Reads the raw tracking JSON for a goal
+    pub fn read_tracking(&self, game_id: u32, event_id: u32) -> Result<String> {
+        self.read_file(game_id, &event_id.to_string())?
+            .ok_or(anyhow!("No tracking data for game {}, goal {}", game_id, event_id))
+    }
+
+    /// Reads and parses the tracking data for a goal
+    pub fn read_frames(&self, game_id: u32, event_id: u32) -> Result<Vec<Frame>> {
+        parse_tracking(&self.read_tracking(game_id, event_id)?)
+            .with_context(|| format!("Invalid tracking data for game {}, goal {}", game_id, event_id))
+    }
+
+    /// The folder a game is saved in
+    /// Returns None for a game inside an archive, or one that isn't in the
+    /// output.
+    pub fn game_folder(&self, game_id: u32) -> Option<&Path> {
+        match self.games.get(&game_id) {
+            Some((_, GameLocation::Folder(folder))) => Some(folder),
+            _ => None,
+        }
+    }
+
+    /// Reads any file saved for a game
+    /// Returns None if the game exists but doesn't have the file.
+    pub fn read_file(&self, game_id: u32, name: &str) -> Result<Option<String>> {
+        let (_, location) = self
+            .games
+            .get(&game_id)
+            .ok_or(anyhow!("Game {} isn't in the output", game_id))?;
+
+        match location {
+            GameLocation::Folder(folder) => {
+                let path = folder.join(name);
+                if path.is_file() {
+                    Ok(Some(read_to_string(&path).with_context(|| {
+                        format!("Failed to read {}", path.display())
+                    })?))
+                } else {
+                    Ok(None)
+                }
+            }
+            GameLocation::Archive { path, prefix } => {
+                let mut archive = open_archive(path)?;
+                read_entry(&mut archive, &format!("{}{}", prefix, name))
+            }
+        }
+    }
+
+    /// Adds the games in a date folder, which holds game folders and/or
+    /// per-game archives
+    fn add_date_folder(&mut self, date_path: &Path) -> Result<()> {
+        let date = match parse_date(date_path) {
+            Some(date) => date,
+            None => return Ok(()), // not a date folder
+        };
+
+        for entry in read_dir(date_path)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                if let Some(game_id) = path.file_name().and_then(|n| n.to_str()).and_then(|n| n.parse().ok()) {
+                    self.games.insert(game_id, (date, GameLocation::Folder(path)));
+                }
+            } else if is_archive(&path) {
+                self.add_archive(&path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the games in an archive, using its index
+    fn add_archive(&mut self, path: &Path) -> Result<()> {
+        let mut archive = open_archive(path)?;
+        let index = read_index(&mut archive)?;
+        let date = NaiveDate::parse_from_str(&index.date, "%Y-%m-%d")
+            .with_context(|| format!("Invalid date in the index of {}", path.display()))?;
+
+        for g in index.games {
+            let location = GameLocation::Archive {
+                path: path.to_path_buf(),
+                prefix: g.prefix,
+            };
+            self.games.insert(g.game_id, (date, location));
+        }
+        Ok(())
+    }
+}
+
+fn is_archive(path: &Path) -> bool {
+    path.is_file() && path.extension().and_then(|e| e.to_str()) == Some(ARCHIVE_EXTENSION)
+}
+
+fn parse_date(path: &Path) -> Option<NaiveDate> {
+    let name = path.file_name()?.to_str()?;
+    NaiveDate::parse_from_str(name, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::archive::{archive_date_folder, archive_game_folder};
+    use crate::test_util::{temp_folder, write_goal_details};
+
+    use std::fs::{remove_dir_all, write};
+
+    fn make_game(root: &Path, date: &str, game_id: u32, goals: &[u32]) -> PathBuf {
+        let game_path = root.join(date).join(game_id.to_string());
+        write_goal_details(&game_path, goals);
+        for g in goals {
+            write(game_path.join(g.to_string()), format!("[{}]", g)).unwrap();
+        }
+        game_path
+    }
+
+    // folders, per-game archives and per-date archives can all be read from
+    // the same output folder
+    #[test]
+    fn open_mixed_output() {
+        let root = temp_folder("reader_mixed");
+        make_game(&root, "2025-10-26", 2025020140, &[663]);
+        let archived_game = make_game(&root, "2025-10-26", 2025020141, &[12, 13]);
+        archive_game_folder(archived_game).unwrap();
+        make_game(&root, "2025-10-29", 2025020150, &[7]);
+        archive_date_folder(root.join("2025-10-29")).unwrap();
+
+        let reader = OutputReader::open(&root).unwrap();
+        let oct_26 = NaiveDate::from_ymd_opt(2025, 10, 26).unwrap();
+        let oct_29 = NaiveDate::from_ymd_opt(2025, 10, 29).unwrap();
+        assert_eq!(
+            reader.games(),
+            vec![
+                GameRef { game_id: 2025020140, date: oct_26 },
+                GameRef { game_id: 2025020141, date: oct_26 },
+                GameRef { game_id: 2025020150, date: oct_29 },
+            ]
+        );
+
+        assert_eq!(reader.read_tracking(2025020140, 663).unwrap(), "[663]");
+        assert_eq!(reader.read_tracking(2025020141, 13).unwrap(), "[13]");
+        assert_eq!(reader.read_tracking(2025020150, 7).unwrap(), "[7]");
+        assert_eq!(reader.read_game_data(2025020141).unwrap().goals.len(), 2);
+        assert!(reader.read_tracking(2025020150, 8).is_err());
+        assert!(reader.read_file(2025020150, "missing.json").unwrap().is_none());
+        remove_dir_all(root).unwrap();
+    }
+
+    // a single archive can be used as the input
+    #[test]
+    fn open_single_archive() {
+        let root = temp_folder("reader_single");
+        make_game(&root, "2025-10-29", 2025020150, &[7, 8]);
+        let archive_path = archive_date_folder(root.join("2025-10-29")).unwrap();
+
+        let reader = OutputReader::open(archive_path).unwrap();
+        assert_eq!(reader.games().len(), 1);
+        assert_eq!(reader.read_tracking(2025020150, 8).unwrap(), "[8]");
+        remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,437 @@
+use anyhow::{anyhow, Context, Result};
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::fs::File;
+use std::path::Path;
+
+use crate::api_calls::GoalDetails;
+use crate::normalize::{
+    normalize_frames, BLUE_LINE_X_FT, GOAL_LINE_X_FT, NET_DEPTH_FT, NET_HALF_WIDTH_FT, RINK_LENGTH_FT,
+    RINK_WIDTH_FT,
+};
+use crate::reader::OutputReader;
+use crate::shot::{detect_shot, ShotRelease};
+use crate::tracking::Frame;
+
+/// Default size of the image, in pixels per foot
+pub const DEFAULT_SCALE: f64 = 5.0;
+
+/// Radius of the rink's corners
+const CORNER_RADIUS_FT: f64 = 28.0;
+/// Faceoff circles and the center circle
+const CIRCLE_RADIUS_FT: f64 = 15.0;
+/// Faceoff dots in the zones are 20 ft in from the goal lines, and the ones
+/// in the neutral zone are 5 ft from the blue lines, all 22 ft from the middle
+const ZONE_DOT_X_FT: f64 = GOAL_LINE_X_FT - 20.0;
+const NEUTRAL_DOT_X_FT: f64 = BLUE_LINE_X_FT - 5.0;
+const DOT_Y_FT: f64 = 22.0;
+const CREASE_RADIUS_FT: f64 = 6.0;
+
+const PLAYER_RADIUS_FT: f64 = 1.5;
+const PUCK_RADIUS_FT: f64 = 0.6;
+
+/// Colors for teams that aren't in `team_color`
+const HOME_COLOR: &str = "#1f4e9c";
+const AWAY_COLOR: &str = "#c8102e";
+/// Team colors closer than this (see `color_distance`) are too hard to tell
+/// apart
+const MIN_COLOR_DISTANCE: f64 = 100.0;
+
+/// Settings for rendering a goal
+#[derive(Debug, Clone, Copy)]
+pub struct RenderOptions {
+    /// pixels per foot
+    pub scale: f64,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions { scale: DEFAULT_SCALE }
+    }
+}
+
+/// Renders a goal to an SVG file
+/// The rink is drawn in the canonical frame of reference, with the scoring
+/// team attacking the net on the right.
+pub fn render<P>(reader: &OutputReader, game_id: u32, event_id: u32, options: &RenderOptions, path: P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let goal = read_normalized_goal(reader, game_id, event_id)?;
+    let shot = detect_shot(&goal.frames, goal.goal.scoring_team_id);
+    let title = format!("Game {}, goal {}", game_id, event_id);
+    let svg = render_goal_svg(&goal.frames, goal.home_team_id, shot.as_ref(), &title, options);
+
+    let path = path.as_ref();
+    let mut file = File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    std::io::Write::write_all(&mut file, svg.as_bytes())?;
+    Ok(())
+}
+
+/// A goal along with its frames in the canonical frame of reference
+pub struct NormalizedGoal {
+    pub goal: GoalDetails,
+    pub home_team_id: u16,
+    pub frames: Vec<Frame>,
+}
+
+/// Reads a goal's details and frames, normalizing the frames (see
+/// `normalize_frames`)
+pub fn read_normalized_goal(reader: &OutputReader, game_id: u32, event_id: u32) -> Result<NormalizedGoal> {
+    let game_data = reader.read_game_data(game_id)?;
+    let goal = game_data
+        .goals
+        .into_iter()
+        .find(|g| g.event_id == event_id)
+        .ok_or_else(|| anyhow!("No goal {} in game {}", event_id, game_id))?;
+    let frames = normalize_frames(&reader.read_frames(game_id, event_id)?, &goal, game_data.home_team_id);
+    Ok(NormalizedGoal {
+        goal,
+        home_team_id: game_data.home_team_id,
+        frames,
+    })
+}
+
+/// Renders a goal as an SVG: the players and puck in the last frame, the
+/// puck's path over the whole sequence and the shot
+/// The frames need to be normalized (see `normalize_frames`).
+pub fn render_goal_svg(
+    frames: &[Frame],
+    home_team_id: u16,
+    shot: Option<&ShotRelease>,
+    title: &str,
+    options: &RenderOptions,
+) -> String {
+    let mut body = rink_svg();
+    let colors = team_colors(frames, home_team_id);
+    draw_trail(&mut body, frames);
+    if let Some(shot) = shot {
+        draw_shot(&mut body, frames, shot);
+    }
+    if let Some(last) = frames.last() {
+        draw_frame(&mut body, last, &colors);
+    }
+    svg_document(&body, title, options)
+}
+
+/// Draws the puck's path through the frames
+pub fn draw_trail(body: &mut String, frames: &[Frame]) {
+    let trail: Vec<String> = frames
+        .iter()
+        .filter_map(|f| f.puck())
+        .map(|p| {
+            let (x, y) = to_svg(p.x, p.y);
+            format!("{:.2},{:.2}", x, y)
+        })
+        .collect();
+    if !trail.is_empty() {
+        let _ = writeln!(
+            body,
+            r##"<polyline points="{}" fill="none" stroke="#333333" stroke-width="0.3" stroke-opacity="0.7"/>"##,
+            trail.join(" ")
+        );
+    }
+}
+
+/// Draws a dashed line from the shot's release to where the puck crossed the
+/// goal line (or the middle of the net, if it wasn't tracked that far)
+pub fn draw_shot(body: &mut String, frames: &[Frame], shot: &ShotRelease) {
+    let (x1, y1) = to_svg(shot.release_x, shot.release_y);
+    let (x2, y2) = shot
+        .goal_line_frame
+        .and_then(|i| frames[i].puck())
+        .map_or(to_svg(GOAL_LINE_X_FT, 0.0), |p| to_svg(p.x, p.y));
+    let _ = writeln!(
+        body,
+        r##"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" stroke="#f5a623" stroke-width="0.4" stroke-dasharray="1,0.6"/>"##,
+        x1, y1, x2, y2
+    );
+}
+
+/// Draws the players, with their sweater numbers, and the puck in a frame
+pub fn draw_frame(body: &mut String, frame: &Frame, colors: &BTreeMap<u16, &str>) {
+    for p in frame.players() {
+        let color = p.team_id.and_then(|id| colors.get(&id)).copied().unwrap_or(AWAY_COLOR);
+        let (x, y) = to_svg(p.x, p.y);
+        let _ = writeln!(
+            body,
+            r##"<circle cx="{:.2}" cy="{:.2}" r="{}" fill="{}" stroke="#ffffff" stroke-width="0.2"/>"##,
+            x, y, PLAYER_RADIUS_FT, color
+        );
+        if let Some(number) = p.sweater_number {
+            let _ = writeln!(
+                body,
+                r##"<text x="{:.2}" y="{:.2}" font-size="1.5" font-family="Helvetica, Arial, DejaVu Sans, sans-serif" font-weight="bold" fill="#ffffff" text-anchor="middle" dominant-baseline="central">{}</text>"##,
+                x, y, number
+            );
+        }
+    }
+    if let Some(puck) = frame.puck() {
+        let (x, y) = to_svg(puck.x, puck.y);
+        let _ = writeln!(body, r##"<circle cx="{:.2}" cy="{:.2}" r="{}" fill="#000000"/>"##, x, y, PUCK_RADIUS_FT);
+    }
+}
+
+/// Wraps SVG elements drawn in rink coordinates (see `to_svg`) in a document
+pub fn svg_document(body: &str, title: &str, options: &RenderOptions) -> String {
+    format!(
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{:.0}" height="{:.0}" viewBox="0 0 {} {}">
+<title>{}</title>
+{}</svg>
+"#,
+        RINK_LENGTH_FT * options.scale,
+        RINK_WIDTH_FT * options.scale,
+        RINK_LENGTH_FT,
+        RINK_WIDTH_FT,
+        escape(title),
+        body
+    )
+}
+
+/// The markings of a regulation NHL rink, in rink coordinates
+pub fn rink_svg() -> String {
+    let mut svg = String::new();
+    let _ = writeln!(
+        svg,
+        r##"<defs><clipPath id="rink"><rect x="0" y="0" width="{w}" height="{h}" rx="{r}"/></clipPath></defs>
+<rect x="0" y="0" width="{w}" height="{h}" rx="{r}" fill="#ffffff"/>
+<g clip-path="url(#rink)">"##,
+        w = RINK_LENGTH_FT,
+        h = RINK_WIDTH_FT,
+        r = CORNER_RADIUS_FT
+    );
+
+    // lines across the ice
+    let line = |svg: &mut String, x: f64, width: f64, color: &str| {
+        let (x, _) = to_svg(x, 0.0);
+        let _ = writeln!(
+            svg,
+            r#"<line x1="{:.2}" y1="0" x2="{:.2}" y2="{}" stroke="{}" stroke-width="{:.3}"/>"#,
+            x, x, RINK_WIDTH_FT, color, width
+        );
+    };
+    line(&mut svg, 0.0, 1.0, "#c8102e");
+    for side in [-1.0, 1.0] {
+        line(&mut svg, side * BLUE_LINE_X_FT, 1.0, "#0033a0");
+        line(&mut svg, side * GOAL_LINE_X_FT, 2.0 / 12.0, "#c8102e");
+    }
+
+    // circles and dots
+    let (cx, cy) = to_svg(0.0, 0.0);
+    let _ = writeln!(
+        svg,
+        r##"<circle cx="{:.2}" cy="{:.2}" r="{}" fill="none" stroke="#0033a0" stroke-width="0.17"/>
+<circle cx="{:.2}" cy="{:.2}" r="0.5" fill="#0033a0"/>"##,
+        cx, cy, CIRCLE_RADIUS_FT, cx, cy
+    );
+    for x_side in [-1.0, 1.0] {
+        for y_side in [-1.0, 1.0] {
+            let (x, y) = to_svg(x_side * ZONE_DOT_X_FT, y_side * DOT_Y_FT);
+            let _ = writeln!(
+                svg,
+                r##"<circle cx="{:.2}" cy="{:.2}" r="{}" fill="none" stroke="#c8102e" stroke-width="0.17"/>
+<circle cx="{:.2}" cy="{:.2}" r="1" fill="#c8102e"/>"##,
+                x, y, CIRCLE_RADIUS_FT, x, y
+            );
+            let (x, y) = to_svg(x_side * NEUTRAL_DOT_X_FT, y_side * DOT_Y_FT);
+            let _ = writeln!(svg, r##"<circle cx="{:.2}" cy="{:.2}" r="1" fill="#c8102e"/>"##, x, y);
+        }
+    }
+
+    // creases and nets
+    for side in [-1.0, 1.0] {
+        let (x, top) = to_svg(side * GOAL_LINE_X_FT, CREASE_RADIUS_FT);
+        let (_, bottom) = to_svg(side * GOAL_LINE_X_FT, -CREASE_RADIUS_FT);
+        // the crease bulges out towards center ice
+        let sweep = if side > 0.0 { 0 } else { 1 };
+        let _ = writeln!(
+            svg,
+            r##"<path d="M {:.2} {:.2} A {r} {r} 0 0 {} {:.2} {:.2} Z" fill="#a6d8f0" stroke="#c8102e" stroke-width="0.17"/>"##,
+            x,
+            top,
+            sweep,
+            x,
+            bottom,
+            r = CREASE_RADIUS_FT
+        );
+        let (net_x, net_y) = to_svg(side * GOAL_LINE_X_FT + side.min(0.0) * NET_DEPTH_FT, NET_HALF_WIDTH_FT);
+        let _ = writeln!(
+            svg,
+            r##"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{}" fill="#dddddd" stroke="#c8102e" stroke-width="0.17"/>"##,
+            net_x,
+            net_y,
+            NET_DEPTH_FT,
+            2.0 * NET_HALF_WIDTH_FT
+        );
+    }
+
+    let _ = writeln!(
+        svg,
+        r##"</g>
+<rect x="0" y="0" width="{}" height="{}" rx="{}" fill="none" stroke="#000000" stroke-width="0.5"/>"##,
+        RINK_LENGTH_FT, RINK_WIDTH_FT, CORNER_RADIUS_FT
+    );
+    svg
+}
+
+/// Converts a point in the canonical frame of reference (feet from center
+/// ice, y up) to rink coordinates: feet from the top left corner, y down
+pub fn to_svg(x: f64, y: f64) -> (f64, f64) {
+    (x + RINK_LENGTH_FT / 2.0, RINK_WIDTH_FT / 2.0 - y)
+}
+
+/// Picks a color for each team in the frames: its primary color, unless the
+/// two teams' colors are too alike, in which case the away team gets
+/// whichever plain color stands out more from the home team's
+pub fn team_colors(frames: &[Frame], home_team_id: u16) -> BTreeMap<u16, &'static str> {
+    let mut abbrevs: BTreeMap<u16, Option<&str>> = BTreeMap::new();
+    for p in frames.iter().flat_map(|f| f.players()) {
+        if let Some(team_id) = p.team_id {
+            let abbrev = abbrevs.entry(team_id).or_insert(None);
+            if abbrev.is_none() {
+                *abbrev = p.team_abbrev.as_deref();
+            }
+        }
+    }
+
+    let home_color = abbrevs
+        .get(&home_team_id)
+        .copied()
+        .flatten()
+        .and_then(team_color)
+        .unwrap_or(HOME_COLOR);
+    abbrevs
+        .into_iter()
+        .map(|(team_id, abbrev)| {
+            if team_id == home_team_id {
+                return (team_id, home_color);
+            }
+            let color = match abbrev.and_then(team_color) {
+                Some(color) if color_distance(color, home_color) >= MIN_COLOR_DISTANCE => color,
+                _ if color_distance(AWAY_COLOR, home_color) >= color_distance(HOME_COLOR, home_color) => AWAY_COLOR,
+                _ => HOME_COLOR,
+            };
+            (team_id, color)
+        })
+        .collect()
+}
+
+/// A team's primary color
+pub fn team_color(abbrev: &str) -> Option<&'static str> {
+    let color = match abbrev {
+        "ANA" => "#f47a38",
+        "BOS" => "#ffb81c",
+        "BUF" => "#003087",
+        "CGY" => "#c8102e",
+        "CAR" => "#ce1126",
+        "CHI" => "#cf0a2c",
+        "COL" => "#6f263d",
+        "CBJ" => "#002654",
+        "DAL" => "#006847",
+        "DET" => "#ce1126",
+        "EDM" => "#ff4c00",
+        "FLA" => "#c8102e",
+        "LAK" => "#111111",
+        "MIN" => "#154734",
+        "MTL" => "#af1e2d",
+        "NSH" => "#ffb81c",
+        "NJD" => "#ce1126",
+        "NYI" => "#00539b",
+        "NYR" => "#0038a8",
+        "OTT" => "#c52032",
+        "PHI" => "#f74902",
+        "PIT" => "#fcb514",
+        "SJS" => "#006d75",
+        "SEA" => "#001628",
+        "STL" => "#002f87",
+        "TBL" => "#002868",
+        "TOR" => "#00205b",
+        "UTA" => "#71afe5",
+        "VAN" => "#00205b",
+        "VGK" => "#b4975a",
+        "WSH" => "#c8102e",
+        "WPG" => "#041e42",
+        _ => return None,
+    };
+    Some(color)
+}
+
+/// Distance between two "#rrggbb" colors in RGB space
+fn color_distance(a: &str, b: &str) -> f64 {
+    let rgb = |color: &str| -> [f64; 3] {
+        let channel = |i: usize| {
+            color
+                .get(1 + 2 * i..3 + 2 * i)
+                .and_then(|c| u8::from_str_radix(c, 16).ok())
+                .unwrap_or(0) as f64
+        };
+        [channel(0), channel(1), channel(2)]
+    };
+    let (a, b) = (rgb(a), rgb(b));
+    ((a[0] - b[0]).powi(2) + (a[1] - b[1]).powi(2) + (a[2] - b[2]).powi(2)).sqrt()
+}
+
+/// Escapes text to go in SVG or HTML
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util;
+    use crate::tracking::Entity;
+
+    fn entity(id: u32, team_id: Option<u16>, abbrev: Option<&str>, sweater_number: Option<u16>, x: f64, y: f64) -> Entity {
+        Entity { team_abbrev: abbrev.map(String::from), sweater_number, ..test_util::entity(id, team_id, x, y) }
+    }
+
+    #[test]
+    fn to_svg_corners() {
+        assert_eq!(to_svg(0.0, 0.0), (100.0, 42.5));
+        assert_eq!(to_svg(-100.0, 42.5), (0.0, 0.0));
+        assert_eq!(to_svg(89.0, -3.0), (189.0, 45.5));
+    }
+
+    // two teams with the same primary color
+    #[test]
+    fn team_colors_clash() {
+        let frames = vec![Frame {
+            time_stamp: 0.0,
+            entities: vec![
+                entity(10, Some(12), Some("CAR"), Some(20), 0.0, 0.0),
+                entity(20, Some(17), Some("DET"), Some(71), 0.0, 0.0),
+                entity(30, Some(10), Some("TOR"), Some(34), 0.0, 0.0),
+            ],
+        }];
+        let colors = team_colors(&frames, 12);
+        assert_eq!(colors[&12], "#ce1126");
+        assert_eq!(colors[&17], HOME_COLOR);
+        assert_eq!(colors[&10], "#00205b");
+    }
+
+    #[test]
+    fn render_goal_svg_elements() {
+        let frames: Vec<Frame> = (0..3)
+            .map(|i| Frame {
+                time_stamp: i as f64,
+                entities: vec![
+                    entity(1, None, None, None, 60.0 + 10.0 * i as f64, 0.0),
+                    entity(10, Some(22), Some("EDM"), Some(97), 59.0, 0.0),
+                    entity(20, Some(15), Some("TOR"), Some(34), 70.0, 5.0),
+                ],
+            })
+            .collect();
+        let svg = render_goal_svg(&frames, 22, None, "Game <1>", &RenderOptions::default());
+        assert!(svg.starts_with("<svg"));
+        assert!(svg.contains(r#"width="1000" height="425""#));
+        assert!(svg.contains("<title>Game &lt;1&gt;</title>"));
+        assert!(svg.contains(r#"<polyline points="160.00,42.50 170.00,42.50 180.00,42.50""#));
+        assert!(svg.contains(">97</text>"));
+        assert!(svg.contains(r##"fill="#ff4c00""##));
+        assert!(svg.contains(r##"fill="#00205b" stroke="#ffffff""##));
+        assert!(!svg.contains("stroke-dasharray"));
+    }
+}
@@ -0,0 +1,286 @@
+use anyhow::{anyhow, Result};
+
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Color, Print, ResetColor, SetForegroundColor};
+use crossterm::{cursor, execute, queue, terminal};
+
+use std::io::Write;
+use std::time::{Duration, Instant};
+
+use crate::normalize::{to_feet, BLUE_LINE_X_FT, GOAL_LINE_X_FT, RINK_LENGTH_FT, RINK_WIDTH_FT};
+use crate::reader::OutputReader;
+use crate::tracking::Frame;
+
+/// How far the seek keys jump, in seconds
+const SEEK_SECONDS: f64 = 1.0;
+
+/// What's drawn in a cell of the terminal, which decides its color
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CellKind {
+    Ice,
+    Boards,
+    RedLine,
+    BlueLine,
+    Home,
+    Away,
+    Puck,
+}
+
+/// Draws a frame as a grid of characters, `width` columns by `height` rows
+/// The rink is shown the way it is in the tracking data, without
+/// normalizing it.  Players are their sweater numbers and the puck is an `o`,
+/// drawn over the players if they overlap.
+pub fn ascii_grid(frame: &Frame, home_team_id: u16, width: usize, height: usize) -> Vec<Vec<(char, CellKind)>> {
+    let mut grid = vec![vec![(' ', CellKind::Ice); width]; height];
+    if width < 3 || height < 3 {
+        return grid;
+    }
+    let column = |x_ft: f64| -> usize {
+        let u = (x_ft + RINK_LENGTH_FT / 2.0) / RINK_LENGTH_FT;
+        ((u * (width - 1) as f64).round().max(0.0) as usize).min(width - 1)
+    };
+    let row = |y_ft: f64| -> usize {
+        let u = (RINK_WIDTH_FT / 2.0 - y_ft) / RINK_WIDTH_FT;
+        ((u * (height - 1) as f64).round().max(0.0) as usize).min(height - 1)
+    };
+
+    for line in grid.iter_mut().take(height - 1).skip(1) {
+        line[column(0.0)] = (':', CellKind::RedLine);
+        for side in [-1.0, 1.0] {
+            line[column(side * BLUE_LINE_X_FT)] = ('|', CellKind::BlueLine);
+            line[column(side * GOAL_LINE_X_FT)] = ('.', CellKind::RedLine);
+        }
+    }
+    for r in [0, height - 1] {
+        grid[r].fill(('-', CellKind::Boards));
+    }
+    for line in grid.iter_mut() {
+        line[0] = ('|', CellKind::Boards);
+        line[width - 1] = ('|', CellKind::Boards);
+    }
+
+    for p in frame.players() {
+        let (x, y) = to_feet(p.x, p.y);
+        let kind = if p.team_id == Some(home_team_id) { CellKind::Home } else { CellKind::Away };
+        let label = p.sweater_number.map_or(String::from("?"), |n| n.to_string());
+        let (start, r) = (column(x), row(y));
+        for (i, c) in label.chars().enumerate() {
+            if let Some(cell) = grid[r].get_mut(start + i) {
+                *cell = (c, kind);
+            }
+        }
+    }
+    if let Some(puck) = frame.puck() {
+        let (x, y) = to_feet(puck.x, puck.y);
+        grid[row(y)][column(x)] = ('o', CellKind::Puck);
+    }
+    grid
+}
+
+/// Plays a goal back in the terminal until the user quits
+/// Keys: space pauses, left/right step a frame, page up/down seek a second,
+/// home/end jump to the start/end, +/- change the speed and q quits.
+pub fn replay(reader: &OutputReader, game_id: u32, event_id: u32) -> Result<()> {
+    let game_data = reader.read_game_data(game_id)?;
+    if !game_data.goals.iter().any(|g| g.event_id == event_id) {
+        return Err(anyhow!("No goal {} in game {}", event_id, game_id));
+    }
+    let frames = reader.read_frames(game_id, event_id)?;
+    if frames.is_empty() {
+        return Err(anyhow!("No frames for goal {} in game {}", event_id, game_id));
+    }
+
+    let _terminal = TerminalGuard::enter()?;
+    let mut stdout = std::io::stdout();
+    let mut index = 0;
+    let mut paused = false;
+    let mut speed = 1.0;
+    let mut shown_at = Instant::now();
+
+    loop {
+        let (columns, rows) = terminal::size()?;
+        let grid = ascii_grid(&frames[index], game_data.home_team_id, columns as usize, rows.saturating_sub(2) as usize);
+        queue!(stdout, cursor::MoveTo(0, 0))?;
+        for line in &grid {
+            for &(c, kind) in line {
+                queue!(stdout, SetForegroundColor(color(kind)), Print(c))?;
+            }
+            queue!(stdout, Print("\r\n"))?;
+        }
+        let status = format!(
+            "game {} goal {}  frame {}/{}  {:.1}s  {}x{}  space pause, <-/-> step, pgup/pgdn seek, home/end, +/- speed, q quit",
+            game_id,
+            event_id,
+            index + 1,
+            frames.len(),
+            frames[index].seconds_since(&frames[0]),
+            speed,
+            if paused { "  paused" } else { "" },
+        );
+        queue!(
+            stdout,
+            ResetColor,
+            terminal::Clear(terminal::ClearType::CurrentLine),
+            Print(truncate(&status, columns as usize))
+        )?;
+        stdout.flush()?;
+
+        // how long until the next frame is due
+        let wait = if paused || index + 1 == frames.len() {
+            Duration::from_millis(250)
+        } else {
+            let seconds = frames[index + 1].seconds_since(&frames[index]) / speed;
+            Duration::from_secs_f64(seconds.max(0.0)).saturating_sub(shown_at.elapsed())
+        };
+        if event::poll(wait)? {
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                match handle_key(key, &frames, index, &mut paused, &mut speed) {
+                    Some(next) => index = next,
+                    None => return Ok(()),
+                }
+                shown_at = Instant::now();
+            }
+        } else if !paused && index + 1 < frames.len() {
+            index += 1;
+            shown_at = Instant::now();
+        }
+    }
+}
+
+/// Applies a key press, returning the frame to show next, or None to quit
+fn handle_key(key: KeyEvent, frames: &[Frame], index: usize, paused: &mut bool, speed: &mut f64) -> Option<usize> {
+    let last = frames.len() - 1;
+    let seek = |seconds: f64| {
+        let target = frames[index].seconds_since(&frames[0]) + seconds;
+        frames
+            .iter()
+            .position(|f| f.seconds_since(&frames[0]) >= target)
+            .unwrap_or(last)
+    };
+    let next = match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => return None,
+        KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return None,
+        KeyCode::Char(' ') => {
+            *paused = !*paused;
+            index
+        }
+        KeyCode::Right => {
+            *paused = true;
+            (index + 1).min(last)
+        }
+        KeyCode::Left => {
+            *paused = true;
+            index.saturating_sub(1)
+        }
+        KeyCode::PageDown => seek(SEEK_SECONDS),
+        KeyCode::PageUp => seek(-SEEK_SECONDS),
+        KeyCode::Home => 0,
+        KeyCode::End => last,
+        KeyCode::Char('+') | KeyCode::Char('=') => {
+            *speed = (*speed * 2.0).min(8.0);
+            index
+        }
+        KeyCode::Char('-') => {
+            *speed = (*speed / 2.0).max(0.125);
+            index
+        }
+        _ => index,
+    };
+    Some(next)
+}
+
+fn color(kind: CellKind) -> Color {
+    match kind {
+        CellKind::Ice => Color::Reset,
+        CellKind::Boards => Color::Grey,
+        CellKind::RedLine => Color::DarkRed,
+        CellKind::BlueLine => Color::DarkBlue,
+        CellKind::Home => Color::Cyan,
+        CellKind::Away => Color::Yellow,
+        CellKind::Puck => Color::White,
+    }
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    text.chars().take(width).collect()
+}
+
+/// Switches the terminal to a full screen, unbuffered mode, and back again
+/// when dropped, even if the replay fails
+struct TerminalGuard;
+
+impl TerminalGuard {
+    fn enter() -> Result<TerminalGuard> {
+        terminal::enable_raw_mode()?;
+        execute!(std::io::stdout(), terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(TerminalGuard)
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        let _ = execute!(std::io::stdout(), ResetColor, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util;
+    use crate::tracking::Entity;
+
+    fn entity(id: u32, team_id: Option<u16>, sweater_number: Option<u16>, x: f64, y: f64) -> Entity {
+        Entity { sweater_number, ..test_util::entity(id, team_id, x, y) }
+    }
+
+    fn frames() -> Vec<Frame> {
+        (0..30)
+            .map(|i| Frame {
+                time_stamp: i as f64,
+                entities: vec![
+                    // the puck at center ice, in sprite coordinates
+                    entity(1, None, None, 1200.0, 510.0),
+                    // top left corner and bottom right corner
+                    entity(10, Some(22), Some(97), 0.0, 0.0),
+                    entity(20, Some(15), Some(8), 2400.0, 1020.0),
+                ],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn ascii_grid_positions() {
+        let grid = ascii_grid(&frames()[0], 22, 41, 11);
+        assert_eq!(grid.len(), 11);
+        assert_eq!(grid[5][20], ('o', CellKind::Puck));
+        assert_eq!(grid[0][0], ('9', CellKind::Home));
+        assert_eq!(grid[0][1], ('7', CellKind::Home));
+        assert_eq!(grid[10][40], ('8', CellKind::Away));
+        assert_eq!(grid[3][20], (':', CellKind::RedLine));
+        assert_eq!(grid[3][15], ('|', CellKind::BlueLine));
+        assert_eq!(grid[0][10], ('-', CellKind::Boards));
+    }
+
+    #[test]
+    fn handle_key_navigation() {
+        let frames = frames();
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let (mut paused, mut speed) = (false, 1.0);
+
+        assert_eq!(handle_key(key(KeyCode::Right), &frames, 5, &mut paused, &mut speed), Some(6));
+        assert!(paused);
+        assert_eq!(handle_key(key(KeyCode::Left), &frames, 0, &mut paused, &mut speed), Some(0));
+        assert_eq!(handle_key(key(KeyCode::PageDown), &frames, 5, &mut paused, &mut speed), Some(15));
+        assert_eq!(handle_key(key(KeyCode::PageDown), &frames, 25, &mut paused, &mut speed), Some(29));
+        assert_eq!(handle_key(key(KeyCode::PageUp), &frames, 5, &mut paused, &mut speed), Some(0));
+        assert_eq!(handle_key(key(KeyCode::End), &frames, 5, &mut paused, &mut speed), Some(29));
+        assert_eq!(handle_key(key(KeyCode::Char('+')), &frames, 5, &mut paused, &mut speed), Some(5));
+        assert_eq!(speed, 2.0);
+        assert_eq!(handle_key(key(KeyCode::Char('q')), &frames, 5, &mut paused, &mut speed), None);
+    }
+}
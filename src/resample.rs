@@ -0,0 +1,292 @@
+use anyhow::{anyhow, Result};
+
+use clap::ValueEnum;
+
+use std::collections::BTreeMap;
+
+use crate::tracking::{Entity, Frame, TIMESTAMPS_PER_SECOND};
+
+/// Timestamps closer than this (in seconds) are treated as the same point in
+/// time, so observed positions aren't flagged as interpolated
+const SAME_TIME_SECONDS: f64 = 1e-6;
+
+/// How positions between two observations get filled in
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum)]
+pub enum Interpolation {
+    /// straight line between the observations on either side
+    Linear,
+    /// cubic Hermite spline, with tangents from the neighbouring observations
+    Spline,
+}
+
+/// Settings for resampling tracking frames to a fixed rate
+#[derive(Debug, Clone, Copy)]
+pub struct ResampleOptions {
+    /// frames per second of the output
+    pub hz: f64,
+    pub interpolation: Interpolation,
+    /// an entity missing for longer than this many seconds is treated as
+    /// having left the ice, rather than being interpolated across the gap
+    pub max_gap_seconds: f64,
+}
+
+impl ResampleOptions {
+    pub fn new(hz: f64, interpolation: Interpolation) -> Self {
+        const DEFAULT_MAX_GAP_SECONDS: f64 = 1.0;
+
+        ResampleOptions {
+            hz,
+            interpolation,
+            max_gap_seconds: DEFAULT_MAX_GAP_SECONDS,
+        }
+    }
+}
+
+/// An observed position of an entity
+#[derive(Debug, Clone, Copy)]
+struct Sample {
+    seconds: f64,
+    x: f64,
+    y: f64,
+}
+
+/// Everything observed for one entity: its details (from its first
+/// appearance) and its positions, split wherever it was off the ice
+struct Track {
+    entity: Entity,
+    segments: Vec<Vec<Sample>>,
+}
+
+/// Resamples frames to a fixed rate, starting at the first frame
+/// Positions between observations are interpolated and flagged as such.
+/// Entities only show up in the output while they're on the ice: they're
+/// never extrapolated past their first or last observation, or across a gap
+/// longer than `max_gap_seconds`.
+pub fn resample_frames(frames: &[Frame], options: &ResampleOptions) -> Result<Vec<Frame>> {
+    if !options.hz.is_finite() || options.hz <= 0.0 {
+        return Err(anyhow!("Invalid resampling rate: {} Hz", options.hz));
+    }
+    let first = match frames.first() {
+        Some(first) => first,
+        None => return Ok(vec![]),
+    };
+    let last = frames.last().unwrap_or(first);
+
+    let tracks = build_tracks(frames, first, options.max_gap_seconds);
+
+    let duration = last.seconds_since(first);
+    let num_frames = (duration * options.hz + SAME_TIME_SECONDS).floor() as usize + 1;
+    let mut resampled = Vec::with_capacity(num_frames);
+    for i in 0..num_frames {
+        let seconds = i as f64 / options.hz;
+        let mut entities = vec![];
+        for track in tracks.values() {
+            if let Some((x, y, interpolated)) = position_at(track, seconds, options.interpolation) {
+                entities.push(Entity {
+                    x,
+                    y,
+                    interpolated,
+                    ..track.entity.clone()
+                });
+            }
+        }
+        resampled.push(Frame {
+            time_stamp: first.time_stamp + seconds * TIMESTAMPS_PER_SECOND,
+            entities,
+        });
+    }
+    Ok(resampled)
+}
+
+/// Groups the observations in the frames by entity
+fn build_tracks(frames: &[Frame], first: &Frame, max_gap_seconds: f64) -> BTreeMap<u32, Track> {
+    let mut tracks: BTreeMap<u32, Track> = BTreeMap::new();
+    for frame in frames {
+        let seconds = frame.seconds_since(first);
+        for e in &frame.entities {
+            let sample = Sample { seconds, x: e.x, y: e.y };
+            let track = tracks.entry(e.id).or_insert_with(|| Track {
+                entity: e.clone(),
+                segments: vec![],
+            });
+            match track.segments.last_mut() {
+                Some(segment) if seconds - segment[segment.len() - 1].seconds <= max_gap_seconds => {
+                    segment.push(sample)
+                }
+                _ => track.segments.push(vec![sample]),
+            }
+        }
+    }
+    tracks
+}
+
+/// Gets an entity's position at a point in time
+/// Returns None if the entity wasn't on the ice then, and otherwise the
+/// position and whether it was interpolated.
+fn position_at(track: &Track, seconds: f64, interpolation: Interpolation) -> Option<(f64, f64, bool)> {
+    let segment = track.segments.iter().find(|s| {
+        seconds >= s[0].seconds - SAME_TIME_SECONDS
+            && seconds <= s[s.len() - 1].seconds + SAME_TIME_SECONDS
+    })?;
+
+    // index of the last observation at or before the time
+    let i = segment
+        .partition_point(|s| s.seconds <= seconds + SAME_TIME_SECONDS)
+        .saturating_sub(1);
+    let before = segment[i];
+    if (seconds - before.seconds).abs() <= SAME_TIME_SECONDS {
+        return Some((before.x, before.y, false));
+    }
+    let after = segment[i + 1];
+    if (after.seconds - seconds).abs() <= SAME_TIME_SECONDS {
+        return Some((after.x, after.y, false));
+    }
+
+    let (x, y) = match interpolation {
+        Interpolation::Linear => {
+            let u = (seconds - before.seconds) / (after.seconds - before.seconds);
+            (lerp(before.x, after.x, u), lerp(before.y, after.y, u))
+        }
+        Interpolation::Spline => hermite(segment, i, seconds),
+    };
+    Some((x, y, true))
+}
+
+fn lerp(a: f64, b: f64, u: f64) -> f64 {
+    a + (b - a) * u
+}
+
+/// Cubic Hermite interpolation between observations i and i + 1, with each
+/// observation's velocity estimated from its neighbours (Catmull-Rom style,
+/// but allowing uneven time steps)
+fn hermite(segment: &[Sample], i: usize, seconds: f64) -> (f64, f64) {
+    let p1 = segment[i];
+    let p2 = segment[i + 1];
+    let p0 = if i > 0 { segment[i - 1] } else { p1 };
+    let p3 = if i + 2 < segment.len() { segment[i + 2] } else { p2 };
+
+    let tangent = |a: Sample, b: Sample| {
+        let dt = b.seconds - a.seconds;
+        if dt > 0.0 {
+            ((b.x - a.x) / dt, (b.y - a.y) / dt)
+        } else {
+            (0.0, 0.0)
+        }
+    };
+    let m1 = tangent(p0, p2);
+    let m2 = tangent(p1, p3);
+
+    let h = p2.seconds - p1.seconds;
+    let u = (seconds - p1.seconds) / h;
+    let u2 = u * u;
+    let u3 = u2 * u;
+    let h00 = 2.0 * u3 - 3.0 * u2 + 1.0;
+    let h10 = u3 - 2.0 * u2 + u;
+    let h01 = -2.0 * u3 + 3.0 * u2;
+    let h11 = u3 - u2;
+
+    (
+        h00 * p1.x + h10 * h * m1.0 + h01 * p2.x + h11 * h * m2.0,
+        h00 * p1.y + h10 * h * m1.1 + h01 * p2.y + h11 * h * m2.1,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::entity;
+
+    fn frame(time_stamp: f64, entities: Vec<Entity>) -> Frame {
+        Frame { time_stamp, entities }
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    // 10 Hz input with a dropped frame, resampled to 20 Hz
+    #[test]
+    fn resample_frames_linear() {
+        let frames = vec![
+            frame(0.0, vec![entity(1, None, 0.0, 0.0)]),
+            frame(1.0, vec![entity(1, None, 10.0, 5.0)]),
+            // frame at 2.0 was dropped
+            frame(3.0, vec![entity(1, None, 30.0, 5.0)]),
+        ];
+        let options = ResampleOptions::new(20.0, Interpolation::Linear);
+        let resampled = resample_frames(&frames, &options).unwrap();
+
+        assert_eq!(resampled.len(), 7);
+        assert_close(resampled[6].time_stamp, 3.0);
+
+        let puck_positions: Vec<(f64, f64, bool)> = resampled
+            .iter()
+            .map(|f| {
+                let p = f.puck().unwrap();
+                (p.x, p.y, p.interpolated)
+            })
+            .collect();
+        assert_eq!(
+            puck_positions,
+            vec![
+                (0.0, 0.0, false),
+                (5.0, 2.5, true),
+                (10.0, 5.0, false),
+                (15.0, 5.0, true),
+                (20.0, 5.0, true),
+                (25.0, 5.0, true),
+                (30.0, 5.0, false),
+            ]
+        );
+    }
+
+    // entities only appear while they're on the ice
+    #[test]
+    fn resample_frames_entering_leaving() {
+        let frames = vec![
+            frame(0.0, vec![entity(1, None, 0.0, 0.0), entity(8478402, None, 0.0, 0.0)]),
+            frame(1.0, vec![entity(1, None, 1.0, 0.0), entity(8478402, None, 1.0, 0.0)]),
+            frame(2.0, vec![entity(1, None, 2.0, 0.0), entity(8471214, None, 2.0, 0.0)]),
+            frame(3.0, vec![entity(1, None, 3.0, 0.0), entity(8471214, None, 3.0, 0.0)]),
+            // 8478402 comes back after being off the ice for over a second
+            frame(12.0, vec![entity(1, None, 12.0, 0.0), entity(8478402, None, 12.0, 0.0)]),
+        ];
+        let options = ResampleOptions::new(10.0, Interpolation::Linear);
+        let resampled = resample_frames(&frames, &options).unwrap();
+        assert_eq!(resampled.len(), 13);
+
+        let ids = |i: usize| -> Vec<u32> { resampled[i].entities.iter().map(|e| e.id).collect() };
+        assert_eq!(ids(0), vec![1, 8478402]);
+        assert_eq!(ids(1), vec![1, 8478402]);
+        assert_eq!(ids(2), vec![1, 8471214]);
+        assert_eq!(ids(3), vec![1, 8471214]);
+        assert_eq!(ids(10), vec![1]);
+        assert_eq!(ids(11), vec![1]);
+        assert_eq!(ids(12), vec![1, 8478402]);
+        assert!(resampled[10].entities[0].interpolated);
+    }
+
+    // the spline passes through the observations and follows straight lines
+    #[test]
+    fn resample_frames_spline() {
+        let frames: Vec<Frame> = (0..5)
+            .map(|i| frame(i as f64, vec![entity(1, None, 3.0 * i as f64, 1.0)]))
+            .collect();
+        let options = ResampleOptions::new(40.0, Interpolation::Spline);
+        let resampled = resample_frames(&frames, &options).unwrap();
+        assert_eq!(resampled.len(), 17);
+        for (i, f) in resampled.iter().enumerate() {
+            let puck = f.puck().unwrap();
+            assert_close(puck.x, 0.75 * i as f64);
+            assert_close(puck.y, 1.0);
+            assert_eq!(puck.interpolated, i % 4 != 0);
+        }
+    }
+
+    #[test]
+    fn resample_frames_invalid_rate() {
+        let frames = vec![frame(0.0, vec![entity(1, None, 0.0, 0.0)])];
+        assert!(resample_frames(&frames, &ResampleOptions::new(0.0, Interpolation::Linear)).is_err());
+    }
+}
@@ -0,0 +1,133 @@
+use anyhow::Result;
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+use crate::api_calls::GoalDetails;
+use crate::json_file::{load_json_or_default, save_json_atomic};
+
+/// Name of the file in the output folder listing the goals to try again
+pub const RETRY_QUEUE_FILENAME: &str = "retry_queue.json";
+
+/// A goal whose tracking data came back empty or invalid
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedGoal {
+    pub game_id: u32,
+    pub season: u32,
+    /// YYYY-MM-DD, which with the game id gives the game folder
+    pub game_date: String,
+    pub goal: GoalDetails,
+    /// what was wrong with the last attempt
+    pub reason: String,
+    pub attempts: u32,
+    /// when the goal was last tried, in RFC 3339
+    pub last_attempt: String,
+}
+
+/// The goals waiting to be downloaded again, saved between runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RetryQueue {
+    pub goals: Vec<QueuedGoal>,
+}
+
+impl RetryQueue {
+    /// Reads the queue saved in a file, or an empty queue if there isn't one
+    pub fn load<P>(path: P) -> Result<RetryQueue>
+    where
+        P: AsRef<Path>,
+    {
+        load_json_or_default(path, "retry queue")
+    }
+
+    /// Saves the queue to a file
+    pub fn save<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        save_json_atomic(path, self)
+    }
+
+    /// Adds a goal whose download failed, or counts another failed attempt
+    /// for a goal that's already queued
+    pub fn push(
+        &mut self,
+        game_id: u32,
+        season: u32,
+        game_date: &NaiveDate,
+        goal: &GoalDetails,
+        reason: &str,
+        now: DateTime<Utc>,
+    ) {
+        let existing = self
+            .goals
+            .iter_mut()
+            .find(|q| q.game_id == game_id && q.goal.event_id == goal.event_id);
+        match existing {
+            Some(queued) => {
+                queued.goal = goal.clone();
+                queued.reason = String::from(reason);
+                queued.attempts += 1;
+                queued.last_attempt = now.to_rfc3339();
+            }
+            None => self.goals.push(QueuedGoal {
+                game_id,
+                season,
+                game_date: game_date.to_string(),
+                goal: goal.clone(),
+                reason: String::from(reason),
+                attempts: 1,
+                last_attempt: now.to_rfc3339(),
+            }),
+        }
+    }
+
+    /// Takes a goal off the queue, returning whether it was on it
+    pub fn remove(&mut self, game_id: u32, event_id: u32) -> bool {
+        let len = self.goals.len();
+        self.goals.retain(|q| q.game_id != game_id || q.goal.event_id != event_id);
+        self.goals.len() != len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::api_calls::IceSide;
+
+    fn goal(event_id: u32) -> GoalDetails {
+        GoalDetails {
+            event_id,
+            ppt_replay_url: None,
+            scoring_team_id: 1,
+            home_team_defending_side: IceSide::Left,
+            situation_code: None,
+        }
+    }
+
+    fn time(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn push_counts_attempts_and_remove() {
+        let date = NaiveDate::from_ymd_opt(2025, 10, 26).unwrap();
+        let mut queue = RetryQueue::default();
+        queue.push(2025020140, 20252026, &date, &goal(663), "empty", time("2025-10-27T01:00:00Z"));
+        queue.push(2025020140, 20252026, &date, &goal(700), "empty", time("2025-10-27T01:00:00Z"));
+        queue.push(2025020140, 20252026, &date, &goal(663), "invalid", time("2025-10-27T03:00:00Z"));
+        assert_eq!(queue.goals.len(), 2);
+        assert_eq!(queue.goals[0].attempts, 2);
+        assert_eq!(queue.goals[0].reason, "invalid");
+        assert_eq!(queue.goals[0].last_attempt, "2025-10-27T03:00:00+00:00");
+        assert_eq!(queue.goals[0].game_date, "2025-10-26");
+
+        assert!(queue.remove(2025020140, 663));
+        assert!(!queue.remove(2025020140, 663));
+        assert_eq!(queue.goals.len(), 1);
+        assert_eq!(queue.goals[0].goal.event_id, 700);
+    }
+}
@@ -0,0 +1,373 @@
+use anyhow::Result;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::json_file::{load_json_or_default, save_json_atomic};
+
+/// Name of the file in the output folder with the fields seen in the NHL's
+/// responses
+pub const SCHEMA_LOG_FILENAME: &str = "schema_drift.json";
+
+/// An NHL endpoint whose responses are checked against the fields the
+/// downloader reads
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Endpoint {
+    Schedule,
+    Landing,
+    PlayByPlay,
+    Sprites,
+}
+
+impl fmt::Display for Endpoint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Endpoint::Schedule => write!(f, "schedule"),
+            Endpoint::Landing => write!(f, "landing"),
+            Endpoint::PlayByPlay => write!(f, "play-by-play"),
+            Endpoint::Sprites => write!(f, "sprites"),
+        }
+    }
+}
+
+impl Endpoint {
+    /// The fields the downloader reads from the endpoint, matching the structs
+    /// in `api_calls` and `tracking`
+    /// `[]` marks the items of an array and `*` the values of an object keyed
+    /// by id.
+    pub fn expected_fields(&self) -> &'static [&'static str] {
+        match self {
+            Endpoint::Schedule => &[
+                "gameWeek[].date",
+                "gameWeek[].games[].id",
+                "gameWeek[].games[].season",
+                "gameWeek[].games[].startTimeUTC",
+                "gameWeek[].games[].venueUTCOffset",
+                "gameWeek[].games[].gameState",
+            ],
+            Endpoint::Landing => &[
+                "id",
+                "season",
+                "gameDate",
+                "gameState",
+                "homeTeam.id",
+                "awayTeam.id",
+                "summary.scoring[].periodDescriptor.periodType",
+                "summary.scoring[].goals[].eventId",
+                "summary.scoring[].goals[].pptReplayUrl",
+                "summary.scoring[].goals[].homeTeamDefendingSide",
+                "summary.scoring[].goals[].isHome",
+                "summary.scoring[].goals[].situationCode",
+            ],
+            // the roster comes from the play-by-play endpoint too
+            Endpoint::PlayByPlay => &[
+                "id",
+                "season",
+                "gameDate",
+                "gameState",
+                "homeTeam.id",
+                "plays[].eventId",
+                "plays[].homeTeamDefendingSide",
+                "plays[].typeDescKey",
+                "plays[].pptReplayUrl",
+                "plays[].situationCode",
+                "plays[].details.eventOwnerTeamId",
+                "plays[].periodDescriptor.periodType",
+                "rosterSpots[].teamId",
+                "rosterSpots[].playerId",
+                "rosterSpots[].firstName.default",
+                "rosterSpots[].lastName.default",
+                "rosterSpots[].sweaterNumber",
+                "rosterSpots[].positionCode",
+            ],
+            Endpoint::Sprites => &[
+                "[].timeStamp",
+                "[].onIce.*.id",
+                "[].onIce.*.playerId",
+                "[].onIce.*.teamId",
+                "[].onIce.*.teamAbbrev",
+                "[].onIce.*.sweaterNumber",
+                "[].onIce.*.x",
+                "[].onIce.*.y",
+            ],
+        }
+    }
+
+    /// Objects keyed by ids rather than field names
+    fn map_fields(&self) -> &'static [&'static str] {
+        match self {
+            Endpoint::Sprites => &["[].onIce"],
+            _ => &[],
+        }
+    }
+}
+
+/// Every field path in a response, e.g. `summary.scoring[].goals[].eventId`
+/// Arrays only have an `[]` path when they have items.
+pub fn field_paths(endpoint: Endpoint, response: &Value) -> BTreeSet<String> {
+    let mut paths = BTreeSet::new();
+    add_field_paths(response, "", endpoint.map_fields(), &mut paths);
+    paths
+}
+
+fn add_field_paths(value: &Value, path: &str, map_fields: &[&str], paths: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(fields) => {
+            let is_map = map_fields.contains(&path);
+            for (key, field) in fields {
+                let key = if is_map { "*" } else { key.as_str() };
+                let field_path = if path.is_empty() {
+                    String::from(key)
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                paths.insert(field_path.clone());
+                add_field_paths(field, &field_path, map_fields, paths);
+            }
+        }
+        Value::Array(items) if !items.is_empty() => {
+            let item_path = format!("{}[]", path);
+            paths.insert(item_path.clone());
+            for item in items {
+                add_field_paths(item, &item_path, map_fields, paths);
+            }
+        }
+        _ => (),
+    }
+}
+
+/// The path a field lives under: "" for top-level fields
+fn parent_path(path: &str) -> &str {
+    match path.strip_suffix("[]") {
+        Some(array_path) => array_path,
+        None => path.rsplit_once('.').map_or("", |(parent, _)| parent),
+    }
+}
+
+/// The fields seen in each endpoint's responses over one or more runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SchemaLog {
+    pub endpoints: BTreeMap<Endpoint, EndpointLog>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct EndpointLog {
+    pub responses: u64,
+    /// responses logged before the latest run
+    #[serde(default)]
+    pub run_start: u64,
+    /// responses that weren't JSON at all
+    pub unreadable: u64,
+    pub fields: BTreeMap<String, FieldLog>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FieldLog {
+    /// how many responses had the field
+    pub count: u64,
+    /// the number of the first response with the field, counting from 1
+    pub first_response: u64,
+    /// what that response was for, e.g. "game 2025020140"
+    pub first_source: String,
+}
+
+/// An expected field that some responses were missing
+#[derive(Debug, Clone, PartialEq)]
+pub struct MissingField {
+    pub path: &'static str,
+    /// responses that had the field's parent but not the field
+    pub missing: u64,
+    /// responses that had the field's parent
+    pub applicable: u64,
+}
+
+impl SchemaLog {
+    /// Reads the log saved in a file, or an empty log if there isn't one
+    pub fn load<P>(path: P) -> Result<SchemaLog>
+    where
+        P: AsRef<Path>,
+    {
+        load_json_or_default(path, "schema log")
+    }
+
+    /// Saves the log to a file
+    pub fn save<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        save_json_atomic(path, self)
+    }
+
+    /// Marks the start of a run, so the fields new in it can be told apart
+    /// from the ones earlier runs found
+    pub fn start_run(&mut self) {
+        for log in self.endpoints.values_mut() {
+            log.run_start = log.responses;
+        }
+    }
+
+    /// Adds the fields in a response to the log
+    pub fn record(&mut self, endpoint: Endpoint, source: &str, response: &str) {
+        let log = self.endpoints.entry(endpoint).or_default();
+        let Ok(value) = serde_json::from_str::<Value>(response) else {
+            log.unreadable += 1;
+            return;
+        };
+        log.responses += 1;
+        for path in field_paths(endpoint, &value) {
+            let field = log.fields.entry(path).or_insert_with(|| FieldLog {
+                count: 0,
+                first_response: log.responses,
+                first_source: String::from(source),
+            });
+            field.count += 1;
+        }
+    }
+}
+
+impl EndpointLog {
+    fn count(&self, path: &str) -> u64 {
+        if path.is_empty() {
+            self.responses
+        } else {
+            self.fields.get(path).map_or(0, |f| f.count)
+        }
+    }
+
+    /// Expected fields that were missing from responses that had the field's
+    /// parent, so a game without goals doesn't count as missing goal fields
+    pub fn missing_fields(&self, endpoint: Endpoint) -> Vec<MissingField> {
+        endpoint
+            .expected_fields()
+            .iter()
+            .map(|path| MissingField {
+                path,
+                missing: self.count(parent_path(path)).saturating_sub(self.count(path)),
+                applicable: self.count(parent_path(path)),
+            })
+            .filter(|f| f.missing > 0)
+            .collect()
+    }
+
+    /// Fields the downloader doesn't read, e.g. ones the NHL has added
+    pub fn unknown_fields(&self, endpoint: Endpoint) -> Vec<(&str, &FieldLog)> {
+        let mut known = BTreeSet::new();
+        for path in endpoint.expected_fields() {
+            let mut path = *path;
+            while !path.is_empty() {
+                known.insert(path);
+                path = parent_path(path);
+            }
+        }
+        self.fields
+            .iter()
+            .filter(|(path, _)| !known.contains(path.as_str()))
+            .map(|(path, field)| (path.as_str(), field))
+            .collect()
+    }
+
+    /// Unknown fields that first showed up in the latest run: likely new
+    /// In the first run logged, that's the ones that weren't in its first
+    /// response.
+    pub fn new_fields(&self, endpoint: Endpoint) -> Vec<(&str, &FieldLog)> {
+        self.unknown_fields(endpoint)
+            .into_iter()
+            .filter(|(_, field)| field.first_response > self.run_start.max(1))
+            .collect()
+    }
+}
+
+/// The log being recorded to in strict schema mode, if any
+static RECORDING: Mutex<Option<SchemaLog>> = Mutex::new(None);
+
+/// Turns on strict schema mode: every response from the NHL is logged for
+/// the rest of the run, as a new run in the log
+pub fn start_recording(mut log: SchemaLog) {
+    log.start_run();
+    *RECORDING.lock().unwrap_or_else(|e| e.into_inner()) = Some(log);
+}
+
+/// Logs a response's fields, if strict schema mode is on
+pub fn record_response(endpoint: Endpoint, source: &str, response: &str) {
+    if let Some(log) = RECORDING.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+        log.record(endpoint, source, response);
+    }
+}
+
+/// The log recorded so far, if strict schema mode is on
+pub fn recording() -> Option<SchemaLog> {
+    RECORDING.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn field_paths_of_arrays_and_maps() {
+        let sprites: Value = serde_json::from_str(
+            r#"[{"timeStamp":100,"onIce":{"1":{"id":1,"x":1200,"y":510},"2":{"id":2,"x":1,"y":2,"teamId":1}}}]"#,
+        )
+        .unwrap();
+        let paths: Vec<String> = field_paths(Endpoint::Sprites, &sprites).into_iter().collect();
+        assert_eq!(
+            paths,
+            vec!["[]", "[].onIce", "[].onIce.*", "[].onIce.*.id", "[].onIce.*.teamId", "[].onIce.*.x", "[].onIce.*.y", "[].timeStamp"]
+        );
+
+        let schedule: Value = serde_json::from_str(r#"{"gameWeek":[{"date":"2025-10-26","games":[]}]}"#).unwrap();
+        let paths: Vec<String> = field_paths(Endpoint::Schedule, &schedule).into_iter().collect();
+        assert_eq!(paths, vec!["gameWeek", "gameWeek[]", "gameWeek[].date", "gameWeek[].games"]);
+    }
+
+    #[test]
+    fn record_finds_missing_and_new_fields() {
+        let mut log = SchemaLog::default();
+        let landing = |goal: &str, extra: &str| {
+            format!(
+                r#"{{"id":1,"season":20252026,"gameDate":"2025-10-26","gameState":"OFF","homeTeam":{{"id":1}},"awayTeam":{{"id":21}},"venue":"X"{},"summary":{{"scoring":[{{"periodDescriptor":{{"periodType":"REG"}},"goals":[{}]}}]}}}}"#,
+                extra, goal
+            )
+        };
+        let goal = r#"{"eventId":663,"pptReplayUrl":"u","homeTeamDefendingSide":"left","isHome":true,"situationCode":"1551"}"#;
+        log.record(Endpoint::Landing, "game 1", &landing(goal, ""));
+        // a scoreless game isn't missing goal fields
+        log.record(Endpoint::Landing, "game 2", &landing("", ""));
+        log.record(Endpoint::Landing, "game 3", &landing(r#"{"eventId":700,"homeTeamDefendingSide":"left","isHome":true}"#, r#","clock":1"#));
+        log.record(Endpoint::Landing, "game 4", "<html>");
+
+        let landing_log = &log.endpoints[&Endpoint::Landing];
+        assert_eq!((landing_log.responses, landing_log.unreadable), (3, 1));
+        let missing: Vec<(&str, u64, u64)> = landing_log
+            .missing_fields(Endpoint::Landing)
+            .iter()
+            .map(|f| (f.path, f.missing, f.applicable))
+            .collect();
+        assert_eq!(
+            missing,
+            vec![
+                ("summary.scoring[].goals[].pptReplayUrl", 1, 2),
+                ("summary.scoring[].goals[].situationCode", 1, 2),
+            ]
+        );
+        let unknown: Vec<&str> = landing_log.unknown_fields(Endpoint::Landing).iter().map(|(p, _)| *p).collect();
+        assert_eq!(unknown, vec!["clock", "venue"]);
+        let new = landing_log.new_fields(Endpoint::Landing);
+        assert_eq!(new.len(), 1);
+        assert_eq!((new[0].0, new[0].1.first_response, new[0].1.first_source.as_str()), ("clock", 3, "game 3"));
+
+        // in the next run only the fields that first show up in it are new
+        log.start_run();
+        log.record(Endpoint::Landing, "game 5", &landing(goal, r#","clock":1,"tvBroadcasts":[]"#));
+        let landing_log = &log.endpoints[&Endpoint::Landing];
+        assert_eq!(landing_log.run_start, 3);
+        let new: Vec<&str> = landing_log.new_fields(Endpoint::Landing).iter().map(|(p, _)| *p).collect();
+        assert_eq!(new, vec!["tvBroadcasts"]);
+    }
+}
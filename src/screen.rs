@@ -0,0 +1,214 @@
+use crate::normalize::{GOAL_LINE_X_FT, NET_HALF_WIDTH_FT};
+use crate::shot::ShotRelease;
+use crate::tracking::{Entity, Frame};
+
+/// How close (in feet) a player has to be to the line from the puck to the
+/// goalie to block the goalie's view of it: about half a body's width
+pub const SIGHTLINE_HALF_WIDTH_FT: f64 = 1.5;
+
+/// How far from the center of the net (in feet) to look for the goalie when
+/// the roster doesn't say who the goalies are
+const GOALIE_SEARCH_RADIUS_FT: f64 = 15.0;
+
+/// Traffic between the shooter and the net at the shot release
+#[derive(Debug, Clone, PartialEq)]
+pub struct Screen {
+    /// skaters from either team (not the shooter or goalie) inside the
+    /// triangle between the puck and the posts
+    pub skaters_in_triangle: usize,
+    pub attackers_in_triangle: usize,
+    pub defenders_in_triangle: usize,
+    /// distance from the shooter to the nearest skater on the other team
+    pub nearest_defender_ft: Option<f64>,
+    /// entity id of the goalie defending the shot, if one was found
+    pub goalie_id: Option<u32>,
+    /// whether a teammate of the shooter was on the line from the puck to the
+    /// goalie (or to the middle of the net, with no goalie)
+    pub teammate_in_sightline: bool,
+}
+
+/// Measures the traffic at a shot's release
+/// The frames need to be normalized (see `normalize_frames`) so the net is at
+/// (89, 0).  Goalies are taken from `goalie_ids` (the roster's player ids);
+/// if none of them are on the ice for the defending team, the defending
+/// player nearest the net, within 15 ft of it, is taken to be the goalie.
+pub fn measure_screen(frames: &[Frame], shot: &ShotRelease, scoring_team_id: u16, goalie_ids: &[u32]) -> Screen {
+    let frame = &frames[shot.release_frame];
+    let (puck_x, puck_y) = (shot.release_x, shot.release_y);
+    let shooter = frame.entities.iter().find(|e| e.id == shot.shooter_id);
+    let goalie = find_goalie(frame, scoring_team_id, goalie_ids);
+    let goalie_id = goalie.map(|g| g.id);
+
+    let skaters: Vec<&Entity> = frame
+        .players()
+        .filter(|p| {
+            Some(p.id) != goalie_id
+                && p.id != shot.shooter_id
+                && !p.player_id.is_some_and(|id| goalie_ids.contains(&id))
+        })
+        .collect();
+    let in_triangle: Vec<&&Entity> = skaters
+        .iter()
+        .filter(|p| {
+            in_triangle(
+                (p.x, p.y),
+                (puck_x, puck_y),
+                (GOAL_LINE_X_FT, NET_HALF_WIDTH_FT),
+                (GOAL_LINE_X_FT, -NET_HALF_WIDTH_FT),
+            )
+        })
+        .collect();
+    let attackers_in_triangle = in_triangle
+        .iter()
+        .filter(|p| p.team_id == Some(scoring_team_id))
+        .count();
+
+    let nearest_defender_ft = shooter.and_then(|shooter| {
+        skaters
+            .iter()
+            .filter(|p| p.team_id != Some(scoring_team_id))
+            .map(|p| (p.x - shooter.x).hypot(p.y - shooter.y))
+            .min_by(|a, b| a.total_cmp(b))
+    });
+
+    let target = goalie.map_or((GOAL_LINE_X_FT, 0.0), |g| (g.x, g.y));
+    let teammate_in_sightline = skaters.iter().any(|p| {
+        p.team_id == Some(scoring_team_id)
+            && distance_to_segment((p.x, p.y), (puck_x, puck_y), target) <= SIGHTLINE_HALF_WIDTH_FT
+    });
+
+    Screen {
+        skaters_in_triangle: in_triangle.len(),
+        attackers_in_triangle,
+        defenders_in_triangle: in_triangle.len() - attackers_in_triangle,
+        nearest_defender_ft,
+        goalie_id,
+        teammate_in_sightline,
+    }
+}
+
+/// The defending team's goalie in a frame
+fn find_goalie<'a>(frame: &'a Frame, scoring_team_id: u16, goalie_ids: &[u32]) -> Option<&'a Entity> {
+    let defenders: Vec<&Entity> = frame
+        .players()
+        .filter(|p| p.team_id != Some(scoring_team_id))
+        .collect();
+    if let Some(goalie) = defenders.iter().find(|p| p.player_id.is_some_and(|id| goalie_ids.contains(&id))) {
+        return Some(goalie);
+    }
+    defenders
+        .into_iter()
+        .map(|p| (p, (p.x - GOAL_LINE_X_FT).hypot(p.y)))
+        .filter(|(_, d)| *d <= GOALIE_SEARCH_RADIUS_FT)
+        .min_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(p, _)| p)
+}
+
+/// Whether a point is inside a triangle, by checking it's on the same side
+/// of all three edges
+fn in_triangle(p: (f64, f64), a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> bool {
+    let cross = |u: (f64, f64), v: (f64, f64)| (v.0 - u.0) * (p.1 - u.1) - (v.1 - u.1) * (p.0 - u.0);
+    let (d1, d2, d3) = (cross(a, b), cross(b, c), cross(c, a));
+    let has_negative = d1 < 0.0 || d2 < 0.0 || d3 < 0.0;
+    let has_positive = d1 > 0.0 || d2 > 0.0 || d3 > 0.0;
+    !(has_negative && has_positive)
+}
+
+/// Distance from a point to the line segment between a and b
+fn distance_to_segment(p: (f64, f64), a: (f64, f64), b: (f64, f64)) -> f64 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let length_squared = dx * dx + dy * dy;
+    let u = if length_squared > 0.0 {
+        (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / length_squared).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    (p.0 - (a.0 + u * dx)).hypot(p.1 - (a.1 + u * dy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::{entity, player};
+
+    fn shot(release_x: f64, release_y: f64) -> ShotRelease {
+        ShotRelease {
+            release_frame: 0,
+            goal_line_frame: None,
+            shooter_id: 10,
+            release_x,
+            release_y,
+            distance_ft: 0.0,
+            angle_deg: 0.0,
+            release_speed_ft_s: 0.0,
+        }
+    }
+
+    // a shot from the slot with a teammate screening the goalie and a
+    // defender tying up another teammate in front
+    #[test]
+    fn measure_screen_traffic() {
+        let frames = vec![Frame {
+            time_stamp: 0.0,
+            entities: vec![
+                entity(1, None, 60.0, 0.0),
+                entity(10, Some(22), 59.0, 0.0),
+                entity(20, Some(22), 80.0, 0.5),
+                entity(30, Some(22), 75.0, 1.0),
+                entity(4, Some(15), 75.0, -1.0),
+                entity(5, Some(15), 62.0, 4.0),
+                entity(31, Some(15), 87.0, 0.0),
+                // off to the side, out of the play
+                entity(40, Some(22), 70.0, -20.0),
+            ],
+        }];
+        let screen = measure_screen(&frames, &shot(60.0, 0.0), 22, &[31]);
+        assert_eq!(screen.skaters_in_triangle, 3);
+        assert_eq!(screen.attackers_in_triangle, 2);
+        assert_eq!(screen.defenders_in_triangle, 1);
+        assert_eq!(screen.goalie_id, Some(31));
+        assert!(screen.teammate_in_sightline);
+        assert!((screen.nearest_defender_ft.unwrap() - 5.0).abs() < 1e-9);
+
+        // without the roster, the goalie is the defender nearest the net
+        let screen = measure_screen(&frames, &shot(60.0, 0.0), 22, &[]);
+        assert_eq!(screen.goalie_id, Some(31));
+        assert_eq!(screen.skaters_in_triangle, 3);
+    }
+
+    #[test]
+    fn measure_screen_clear_look() {
+        let frames = vec![Frame {
+            time_stamp: 0.0,
+            entities: vec![
+                entity(1, None, 60.0, 30.0),
+                entity(10, Some(22), 59.0, 30.0),
+                entity(31, Some(15), 87.0, 2.0),
+                entity(20, Some(22), 70.0, 0.0),
+            ],
+        }];
+        let screen = measure_screen(&frames, &shot(60.0, 30.0), 22, &[31]);
+        assert_eq!(screen.skaters_in_triangle, 0);
+        assert!(!screen.teammate_in_sightline);
+        assert_eq!(screen.nearest_defender_ft, None);
+    }
+
+    // the roster's goalie is found by player id, even with a defender nearer
+    // the net
+    #[test]
+    fn measure_screen_goalie_by_player_id() {
+        let frames = vec![Frame {
+            time_stamp: 0.0,
+            entities: vec![
+                entity(1, None, 60.0, 30.0),
+                entity(10, Some(22), 59.0, 30.0),
+                player(31, 8479361, Some(15), 87.0, 2.0),
+                entity(6, Some(15), 88.5, -1.0),
+            ],
+        }];
+        let screen = measure_screen(&frames, &shot(60.0, 30.0), 22, &[8479361]);
+        assert_eq!(screen.goalie_id, Some(31));
+        assert_eq!(screen.skaters_in_triangle, 1);
+    }
+}
@@ -0,0 +1,260 @@
+use anyhow::{anyhow, Result};
+
+use chrono::NaiveDate;
+
+use serde::Serialize;
+
+use crate::api_calls::GoalDetails;
+use crate::mirror::handle_mirror_request;
+use crate::reader::OutputReader;
+use crate::render::read_normalized_goal;
+use crate::tracking::Frame;
+
+/// Address the server listens on by default: only reachable from this machine
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:8080";
+
+/// A response to an API request
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiResponse {
+    pub status: u16,
+    /// always JSON, even for errors
+    pub body: String,
+}
+
+impl ApiResponse {
+    pub(crate) fn json<T>(value: &T) -> ApiResponse
+    where
+        T: Serialize,
+    {
+        match serde_json::to_string(value) {
+            Ok(body) => ApiResponse { status: 200, body },
+            Err(e) => ApiResponse::error(500, &e.to_string()),
+        }
+    }
+
+    pub(crate) fn error(status: u16, message: &str) -> ApiResponse {
+        ApiResponse {
+            status,
+            body: serde_json::json!({ "error": message }).to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct GameRow {
+    game_id: u32,
+    /// YYYY-MM-DD
+    date: String,
+}
+
+#[derive(Debug, Serialize)]
+struct GoalsResponse {
+    game_id: u32,
+    home_team_id: u16,
+    goals: Vec<GoalDetails>,
+}
+
+#[derive(Debug, Serialize)]
+struct FramesResponse {
+    game_id: u32,
+    event_id: u32,
+    normalized: bool,
+    frames: Vec<Frame>,
+}
+
+/// Serves the data in an output folder (or archive) over HTTP until the
+/// process is stopped
+/// The folder is indexed once at startup, so games downloaded afterwards
+/// need a restart to show up.  See `handle_request` for the endpoints, or
+/// `handle_mirror_request` for the ones served with `mirror`, which imitate
+/// the NHL's.
+pub fn serve(reader: &OutputReader, address: &str, mirror: bool) -> Result<()> {
+    let server = tiny_http::Server::http(address).map_err(|e| anyhow!("Unable to listen on {}: {}", address, e))?;
+    let base_url = format!("http://{}", address);
+    println!("Serving {} games on {}", reader.games().len(), base_url);
+
+    let content_type = tiny_http::Header::from_bytes("Content-Type", "application/json")
+        .map_err(|_| anyhow!("Invalid content type header"))?;
+    for request in server.incoming_requests() {
+        let response = if *request.method() != tiny_http::Method::Get {
+            ApiResponse::error(405, "Only GET requests are supported")
+        } else if mirror {
+            handle_mirror_request(reader, &base_url, request.url())
+        } else {
+            handle_request(reader, request.url())
+        };
+        let http_response = tiny_http::Response::from_string(response.body)
+            .with_status_code(response.status)
+            .with_header(content_type.clone());
+        if let Err(e) = request.respond(http_response) {
+            println!("Unable to send a response: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Answers a GET request for a URL (the path and query string)
+///
+/// - `/games`, optionally `?date=YYYY-MM-DD`: the games, in game id order
+/// - `/games/{game}/goals`: the game's home team and goal details
+/// - `/goals/{game}/{event}/tracking`: the raw tracking JSON as downloaded
+/// - `/goals/{game}/{event}/frames`: the parsed frames, in the canonical
+///   frame of reference with `?normalized=true`
+pub fn handle_request(reader: &OutputReader, url: &str) -> ApiResponse {
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let params: Vec<(&str, &str)> = query
+        .split('&')
+        .filter(|p| !p.is_empty())
+        .map(|p| p.split_once('=').unwrap_or((p, "")))
+        .collect();
+    let param = |name: &str| params.iter().find(|(key, _)| *key == name).map(|(_, value)| *value);
+    let segments: Vec<&str> = path.split('/').filter(|s| !s.is_empty()).collect();
+
+    match segments[..] {
+        ["games"] => {
+            let date = match param("date").map(|d| d.parse::<NaiveDate>()) {
+                Some(Ok(date)) => Some(date),
+                Some(Err(_)) => return ApiResponse::error(400, "The date needs to be in YYYY-MM-DD format"),
+                None => None,
+            };
+            let games: Vec<GameRow> = reader
+                .games()
+                .into_iter()
+                .filter(|g| date.is_none_or(|date| g.date == date))
+                .map(|g| GameRow { game_id: g.game_id, date: g.date.to_string() })
+                .collect();
+            ApiResponse::json(&games)
+        }
+        ["games", game_id, "goals"] => {
+            let game_id = match find_game(reader, game_id) {
+                Ok(game_id) => game_id,
+                Err(response) => return response,
+            };
+            match reader.read_game_data(game_id) {
+                Ok(game_data) => ApiResponse::json(&GoalsResponse {
+                    game_id,
+                    home_team_id: game_data.home_team_id,
+                    goals: game_data.goals,
+                }),
+                Err(e) => ApiResponse::error(500, &format!("{:#}", e)),
+            }
+        }
+        ["goals", game_id, event_id, endpoint @ ("tracking" | "frames")] => {
+            let game_id = match find_game(reader, game_id) {
+                Ok(game_id) => game_id,
+                Err(response) => return response,
+            };
+            let Ok(event_id) = event_id.parse::<u32>() else {
+                return ApiResponse::error(400, &format!("Invalid event id: {}", event_id));
+            };
+            let goal_exists = reader
+                .read_game_data(game_id)
+                .is_ok_and(|game_data| game_data.goals.iter().any(|g| g.event_id == event_id));
+            if !goal_exists {
+                return ApiResponse::error(404, &format!("No goal {} in game {}", event_id, game_id));
+            }
+
+            if endpoint == "tracking" {
+                return match reader.read_tracking(game_id, event_id) {
+                    Ok(tracking) => ApiResponse { status: 200, body: tracking },
+                    Err(e) => ApiResponse::error(404, &format!("{:#}", e)),
+                };
+            }
+            let normalized = match param("normalized") {
+                None | Some("false") => false,
+                Some("true") => true,
+                Some(value) => return ApiResponse::error(400, &format!("normalized needs to be true or false: {}", value)),
+            };
+            let frames = if normalized {
+                read_normalized_goal(reader, game_id, event_id).map(|goal| goal.frames)
+            } else {
+                reader.read_frames(game_id, event_id)
+            };
+            match frames {
+                Ok(frames) => ApiResponse::json(&FramesResponse {
+                    game_id,
+                    event_id,
+                    normalized,
+                    frames,
+                }),
+                Err(e) => ApiResponse::error(404, &format!("{:#}", e)),
+            }
+        }
+        _ => ApiResponse::error(404, &format!("Unknown endpoint: {}", path)),
+    }
+}
+
+/// Parses a game id from a URL, as long as the game's in the output folder
+fn find_game(reader: &OutputReader, game_id: &str) -> Result<u32, ApiResponse> {
+    let Ok(game_id) = game_id.parse::<u32>() else {
+        return Err(ApiResponse::error(400, &format!("Invalid game id: {}", game_id)));
+    };
+    if reader.games().iter().any(|g| g.game_id == game_id) {
+        Ok(game_id)
+    } else {
+        Err(ApiResponse::error(404, &format!("No game {}", game_id)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::{temp_folder, write_goal_details};
+
+    use std::fs::write;
+    use std::path::PathBuf;
+
+    fn output_folder(name: &str) -> PathBuf {
+        let root = temp_folder(&format!("server_{}", name));
+        let game_path = root.join("2025-10-26").join("2025020140");
+        write_goal_details(&game_path, &[663]);
+        // home team defends the left, so the frames are already normalized
+        // apart from the units
+        write(
+            game_path.join("663"),
+            r#"[{"timeStamp":100,"onIce":{"1":{"id":1,"playerId":"","x":2268,"y":510}}}]"#,
+        )
+        .unwrap();
+        root
+    }
+
+    #[test]
+    fn handle_request_endpoints() {
+        let reader = OutputReader::open(output_folder("endpoints")).unwrap();
+
+        let games = handle_request(&reader, "/games?date=2025-10-26");
+        assert_eq!(games.status, 200);
+        assert_eq!(games.body, r#"[{"game_id":2025020140,"date":"2025-10-26"}]"#);
+        assert_eq!(handle_request(&reader, "/games?date=2025-10-27").body, "[]");
+
+        let goals = handle_request(&reader, "/games/2025020140/goals");
+        assert_eq!(goals.status, 200);
+        assert!(goals.body.contains(r#""event_id":663"#));
+
+        let tracking = handle_request(&reader, "/goals/2025020140/663/tracking");
+        assert!(tracking.body.starts_with(r#"[{"timeStamp":100"#));
+
+        let frames = handle_request(&reader, "/goals/2025020140/663/frames?normalized=true");
+        assert_eq!(frames.status, 200);
+        let frames: serde_json::Value = serde_json::from_str(&frames.body).unwrap();
+        assert_eq!(frames["normalized"], true);
+        assert_eq!(frames["frames"][0]["entities"][0]["x"], 89.0);
+        let raw: serde_json::Value =
+            serde_json::from_str(&handle_request(&reader, "/goals/2025020140/663/frames").body).unwrap();
+        assert_eq!(raw["frames"][0]["entities"][0]["x"], 2268.0);
+    }
+
+    #[test]
+    fn handle_request_errors() {
+        let reader = OutputReader::open(output_folder("errors")).unwrap();
+        assert_eq!(handle_request(&reader, "/games?date=26-10-2025").status, 400);
+        assert_eq!(handle_request(&reader, "/games/abc/goals").status, 400);
+        assert_eq!(handle_request(&reader, "/games/2025020141/goals").status, 404);
+        assert_eq!(handle_request(&reader, "/goals/2025020140/664/tracking").status, 404);
+        assert_eq!(handle_request(&reader, "/goals/2025020140/663/frames?normalized=yes").status, 400);
+        let unknown = handle_request(&reader, "/players");
+        assert_eq!(unknown.status, 404);
+        assert_eq!(unknown.body, r#"{"error":"Unknown endpoint: /players"}"#);
+    }
+}
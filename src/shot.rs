@@ -0,0 +1,223 @@
+use crate::kinematics::{compute_kinematics, KinematicsOptions};
+use crate::normalize::{GOAL_LINE_X_FT, NET_DEPTH_FT, NET_HALF_WIDTH_FT};
+use crate::tracking::Frame;
+
+/// How close (in feet) a player has to be to the puck to be the one shooting
+/// it
+pub const SHOOTER_RADIUS_FT: f64 = 6.0;
+
+/// How far back (in seconds) from the puck crossing the goal line to look for
+/// the release
+pub const MAX_SHOT_SECONDS: f64 = 3.0;
+
+/// How far outside the posts (in feet) the puck can be and still count as
+/// crossing the goal line into the net, to allow for tracking error
+const GOAL_LINE_TOLERANCE_FT: f64 = 3.0;
+
+/// The shot that led to a goal, in the canonical frame of reference
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShotRelease {
+    /// index of the frame the puck left the shooter's stick
+    pub release_frame: usize,
+    /// index of the frame the puck crossed the goal line into the net, if it
+    /// was tracked that far
+    pub goal_line_frame: Option<usize>,
+    /// entity id of the shooter: the scoring team's player nearest the puck
+    /// at the release
+    pub shooter_id: u32,
+    pub release_x: f64,
+    pub release_y: f64,
+    /// distance from the release to the center of the net
+    pub distance_ft: f64,
+    /// angle of the release away from straight on, in degrees
+    pub angle_deg: f64,
+    /// top puck speed between the release and the goal line
+    pub release_speed_ft_s: f64,
+}
+
+/// Finds the shot release for a goal
+/// The frames need to be normalized (see `normalize_frames`) so the scoring
+/// team is attacking the net on the right.  The release is the biggest jump
+/// in puck speed, within `MAX_SHOT_SECONDS` of the puck crossing the goal
+/// line, where the puck is moving away from a scoring team player within
+/// `SHOOTER_RADIUS_FT` of it.  Only the puck crossing the goal line between
+/// the posts counts, going from in front of the line to no deeper than the
+/// net, not where it came from: a puck carried along behind the net doesn't
+/// cross it, but a wraparound tucked in from the side does.  Returns None if
+/// the puck isn't tracked or no release fits.
+pub fn detect_shot(frames: &[Frame], scoring_team_id: u16) -> Option<ShotRelease> {
+    let first = frames.first()?;
+    let kinematics = compute_kinematics(frames, &KinematicsOptions { units_per_foot: 1.0, smoothing_window: 1 });
+    let puck_speed = |i: usize| -> Option<f64> {
+        let j = frames[i].entities.iter().position(|e| e.is_puck())?;
+        Some(kinematics[i][j].speed_ft_s)
+    };
+
+    let in_net = |x: f64, y: f64| {
+        (GOAL_LINE_X_FT..=GOAL_LINE_X_FT + NET_DEPTH_FT).contains(&x)
+            && y.abs() <= NET_HALF_WIDTH_FT + GOAL_LINE_TOLERANCE_FT
+    };
+    let goal_line_frame = frames
+        .windows(2)
+        .position(|w| match (w[0].puck(), w[1].puck()) {
+            (Some(puck), Some(next_puck)) => puck.x < GOAL_LINE_X_FT && in_net(next_puck.x, next_puck.y),
+            _ => false,
+        })
+        .map(|i| i + 1);
+    let end = goal_line_frame.unwrap_or(frames.len() - 1);
+    let end_seconds = frames[end].seconds_since(first);
+
+    // the biggest jump in puck speed with the puck leaving a scoring team
+    // player: (frame, shooter, speed change per second)
+    let mut release: Option<(usize, u32, f64)> = None;
+    for i in 0..end {
+        if end_seconds - frames[i].seconds_since(first) > MAX_SHOT_SECONDS {
+            continue;
+        }
+        let (puck, next_puck) = match (frames[i].puck(), frames[i + 1].puck()) {
+            (Some(puck), Some(next_puck)) => (puck, next_puck),
+            _ => continue,
+        };
+        let shooter = match frames[i]
+            .players()
+            .filter(|p| p.team_id == Some(scoring_team_id))
+            .map(|p| (p, distance(p.x, p.y, puck.x, puck.y)))
+            .filter(|(_, d)| *d <= SHOOTER_RADIUS_FT)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+        {
+            Some((shooter, _)) => shooter,
+            None => continue,
+        };
+
+        // the puck has to be getting further from the shooter
+        let next_shooter = frames[i + 1].entities.iter().find(|e| e.id == shooter.id);
+        let moving_away = match next_shooter {
+            Some(s) => distance(s.x, s.y, next_puck.x, next_puck.y) > distance(shooter.x, shooter.y, puck.x, puck.y),
+            None => true,
+        };
+        let dt = frames[i + 1].seconds_since(&frames[i]);
+        if !moving_away || dt <= 0.0 {
+            continue;
+        }
+
+        let (speed, next_speed) = match (puck_speed(i), puck_speed(i + 1)) {
+            (Some(speed), Some(next_speed)) => (speed, next_speed),
+            _ => continue,
+        };
+        let jump = (next_speed - speed) / dt;
+        if jump > 0.0 && release.is_none_or(|(_, _, best)| jump >= best) {
+            release = Some((i, shooter.id, jump));
+        }
+    }
+
+    let (release_frame, shooter_id, _) = release?;
+    let puck = frames[release_frame].puck()?;
+    let release_speed_ft_s = (release_frame..=end)
+        .filter_map(puck_speed)
+        .fold(0.0, f64::max);
+
+    Some(ShotRelease {
+        release_frame,
+        goal_line_frame,
+        shooter_id,
+        release_x: puck.x,
+        release_y: puck.y,
+        distance_ft: distance(puck.x, puck.y, GOAL_LINE_X_FT, 0.0),
+        angle_deg: puck.y.abs().atan2(GOAL_LINE_X_FT - puck.x).to_degrees(),
+        release_speed_ft_s,
+    })
+}
+
+fn distance(x1: f64, y1: f64, x2: f64, y2: f64) -> f64 {
+    (x2 - x1).hypot(y2 - y1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::entity;
+
+    /// A shooter skating in on the net from (49, 30) carrying the puck, and
+    /// shooting it at the middle of the net at frame 5, with a defender
+    /// nearby the whole time
+    fn shot_frames() -> Vec<Frame> {
+        (0..15)
+            .map(|i| {
+                let shooter_x = 49.0 + 2.0 * i.min(5) as f64;
+                let puck = if i <= 5 {
+                    (shooter_x + 1.0, 30.0)
+                } else {
+                    // straight at the center of the net
+                    let u = (10.0 * (i - 5) as f64 / 50.0).min(1.0);
+                    (60.0 + 29.0 * u + 0.01 * u, 30.0 - 30.0 * u)
+                };
+                Frame {
+                    time_stamp: i as f64,
+                    entities: vec![
+                        entity(1, None, puck.0, puck.1),
+                        entity(8478402, Some(22), shooter_x, 30.0),
+                        entity(8471214, Some(15), shooter_x + 3.0, 40.0),
+                    ],
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detect_shot_release() {
+        let frames = shot_frames();
+        let shot = detect_shot(&frames, 22).unwrap();
+        assert_eq!(shot.release_frame, 5);
+        assert_eq!(shot.shooter_id, 8478402);
+        assert_eq!(shot.goal_line_frame, Some(10));
+        assert_eq!((shot.release_x, shot.release_y), (60.0, 30.0));
+        assert!((shot.distance_ft - 29.0_f64.hypot(30.0)).abs() < 1e-9);
+        assert!((shot.angle_deg - 30.0_f64.atan2(29.0).to_degrees()).abs() < 1e-9);
+        assert!((shot.release_speed_ft_s - 84.0).abs() < 1.0);
+    }
+
+    // no scoring team player ever has the puck
+    #[test]
+    fn detect_shot_wrong_team() {
+        assert_eq!(detect_shot(&shot_frames(), 15), None);
+    }
+
+    /// A carry from the corner behind the net and out the other side, then a
+    /// wraparound tucked in at frame 10
+    #[test]
+    fn detect_shot_wraparound() {
+        let puck = [
+            (80.0, 12.0),
+            (84.0, 10.0),
+            (88.0, 9.0),
+            (92.0, 8.0),
+            (92.0, 4.0),
+            (92.0, 0.0),
+            (92.0, -4.0),
+            (91.0, -7.0),
+            (88.0, -6.0),
+            (85.0, -4.0),
+            (89.5, -1.0),
+        ];
+        let frames: Vec<Frame> = puck
+            .iter()
+            .enumerate()
+            .map(|(i, &(x, y))| {
+                let (shooter_x, shooter_y) = puck[i.min(9)];
+                Frame {
+                    time_stamp: i as f64,
+                    entities: vec![entity(1, None, x, y), entity(8478402, Some(22), shooter_x - 1.0, shooter_y)],
+                }
+            })
+            .collect();
+        let shot = detect_shot(&frames, 22).unwrap();
+        assert_eq!(shot.goal_line_frame, Some(10));
+        assert_eq!(shot.release_frame, 9);
+    }
+
+    #[test]
+    fn detect_shot_no_frames() {
+        assert_eq!(detect_shot(&[], 22), None);
+    }
+}
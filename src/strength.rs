@@ -0,0 +1,136 @@
+use anyhow::{anyhow, Result};
+
+use crate::tracking::Frame;
+
+/// The number of skaters and whether the goalie is in net, for each team
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Strength {
+    pub away_goalie: bool,
+    pub away_skaters: usize,
+    pub home_skaters: usize,
+    pub home_goalie: bool,
+}
+
+impl Strength {
+    /// Parses the NHL's situation code: four digits for the away goalie (1 in
+    /// net, 0 pulled), away skaters, home skaters and home goalie, e.g. "1541"
+    /// for an away power play
+    pub fn from_situation_code(code: &str) -> Result<Strength> {
+        let digits: Vec<usize> = code
+            .chars()
+            .map(|c| c.to_digit(10).map(|d| d as usize))
+            .collect::<Option<_>>()
+            .ok_or_else(|| anyhow!("Invalid situation code: {}", code))?;
+        match digits[..] {
+            [away_goalie @ 0..=1, away_skaters, home_skaters, home_goalie @ 0..=1] => Ok(Strength {
+                away_goalie: away_goalie == 1,
+                away_skaters,
+                home_skaters,
+                home_goalie: home_goalie == 1,
+            }),
+            _ => Err(anyhow!("Invalid situation code: {}", code)),
+        }
+    }
+
+    /// The strength in the same four digit format as the NHL's situation code
+    pub fn situation_code(&self) -> String {
+        format!(
+            "{}{}{}{}",
+            self.away_goalie as u8, self.away_skaters, self.home_skaters, self.home_goalie as u8
+        )
+    }
+}
+
+/// The strength seen in the tracking data compared with the NHL's
+#[derive(Debug, Clone, PartialEq)]
+pub struct StrengthCheck {
+    pub tracked: Strength,
+    pub reported: Option<Strength>,
+}
+
+impl StrengthCheck {
+    /// Whether the tracking data and the situation code disagree; None if
+    /// there's no situation code to compare with
+    pub fn mismatch(&self) -> Option<bool> {
+        self.reported.map(|reported| reported != self.tracked)
+    }
+}
+
+/// Counts each team's skaters and goalies on the ice in a frame
+/// Goalies are told apart from skaters using `goalie_ids` (the roster's player
+/// ids), and every player not on the home team is counted for the away team.
+pub fn tracked_strength(frame: &Frame, home_team_id: u16, goalie_ids: &[u32]) -> Strength {
+    let mut strength = Strength {
+        away_goalie: false,
+        away_skaters: 0,
+        home_skaters: 0,
+        home_goalie: false,
+    };
+    for p in frame.players() {
+        let is_home = p.team_id == Some(home_team_id);
+        let is_goalie = p.player_id.is_some_and(|id| goalie_ids.contains(&id));
+        match (is_home, is_goalie) {
+            (true, true) => strength.home_goalie = true,
+            (true, false) => strength.home_skaters += 1,
+            (false, true) => strength.away_goalie = true,
+            (false, false) => strength.away_skaters += 1,
+        }
+    }
+    strength
+}
+
+/// Compares the strength in a frame (usually the one the goal was scored in)
+/// with the NHL's situation code for the goal
+/// A situation code that can't be parsed is treated as missing.
+pub fn check_strength(
+    frame: &Frame,
+    home_team_id: u16,
+    goalie_ids: &[u32],
+    situation_code: Option<&str>,
+) -> StrengthCheck {
+    StrengthCheck {
+        tracked: tracked_strength(frame, home_team_id, goalie_ids),
+        reported: situation_code.and_then(|code| Strength::from_situation_code(code).ok()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::{entity, player};
+
+    #[test]
+    fn from_situation_code_valid() {
+        let strength = Strength::from_situation_code("0651").unwrap();
+        assert_eq!(
+            strength,
+            Strength { away_goalie: false, away_skaters: 6, home_skaters: 5, home_goalie: true }
+        );
+        assert_eq!(strength.situation_code(), "0651");
+        assert!(Strength::from_situation_code("155").is_err());
+        assert!(Strength::from_situation_code("2551").is_err());
+        assert!(Strength::from_situation_code("15a1").is_err());
+    }
+
+    // home team (10) with 5 skaters and its goalie, away team (19) on a
+    // power play with its goalie pulled
+    #[test]
+    fn check_strength_disagreement() {
+        let mut entities = vec![entity(1, None, 0.0, 0.0), player(30, 8475883, Some(10), 0.0, 0.0)];
+        entities.extend((100..105).map(|id| entity(id, Some(10), 0.0, 0.0)));
+        entities.extend((200..206).map(|id| entity(id, Some(19), 0.0, 0.0)));
+        let frame = Frame { time_stamp: 0.0, entities };
+        let goalie_ids = [8475883, 8476999];
+
+        let check = check_strength(&frame, 10, &goalie_ids, Some("0651"));
+        assert_eq!(check.tracked.situation_code(), "0651");
+        assert_eq!(check.mismatch(), Some(false));
+
+        // the play-by-play missed the goalie being pulled
+        let check = check_strength(&frame, 10, &goalie_ids, Some("1551"));
+        assert_eq!(check.mismatch(), Some(true));
+
+        assert_eq!(check_strength(&frame, 10, &goalie_ids, None).mismatch(), None);
+    }
+}
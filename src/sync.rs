@@ -0,0 +1,108 @@
+use anyhow::Result;
+
+use chrono::NaiveDate;
+
+use serde::{Deserialize, Serialize};
+
+use std::path::Path;
+
+use crate::api_calls::check_tracking;
+use crate::json_file::{load_json_or_default, save_json_atomic};
+use crate::reader::OutputReader;
+
+/// Name of the file in the output folder that remembers the last sync
+pub const SYNC_STATE_FILENAME: &str = "sync_state.json";
+
+/// How much of a game is already in the output folder
+#[derive(Debug, Clone, PartialEq)]
+pub enum GameStatus {
+    /// not downloaded, or without its goal details
+    Missing,
+    /// downloaded, but these goals' tracking data is missing or empty
+    Stale(Vec<u32>),
+    UpToDate,
+}
+
+/// Checks how much of a game is already in the output folder
+/// Goals whose saved tracking data is empty or invalid (see `check_tracking`)
+/// count as stale, as that's what the NHL sends before it has the tracking.
+pub fn game_status(reader: &OutputReader, game_id: u32) -> GameStatus {
+    let Ok(game_data) = reader.read_game_data(game_id) else {
+        return GameStatus::Missing;
+    };
+    let stale: Vec<u32> = game_data
+        .goals
+        .iter()
+        .filter(|g| {
+            reader
+                .read_tracking(game_id, g.event_id)
+                .map_or(true, |tracking| !check_tracking(&tracking).is_saved())
+        })
+        .map(|g| g.event_id)
+        .collect();
+    if stale.is_empty() {
+        GameStatus::UpToDate
+    } else {
+        GameStatus::Stale(stale)
+    }
+}
+
+/// What the last sync covered, saved between runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SyncState {
+    /// the last date synced, in YYYY-MM-DD format
+    pub last_synced: Option<String>,
+}
+
+impl SyncState {
+    /// Reads the state saved in a file, or an empty state if there isn't one
+    pub fn load<P>(path: P) -> Result<SyncState>
+    where
+        P: AsRef<Path>,
+    {
+        load_json_or_default(path, "sync state")
+    }
+
+    pub fn save<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        save_json_atomic(path, self)
+    }
+
+    pub fn last_synced_date(&self) -> Option<NaiveDate> {
+        self.last_synced
+            .as_deref()
+            .and_then(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::{temp_folder, write_goal_details};
+
+    use std::fs::{create_dir_all, write};
+
+    #[test]
+    fn game_status_finds_stale_goals() {
+        let root = temp_folder("sync");
+        let game_path = root.join("2025-10-26").join("2025020140");
+        write_goal_details(&game_path, &[663, 700, 701]);
+        write(game_path.join("663"), r#"[{"timeStamp":100,"onIce":{}}]"#).unwrap();
+        // a cut off response, and 701 never saved
+        write(game_path.join("700"), r#"[{"timeStamp":100,"on"#).unwrap();
+        // a game folder without its goal details
+        create_dir_all(root.join("2025-10-26").join("2025020141")).unwrap();
+
+        let reader = OutputReader::open(&root).unwrap();
+        assert_eq!(game_status(&reader, 2025020140), GameStatus::Stale(vec![700, 701]));
+        assert_eq!(game_status(&reader, 2025020141), GameStatus::Missing);
+        assert_eq!(game_status(&reader, 2025020142), GameStatus::Missing);
+
+        write(game_path.join("700"), r#"[{"timeStamp":100,"onIce":{}}]"#).unwrap();
+        write(game_path.join("701"), r#"[{"timeStamp":100,"onIce":{}}]"#).unwrap();
+        assert_eq!(game_status(&reader, 2025020140), GameStatus::UpToDate);
+    }
+}
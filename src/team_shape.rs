@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+
+use crate::tracking::{Entity, Frame};
+
+/// How a team's skaters are spread out in a single frame, in the frames'
+/// coordinates
+#[derive(Debug, Clone, PartialEq)]
+pub struct TeamShape {
+    pub team_id: u16,
+    pub num_skaters: usize,
+    pub centroid_x: f64,
+    pub centroid_y: f64,
+    /// area of the convex hull around the skaters; 0 with fewer than three
+    pub hull_area_ft2: f64,
+    /// average distance between each pair of skaters
+    pub inter_player_distance_ft: f64,
+    /// average distance of the skaters from their centroid: lower is more
+    /// compact
+    pub compactness_ft: f64,
+}
+
+/// A team's shape over a whole goal sequence
+#[derive(Debug, Clone, PartialEq)]
+pub struct TeamShapeSummary {
+    pub team_id: u16,
+    pub mean_hull_area_ft2: f64,
+    pub max_hull_area_ft2: f64,
+    pub mean_inter_player_distance_ft: f64,
+    pub mean_compactness_ft: f64,
+    /// the team's shape in the frame the goal was scored
+    pub at_goal: Option<TeamShape>,
+}
+
+/// Computes the shape of each team in a frame, in team id order
+/// Goalies (from `goalie_ids`, the roster's player ids) are left out, so the
+/// shape is of the skaters.
+/// The frames need to be in feet (see `normalize_frames`) for the units to be
+/// right.
+pub fn team_shapes(frame: &Frame, goalie_ids: &[u32]) -> Vec<TeamShape> {
+    let mut teams: BTreeMap<u16, Vec<&Entity>> = BTreeMap::new();
+    for p in frame.players().filter(|p| !p.player_id.is_some_and(|id| goalie_ids.contains(&id))) {
+        if let Some(team_id) = p.team_id {
+            teams.entry(team_id).or_default().push(p);
+        }
+    }
+    teams
+        .into_iter()
+        .map(|(team_id, skaters)| {
+            let points: Vec<(f64, f64)> = skaters.iter().map(|p| (p.x, p.y)).collect();
+            shape(team_id, &points)
+        })
+        .collect()
+}
+
+/// Summarizes each team's shape over the frames, in team id order
+/// `goal_frame` is the index of the frame the goal was scored in.
+pub fn summarize_team_shapes(frames: &[Frame], goalie_ids: &[u32], goal_frame: usize) -> Vec<TeamShapeSummary> {
+    let mut summaries: BTreeMap<u16, (TeamShapeSummary, usize)> = BTreeMap::new();
+    for (i, frame) in frames.iter().enumerate() {
+        for shape in team_shapes(frame, goalie_ids) {
+            let (summary, count) = summaries.entry(shape.team_id).or_insert((
+                TeamShapeSummary {
+                    team_id: shape.team_id,
+                    mean_hull_area_ft2: 0.0,
+                    max_hull_area_ft2: 0.0,
+                    mean_inter_player_distance_ft: 0.0,
+                    mean_compactness_ft: 0.0,
+                    at_goal: None,
+                },
+                0,
+            ));
+            summary.mean_hull_area_ft2 += shape.hull_area_ft2;
+            summary.max_hull_area_ft2 = summary.max_hull_area_ft2.max(shape.hull_area_ft2);
+            summary.mean_inter_player_distance_ft += shape.inter_player_distance_ft;
+            summary.mean_compactness_ft += shape.compactness_ft;
+            *count += 1;
+            if i == goal_frame {
+                summary.at_goal = Some(shape);
+            }
+        }
+    }
+
+    summaries
+        .into_values()
+        .map(|(mut summary, count)| {
+            let count = count as f64;
+            summary.mean_hull_area_ft2 /= count;
+            summary.mean_inter_player_distance_ft /= count;
+            summary.mean_compactness_ft /= count;
+            summary
+        })
+        .collect()
+}
+
+fn shape(team_id: u16, points: &[(f64, f64)]) -> TeamShape {
+    let n = points.len() as f64;
+    let centroid_x = points.iter().map(|p| p.0).sum::<f64>() / n;
+    let centroid_y = points.iter().map(|p| p.1).sum::<f64>() / n;
+
+    let mut pair_distance = 0.0;
+    let mut num_pairs = 0;
+    for (i, a) in points.iter().enumerate() {
+        for b in &points[i + 1..] {
+            pair_distance += (a.0 - b.0).hypot(a.1 - b.1);
+            num_pairs += 1;
+        }
+    }
+
+    TeamShape {
+        team_id,
+        num_skaters: points.len(),
+        centroid_x,
+        centroid_y,
+        hull_area_ft2: polygon_area(&convex_hull(points)),
+        inter_player_distance_ft: if num_pairs > 0 { pair_distance / num_pairs as f64 } else { 0.0 },
+        compactness_ft: points
+            .iter()
+            .map(|p| (p.0 - centroid_x).hypot(p.1 - centroid_y))
+            .sum::<f64>()
+            / n,
+    }
+}
+
+/// Convex hull of the points, counterclockwise (Andrew's monotone chain)
+fn convex_hull(points: &[(f64, f64)]) -> Vec<(f64, f64)> {
+    let mut points = points.to_vec();
+    points.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+
+    let cross = |o: (f64, f64), a: (f64, f64), b: (f64, f64)| (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0);
+    let mut hull: Vec<(f64, f64)> = vec![];
+    // lower hull, then upper hull
+    for pass in [points.clone(), points.iter().rev().copied().collect()] {
+        let start = hull.len();
+        for p in pass {
+            while hull.len() >= start + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        // the last point is the first of the next pass
+        hull.pop();
+    }
+    hull
+}
+
+/// Area of a polygon (shoelace formula)
+fn polygon_area(polygon: &[(f64, f64)]) -> f64 {
+    if polygon.len() < 3 {
+        return 0.0;
+    }
+    let twice_area: f64 = (0..polygon.len())
+        .map(|i| {
+            let (a, b) = (polygon[i], polygon[(i + 1) % polygon.len()]);
+            a.0 * b.1 - b.0 * a.1
+        })
+        .sum();
+    twice_area.abs() / 2.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::{entity, player};
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    // team 22 in a 10 ft square with one skater in the middle, team 15 in a
+    // line, and a goalie who shouldn't count
+    fn shape_frame() -> Frame {
+        Frame {
+            time_stamp: 0.0,
+            entities: vec![
+                entity(1, None, 0.0, 0.0),
+                entity(10, Some(22), 0.0, 0.0),
+                entity(11, Some(22), 10.0, 0.0),
+                entity(12, Some(22), 10.0, 10.0),
+                entity(13, Some(22), 0.0, 10.0),
+                entity(14, Some(22), 5.0, 5.0),
+                player(30, 8475883, Some(22), -89.0, 0.0),
+                entity(20, Some(15), 0.0, 20.0),
+                entity(21, Some(15), 6.0, 20.0),
+            ],
+        }
+    }
+
+    #[test]
+    fn team_shapes_square() {
+        let shapes = team_shapes(&shape_frame(), &[8475883]);
+        assert_eq!(shapes.len(), 2);
+
+        let line = &shapes[0];
+        assert_eq!((line.team_id, line.num_skaters), (15, 2));
+        assert_close(line.hull_area_ft2, 0.0);
+        assert_close(line.inter_player_distance_ft, 6.0);
+        assert_close(line.compactness_ft, 3.0);
+
+        let square = &shapes[1];
+        assert_eq!((square.team_id, square.num_skaters), (22, 5));
+        assert_close(square.hull_area_ft2, 100.0);
+        assert_close(square.centroid_x, 5.0);
+        assert_close(square.centroid_y, 5.0);
+        // 4 sides of 10, 2 diagonals of 10√2, 4 half diagonals of 5√2
+        let expected = (40.0 + 20.0 * 2.0_f64.sqrt() + 20.0 * 2.0_f64.sqrt()) / 10.0;
+        assert_close(square.inter_player_distance_ft, expected);
+        assert_close(square.compactness_ft, 4.0 * 50.0_f64.sqrt() / 5.0);
+    }
+
+    #[test]
+    fn summarize_team_shapes_goal_frame() {
+        let mut spread = shape_frame();
+        for e in &mut spread.entities {
+            e.x *= 2.0;
+            e.y *= 2.0;
+        }
+        let frames = vec![shape_frame(), spread];
+        let summaries = summarize_team_shapes(&frames, &[8475883], 1);
+        assert_eq!(summaries[1].team_id, 22);
+        assert_close(summaries[1].mean_hull_area_ft2, 250.0);
+        assert_close(summaries[1].max_hull_area_ft2, 400.0);
+        assert_close(summaries[1].at_goal.as_ref().unwrap().hull_area_ft2, 400.0);
+    }
+}
@@ -0,0 +1,53 @@
+// helpers shared by the unit tests
+
+use std::fs::{create_dir_all, remove_dir_all, write};
+use std::path::{Path, PathBuf};
+
+use crate::reader::PBP_BOXSCORE_FILENAME;
+use crate::tracking::Entity;
+
+/// A player, or the puck for id 1, with the player id the same as the entity
+/// id
+pub fn entity(id: u32, team_id: Option<u16>, x: f64, y: f64) -> Entity {
+    Entity {
+        id,
+        player_id: if id == 1 { None } else { Some(id) },
+        team_id,
+        team_abbrev: None,
+        sweater_number: None,
+        x,
+        y,
+        interpolated: false,
+    }
+}
+
+/// A player whose player id isn't the same as their entity id, as in the
+/// NHL's tracking data
+pub fn player(id: u32, player_id: u32, team_id: Option<u16>, x: f64, y: f64) -> Entity {
+    Entity { player_id: Some(player_id), ..entity(id, team_id, x, y) }
+}
+
+/// Makes a fresh, empty folder under the system temp folder
+pub fn temp_folder(name: &str) -> PathBuf {
+    let folder = std::env::temp_dir().join(format!("nhl_{}_{}", name, std::process::id()));
+    let _ = remove_dir_all(&folder);
+    create_dir_all(&folder).unwrap();
+    folder
+}
+
+/// Makes a game folder with the goal details for the given goals, all scored
+/// by team 1 with the home team (team 1) defending the left
+pub fn write_goal_details(game_path: &Path, event_ids: &[u32]) {
+    create_dir_all(game_path).unwrap();
+    let goals: Vec<String> = event_ids
+        .iter()
+        .map(|id| {
+            format!(r#"{{"event_id":{},"ppt_replay_url":null,"scoring_team_id":1,"home_team_defending_side":"Left"}}"#, id)
+        })
+        .collect();
+    write(
+        game_path.join(PBP_BOXSCORE_FILENAME),
+        format!(r#"{{"goals":[{}],"home_team_id":1}}"#, goals.join(",")),
+    )
+    .unwrap();
+}
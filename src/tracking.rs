@@ -0,0 +1,175 @@
+// field names mirror the JSON returned by the NHL's sprite endpoint
+#![allow(non_snake_case)]
+
+use anyhow::{anyhow, Result};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use std::collections::BTreeMap;
+
+/// The sprite timestamps count tenths of a second
+pub const TIMESTAMPS_PER_SECOND: f64 = 10.0;
+
+/// A single frame of the tracking data: where everyone on the ice, and the
+/// puck, was at one point in time
+/// Resampled frames can fall between the sprite's timestamps, so the
+/// timestamp isn't always a whole number.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Frame {
+    pub time_stamp: f64,
+    pub entities: Vec<Entity>,
+}
+
+/// A player or the puck within a frame
+/// The puck is the only entity without a player id.  `interpolated` is only
+/// set for positions filled in by resampling.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Entity {
+    pub id: u32,
+    pub player_id: Option<u32>,
+    pub team_id: Option<u16>,
+    pub team_abbrev: Option<String>,
+    pub sweater_number: Option<u16>,
+    pub x: f64,
+    pub y: f64,
+    pub interpolated: bool,
+}
+
+impl Entity {
+    pub fn is_puck(&self) -> bool {
+        self.player_id.is_none()
+    }
+}
+
+impl Frame {
+    /// The puck, if it was tracked in this frame
+    pub fn puck(&self) -> Option<&Entity> {
+        self.entities.iter().find(|e| e.is_puck())
+    }
+
+    /// The players (everything but the puck) in this frame
+    pub fn players(&self) -> impl Iterator<Item = &Entity> {
+        self.entities.iter().filter(|e| !e.is_puck())
+    }
+
+    /// Seconds between this frame and an earlier one
+    pub fn seconds_since(&self, earlier: &Frame) -> f64 {
+        (self.time_stamp - earlier.time_stamp) / TIMESTAMPS_PER_SECOND
+    }
+}
+
+// structs to deserialize the sprite JSON
+#[derive(Deserialize, Debug)]
+struct SpriteFrame {
+    timeStamp: f64,
+    onIce: BTreeMap<String, SpriteEntity>,
+}
+
+/// ids, team ids and sweater numbers come back as either numbers or strings,
+/// and as empty strings for the puck
+#[derive(Deserialize, Debug)]
+struct SpriteEntity {
+    id: Option<Value>,
+    playerId: Option<Value>,
+    teamId: Option<Value>,
+    teamAbbrev: Option<String>,
+    sweaterNumber: Option<Value>,
+    x: f64,
+    y: f64,
+}
+
+/// Parses the tracking JSON for a goal into frames, sorted by time
+pub fn parse_tracking(tracking_json: &str) -> Result<Vec<Frame>> {
+    let sprite_frames: Vec<SpriteFrame> = serde_json::from_str(tracking_json)?;
+    if sprite_frames.is_empty() {
+        return Err(anyhow!("Tracking data has no frames"));
+    }
+
+    let mut frames = vec![];
+    for sprite_frame in sprite_frames {
+        let mut entities = vec![];
+        for (key, e) in sprite_frame.onIce {
+            let id = match e.id.as_ref().and_then(as_number).or(key.parse().ok()) {
+                Some(id) => id as u32,
+                None => return Err(anyhow!("Invalid entity id {} at time stamp {}", key, sprite_frame.timeStamp)),
+            };
+            entities.push(Entity {
+                id,
+                player_id: e.playerId.as_ref().and_then(as_number).map(|n| n as u32),
+                team_id: e.teamId.as_ref().and_then(as_number).map(|n| n as u16),
+                team_abbrev: e.teamAbbrev.filter(|a| !a.is_empty()),
+                sweater_number: e.sweaterNumber.as_ref().and_then(as_number).map(|n| n as u16),
+                x: e.x,
+                y: e.y,
+                interpolated: false,
+            });
+        }
+        frames.push(Frame { time_stamp: sprite_frame.timeStamp, entities });
+    }
+    frames.sort_by(|a, b| a.time_stamp.total_cmp(&b.time_stamp));
+    Ok(frames)
+}
+
+/// Reads a number that may have been sent as a string
+/// Empty strings (used for the puck) are None.
+fn as_number(value: &Value) -> Option<u64> {
+    match value {
+        Value::Number(n) => n.as_u64(),
+        Value::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SPRITE_JSON: &str = r#"[
+        {"timeStamp": 1761519981, "onIce": {
+            "1": {"id": 1, "playerId": "", "x": 1300.5, "y": 400.25, "sweaterNumber": "", "teamId": "", "teamAbbrev": ""},
+            "8478402": {"id": 8478402, "playerId": 8478402, "x": 1250.0, "y": 420.0, "sweaterNumber": 97, "teamId": 22, "teamAbbrev": "EDM"}
+        }},
+        {"timeStamp": 1761519980, "onIce": {
+            "1": {"id": 1, "playerId": "", "x": 1290.0, "y": 401.0, "sweaterNumber": "", "teamId": "", "teamAbbrev": ""},
+            "8478402": {"id": "8478402", "playerId": "8478402", "x": 1245.0, "y": 421.0, "sweaterNumber": "97", "teamId": "22", "teamAbbrev": "EDM"}
+        }}
+    ]"#;
+
+    // frames are sorted by time, and numbers sent as strings are still read
+    #[test]
+    fn parse_tracking_valid() {
+        let frames = parse_tracking(SPRITE_JSON).unwrap();
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].time_stamp, 1761519980.0);
+        assert_eq!(frames[1].seconds_since(&frames[0]), 0.1);
+
+        let puck = frames[0].puck().unwrap();
+        assert_eq!(puck.id, 1);
+        assert_eq!(puck.team_id, None);
+        assert_eq!(puck.team_abbrev, None);
+
+        let players: Vec<&Entity> = frames[0].players().collect();
+        assert_eq!(players.len(), 1);
+        assert_eq!(
+            *players[0],
+            Entity {
+                id: 8478402,
+                player_id: Some(8478402),
+                team_id: Some(22),
+                team_abbrev: Some(String::from("EDM")),
+                sweater_number: Some(97),
+                x: 1245.0,
+                y: 421.0,
+                interpolated: false,
+            }
+        );
+    }
+
+    // empty responses aren't valid tracking data
+    #[test]
+    fn parse_tracking_empty() {
+        assert!(parse_tracking("[]").is_err());
+        assert!(parse_tracking("").is_err());
+    }
+}
@@ -0,0 +1,231 @@
+use serde::Serialize;
+use serde_json::Value;
+
+use std::fmt;
+
+use crate::normalize::{to_feet, RINK_LENGTH_FT, RINK_WIDTH_FT};
+use crate::reader::{OutputReader, PBP_BOXSCORE_FILENAME};
+use crate::tracking::parse_tracking;
+
+/// How far past the boards a position can be before it's out of bounds, in
+/// feet: the puck leaves the ice now and then, and the boards curve
+pub const RINK_BOUNDS_MARGIN_FT: f64 = 5.0;
+
+/// How serious a problem found in the output is
+/// Errors mean data's missing or unreadable; warnings mean it's there but
+/// looks off.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Warning => f.pad("warning"),
+            Severity::Error => f.pad("error"),
+        }
+    }
+}
+
+/// A problem with a game, or one of its goals
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Issue {
+    pub severity: Severity,
+    pub game_id: u32,
+    pub event_id: Option<u32>,
+    pub message: String,
+}
+
+impl Issue {
+    fn new(severity: Severity, game_id: u32, event_id: Option<u32>, message: String) -> Issue {
+        Issue { severity, game_id, event_id, message }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.event_id {
+            Some(event_id) => write!(f, "{:<7} game {}, goal {}: {}", self.severity, self.game_id, event_id, self.message),
+            None => write!(f, "{:<7} game {}: {}", self.severity, self.game_id, self.message),
+        }
+    }
+}
+
+/// The issues found in an output folder, with totals, as written by
+/// `validate --json`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Report {
+    pub games: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    pub issues: Vec<Issue>,
+}
+
+impl Report {
+    pub fn new(games: usize, issues: Vec<Issue>) -> Report {
+        let errors = issues.iter().filter(|i| i.severity == Severity::Error).count();
+        Report { games, errors, warnings: issues.len() - errors, issues }
+    }
+}
+
+/// Checks every game in an output folder (or archive), in game id order
+pub fn validate(reader: &OutputReader) -> Vec<Issue> {
+    reader
+        .games()
+        .iter()
+        .flat_map(|g| validate_game(reader, g.game_id))
+        .collect()
+}
+
+/// Checks a game: its goal details are there, and each goal's tracking data
+/// is there, parses, is in time order and stays on the ice
+pub fn validate_game(reader: &OutputReader, game_id: u32) -> Vec<Issue> {
+    let game_data = match reader.read_game_data(game_id) {
+        Ok(game_data) => game_data,
+        Err(e) => {
+            let message = format!("No goals to check, as {} can't be read: {:#}", PBP_BOXSCORE_FILENAME, e);
+            return vec![Issue::new(Severity::Error, game_id, None, message)];
+        }
+    };
+
+    let mut issues = vec![];
+    for goal in &game_data.goals {
+        match reader.read_tracking(game_id, goal.event_id) {
+            Ok(tracking) => {
+                for (severity, message) in tracking_issues(&tracking) {
+                    issues.push(Issue::new(severity, game_id, Some(goal.event_id), message));
+                }
+            }
+            Err(_) => {
+                let message = String::from("No tracking data");
+                issues.push(Issue::new(Severity::Error, game_id, Some(goal.event_id), message));
+            }
+        }
+    }
+    issues
+}
+
+/// Compares the number of goals saved for a game with the number in the
+/// NHL's landing summary
+pub fn goal_count_issue(game_id: u32, num_saved: usize, num_landing: usize) -> Option<Issue> {
+    if num_saved == num_landing {
+        return None;
+    }
+    let message = format!("{} goals saved, but the landing summary has {}", num_saved, num_landing);
+    Some(Issue::new(Severity::Error, game_id, None, message))
+}
+
+/// The problems with a goal's raw tracking JSON
+fn tracking_issues(tracking: &str) -> Vec<(Severity, String)> {
+    let frames = match parse_tracking(tracking) {
+        Ok(frames) => frames,
+        Err(e) => return vec![(Severity::Error, format!("Unreadable tracking data: {}", e))],
+    };
+
+    let mut issues = vec![];
+    // the parser sorts the frames, so the order they were sent in has to come
+    // from the raw JSON
+    let time_stamps: Vec<f64> = serde_json::from_str::<Vec<Value>>(tracking)
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|f| f["timeStamp"].as_f64())
+        .collect();
+    let num_out_of_order = time_stamps.windows(2).filter(|w| w[1] <= w[0]).count();
+    if num_out_of_order > 0 {
+        issues.push((
+            Severity::Warning,
+            format!("Time stamps aren't increasing at {} of {} frames", num_out_of_order, time_stamps.len()),
+        ));
+    }
+
+    let max_x = RINK_LENGTH_FT / 2.0 + RINK_BOUNDS_MARGIN_FT;
+    let max_y = RINK_WIDTH_FT / 2.0 + RINK_BOUNDS_MARGIN_FT;
+    let out_of_bounds: Vec<(f64, (f64, f64))> = frames
+        .iter()
+        .flat_map(|f| f.entities.iter().map(move |e| (f.time_stamp, to_feet(e.x, e.y))))
+        .filter(|(_, (x, y))| x.abs() > max_x || y.abs() > max_y)
+        .collect();
+    if let Some((time_stamp, (x, y))) = out_of_bounds.first() {
+        issues.push((
+            Severity::Warning,
+            format!(
+                "{} positions off the rink, the first at time stamp {} ({:.1}, {:.1}) ft",
+                out_of_bounds.len(), time_stamp, x, y
+            ),
+        ));
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::{temp_folder, write_goal_details};
+
+    use std::fs::{create_dir_all, write};
+
+    #[test]
+    fn validate_finds_issues() {
+        let root = temp_folder("validate");
+        let game_path = root.join("2025-10-26").join("2025020140");
+        write_goal_details(&game_path, &[663, 700, 701, 702]);
+        write(
+            game_path.join("663"),
+            r#"[{"timeStamp":100,"onIce":{"1":{"id":1,"x":1200,"y":510}}},{"timeStamp":101,"onIce":{"1":{"id":1,"x":1210,"y":510}}}]"#,
+        )
+        .unwrap();
+        // out of order, and the puck 110 ft from center
+        write(
+            game_path.join("700"),
+            r#"[{"timeStamp":101,"onIce":{"1":{"id":1,"x":2520,"y":510}}},{"timeStamp":100,"onIce":{"1":{"id":1,"x":1200,"y":510}}}]"#,
+        )
+        .unwrap();
+        write(game_path.join("701"), r#"[{"timeStamp":100,"onIce":{"1":{"id":1,"x":12"#).unwrap();
+        // a game folder without its goal details
+        create_dir_all(root.join("2025-10-26").join("2025020141")).unwrap();
+
+        let reader = OutputReader::open(&root).unwrap();
+        let issues = validate(&reader);
+        let summary: Vec<(Severity, u32, Option<u32>)> =
+            issues.iter().map(|i| (i.severity, i.game_id, i.event_id)).collect();
+        assert_eq!(
+            summary,
+            vec![
+                (Severity::Warning, 2025020140, Some(700)),
+                (Severity::Warning, 2025020140, Some(700)),
+                (Severity::Error, 2025020140, Some(701)),
+                (Severity::Error, 2025020140, Some(702)),
+                (Severity::Error, 2025020141, None),
+            ]
+        );
+        assert_eq!(issues[0].message, "Time stamps aren't increasing at 1 of 2 frames");
+        assert_eq!(issues[1].message, "1 positions off the rink, the first at time stamp 101 (110.0, 0.0) ft");
+        assert_eq!(issues[3].to_string(), "error   game 2025020140, goal 702: No tracking data");
+    }
+
+    #[test]
+    fn goal_count_issue_only_on_mismatch() {
+        assert_eq!(goal_count_issue(2025020140, 5, 5), None);
+        let issue = goal_count_issue(2025020140, 4, 5).unwrap();
+        assert_eq!(issue.severity, Severity::Error);
+        assert_eq!(issue.to_string(), "error   game 2025020140: 4 goals saved, but the landing summary has 5");
+    }
+
+    #[test]
+    fn report_counts_and_json() {
+        let issues = vec![
+            goal_count_issue(2025020140, 4, 5).unwrap(),
+            Issue::new(Severity::Warning, 2025020141, Some(663), String::from("off the rink")),
+        ];
+        let report = Report::new(2, issues);
+        assert_eq!((report.games, report.errors, report.warnings), (2, 1, 1));
+        assert_eq!(
+            serde_json::to_string(&report.issues[1]).unwrap(),
+            r#"{"severity":"warning","game_id":2025020141,"event_id":663,"message":"off the rink"}"#
+        );
+    }
+}
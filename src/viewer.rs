@@ -0,0 +1,405 @@
+use anyhow::{anyhow, Context, Result};
+
+use serde::Serialize;
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use crate::api_calls::RosterPlayer;
+use crate::reader::OutputReader;
+use crate::render::{escape, read_normalized_goal, rink_svg, svg_document, team_colors, NormalizedGoal, RenderOptions};
+
+/// Everything the viewer needs for a game, embedded in the page as JSON
+#[derive(Debug, Serialize)]
+struct ViewerData {
+    game_id: u32,
+    /// the rink as an SVG document, drawn behind the players
+    rink: String,
+    /// player id to name and sweater number, from the roster
+    players: BTreeMap<u32, ViewerPlayer>,
+    goals: Vec<ViewerGoal>,
+}
+
+#[derive(Debug, Serialize)]
+struct ViewerPlayer {
+    name: String,
+    number: Option<u16>,
+}
+
+#[derive(Debug, Serialize)]
+struct ViewerGoal {
+    event_id: u32,
+    scoring_team_id: u16,
+    scoring_team: Option<String>,
+    situation_code: Option<String>,
+    colors: BTreeMap<u16, &'static str>,
+    frames: Vec<ViewerFrame>,
+}
+
+/// A frame, with positions in the canonical frame of reference
+#[derive(Debug, Serialize)]
+struct ViewerFrame {
+    /// seconds since the first frame
+    t: f64,
+    puck: Option<[f64; 2]>,
+    players: Vec<ViewerPosition>,
+}
+
+/// A player's id, team id, sweater number, x and y, written as an array to
+/// keep the page small
+#[derive(Debug, Serialize)]
+struct ViewerPosition(u32, Option<u16>, Option<u16>, f64, f64);
+
+/// Writes a self-contained HTML replay viewer for each game to a folder,
+/// named after the game id
+/// The tracking data is embedded in the page, so it can be opened straight
+/// from disk and shared without anything else.
+pub fn write_viewers<P>(reader: &OutputReader, game_id: Option<u32>, output: P) -> Result<()>
+where
+    P: AsRef<Path>,
+{
+    let output = output.as_ref();
+    fs::create_dir_all(output).with_context(|| format!("Failed to create {}", output.display()))?;
+
+    let mut num_viewers = 0;
+    for game in reader.games() {
+        if game_id.is_some_and(|id| id != game.game_id) {
+            continue;
+        }
+        let game_data = match reader.read_game_data(game.game_id) {
+            Ok(game_data) => game_data,
+            Err(e) => {
+                println!("Skipping game {}: {}", game.game_id, e);
+                continue;
+            }
+        };
+        let roster = reader.read_roster(game.game_id).unwrap_or_else(|e| {
+            println!("Unable to read the roster for game {}: {}", game.game_id, e);
+            vec![]
+        });
+
+        let mut goals = vec![];
+        for goal in &game_data.goals {
+            match read_normalized_goal(reader, game.game_id, goal.event_id) {
+                Ok(goal) if !goal.frames.is_empty() => goals.push(goal),
+                Ok(_) => println!("Skipping game {}, goal {}: no frames", game.game_id, goal.event_id),
+                Err(e) => println!("Skipping game {}, goal {}: {}", game.game_id, goal.event_id, e),
+            }
+        }
+        if goals.is_empty() {
+            println!("Skipping game {}: no goals with tracking data", game.game_id);
+            continue;
+        }
+
+        let path = output.join(format!("{}.html", game.game_id));
+        fs::write(&path, viewer_html(game.game_id, &goals, &roster)?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        num_viewers += 1;
+    }
+
+    if num_viewers == 0 {
+        return match game_id {
+            Some(id) => Err(anyhow!("No tracking data for game {}", id)),
+            None => Err(anyhow!("No games with tracking data")),
+        };
+    }
+    println!("Wrote {} viewers to {}", num_viewers, output.display());
+    Ok(())
+}
+
+/// Builds the viewer page for a game's goals
+/// The goals' frames need to be normalized (see `read_normalized_goal`).
+pub fn viewer_html(game_id: u32, goals: &[NormalizedGoal], roster: &[RosterPlayer]) -> Result<String> {
+    let title = format!("Game {}", game_id);
+    let data = ViewerData {
+        game_id,
+        rink: svg_document(&rink_svg(), &title, &RenderOptions::default()),
+        players: roster
+            .iter()
+            .map(|p| {
+                let player = ViewerPlayer {
+                    name: p.full_name(),
+                    number: p.sweater_number,
+                };
+                (p.player_id, player)
+            })
+            .collect(),
+        goals: goals.iter().map(viewer_goal).collect(),
+    };
+    // '<' only shows up inside JSON strings, where it can be escaped, so
+    // nothing in the data can close the script tag early
+    let json = serde_json::to_string(&data)?.replace('<', "\\u003c");
+    Ok(VIEWER_TEMPLATE.replace("{{title}}", &escape(&title)).replace("{{data}}", &json))
+}
+
+fn viewer_goal(goal: &NormalizedGoal) -> ViewerGoal {
+    let round = |v: f64| (v * 10.0).round() / 10.0;
+    let first = &goal.frames[0];
+    ViewerGoal {
+        event_id: goal.goal.event_id,
+        scoring_team_id: goal.goal.scoring_team_id,
+        scoring_team: goal
+            .frames
+            .iter()
+            .flat_map(|f| f.players())
+            .find(|p| p.team_id == Some(goal.goal.scoring_team_id))
+            .and_then(|p| p.team_abbrev.clone()),
+        situation_code: goal.goal.situation_code.clone(),
+        colors: team_colors(&goal.frames, goal.home_team_id),
+        frames: goal
+            .frames
+            .iter()
+            .map(|f| ViewerFrame {
+                t: (f.seconds_since(first) * 100.0).round() / 100.0,
+                puck: f.puck().map(|p| [round(p.x), round(p.y)]),
+                players: f
+                    .players()
+                    .map(|p| ViewerPosition(p.id, p.team_id, p.sweater_number, round(p.x), round(p.y)))
+                    .collect(),
+            })
+            .collect(),
+    }
+}
+
+const VIEWER_TEMPLATE: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+<meta charset="utf-8">
+<title>{{title}}</title>
+<style>
+body { font-family: Helvetica, Arial, sans-serif; margin: 16px; color: #222; }
+#controls { display: flex; gap: 12px; align-items: center; flex-wrap: wrap; margin-bottom: 8px; }
+#scrub { flex: 1; min-width: 200px; }
+canvas { width: 100%; max-width: 1200px; display: block; }
+#info { margin-top: 6px; font-size: 14px; color: #555; }
+</style>
+</head>
+<body>
+<h2>{{title}}</h2>
+<div id="controls">
+<select id="goal"></select>
+<button id="play">Pause</button>
+<input id="scrub" type="range" min="0" value="0">
+<select id="speed">
+<option value="0.25">0.25x</option>
+<option value="0.5">0.5x</option>
+<option value="1" selected>1x</option>
+<option value="2">2x</option>
+<option value="4">4x</option>
+</select>
+<label><input id="names" type="checkbox"> names</label>
+<span id="clock"></span>
+</div>
+<canvas id="rink" width="1200" height="510"></canvas>
+<div id="info">The scoring team attacks the net on the right.  Space plays and pauses, the arrow keys step a frame.</div>
+<script type="application/json" id="data">{{data}}</script>
+<script>
+(function () {
+  var data = JSON.parse(document.getElementById("data").textContent);
+  var canvas = document.getElementById("rink");
+  var ctx = canvas.getContext("2d");
+  var goalSelect = document.getElementById("goal");
+  var playButton = document.getElementById("play");
+  var scrub = document.getElementById("scrub");
+  var speedSelect = document.getElementById("speed");
+  var names = document.getElementById("names");
+  var clock = document.getElementById("clock");
+
+  var rink = new Image();
+  rink.onload = draw;
+  rink.src = "data:image/svg+xml;charset=utf-8," + encodeURIComponent(data.rink);
+
+  var goal = null, index = 0, time = 0, playing = true, last = null;
+
+  data.goals.forEach(function (g, i) {
+    var option = document.createElement("option");
+    option.value = i;
+    option.textContent = "Goal " + g.event_id + (g.scoring_team ? " (" + g.scoring_team + ")" : "") +
+      (g.situation_code ? " " + g.situation_code : "");
+    goalSelect.appendChild(option);
+  });
+
+  function selectGoal(i) {
+    goal = data.goals[i];
+    scrub.max = goal.frames.length - 1;
+    seek(0);
+  }
+
+  function seek(i) {
+    index = Math.max(0, Math.min(goal.frames.length - 1, i));
+    time = goal.frames[index].t;
+    draw();
+  }
+
+  function toCanvas(x, y) {
+    var scale = canvas.width / 200;
+    return [(x + 100) * scale, (42.5 - y) * scale];
+  }
+
+  function draw() {
+    if (!goal) return;
+    var scale = canvas.width / 200;
+    var frame = goal.frames[index];
+    ctx.clearRect(0, 0, canvas.width, canvas.height);
+    if (rink.complete) ctx.drawImage(rink, 0, 0, canvas.width, canvas.height);
+
+    ctx.textAlign = "center";
+    ctx.textBaseline = "middle";
+    frame.players.forEach(function (p) {
+      var xy = toCanvas(p[3], p[4]);
+      ctx.beginPath();
+      ctx.arc(xy[0], xy[1], 1.5 * scale, 0, 2 * Math.PI);
+      ctx.fillStyle = goal.colors[p[1]] || "#c8102e";
+      ctx.fill();
+      ctx.lineWidth = 0.2 * scale;
+      ctx.strokeStyle = "#ffffff";
+      ctx.stroke();
+      var player = data.players[p[0]];
+      var number = p[2] !== null ? p[2] : (player ? player.number : null);
+      if (number !== null && number !== undefined) {
+        ctx.fillStyle = "#ffffff";
+        ctx.font = "bold " + (1.5 * scale) + "px Helvetica, Arial, sans-serif";
+        ctx.fillText(number, xy[0], xy[1]);
+      }
+      if (names.checked && player) {
+        ctx.fillStyle = "#222222";
+        ctx.font = (1.4 * scale) + "px Helvetica, Arial, sans-serif";
+        ctx.fillText(player.name, xy[0], xy[1] - 2.6 * scale);
+      }
+    });
+    if (frame.puck) {
+      var xy = toCanvas(frame.puck[0], frame.puck[1]);
+      ctx.beginPath();
+      ctx.arc(xy[0], xy[1], 0.6 * scale, 0, 2 * Math.PI);
+      ctx.fillStyle = "#000000";
+      ctx.fill();
+    }
+
+    scrub.value = index;
+    var total = goal.frames[goal.frames.length - 1].t;
+    clock.textContent = frame.t.toFixed(1) + " / " + total.toFixed(1) + " s";
+  }
+
+  function tick(now) {
+    if (playing && goal && last !== null) {
+      time += (now - last) / 1000 * parseFloat(speedSelect.value);
+      while (index + 1 < goal.frames.length && goal.frames[index + 1].t <= time) index++;
+      if (index + 1 === goal.frames.length) setPlaying(false);
+      draw();
+    }
+    last = now;
+    requestAnimationFrame(tick);
+  }
+
+  function setPlaying(value) {
+    playing = value;
+    if (playing && index + 1 === goal.frames.length) seek(0);
+    playButton.textContent = playing ? "Pause" : "Play";
+  }
+
+  goalSelect.onchange = function () { selectGoal(parseInt(goalSelect.value, 10)); setPlaying(true); };
+  playButton.onclick = function () { setPlaying(!playing); };
+  scrub.oninput = function () { setPlaying(false); seek(parseInt(scrub.value, 10)); };
+  names.onchange = draw;
+  document.addEventListener("keydown", function (e) {
+    if (e.target.tagName === "SELECT") return;
+    if (e.key === " ") { setPlaying(!playing); e.preventDefault(); }
+    else if (e.key === "ArrowRight") { setPlaying(false); seek(index + 1); e.preventDefault(); }
+    else if (e.key === "ArrowLeft") { setPlaying(false); seek(index - 1); e.preventDefault(); }
+  });
+
+  selectGoal(0);
+  requestAnimationFrame(tick);
+})();
+</script>
+</body>
+</html>
+"##;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::api_calls::{GoalDetails, IceSide};
+    use crate::test_util;
+    use crate::tracking::{Entity, Frame};
+
+    fn entity(id: u32, team_id: Option<u16>, x: f64, y: f64) -> Entity {
+        Entity {
+            team_abbrev: team_id.map(|_| String::from("TOR")),
+            sweater_number: team_id.map(|_| 34),
+            ..test_util::entity(id, team_id, x, y)
+        }
+    }
+
+    fn goal() -> NormalizedGoal {
+        NormalizedGoal {
+            goal: GoalDetails {
+                event_id: 663,
+                ppt_replay_url: None,
+                scoring_team_id: 10,
+                home_team_defending_side: IceSide::Left,
+                situation_code: Some(String::from("1551")),
+            },
+            home_team_id: 10,
+            frames: (0..3)
+                .map(|i| Frame {
+                    time_stamp: i as f64,
+                    entities: vec![entity(1, None, 80.123, -2.0), entity(34, Some(10), 70.0, 5.55)],
+                })
+                .collect(),
+        }
+    }
+
+    fn embedded_json(html: &str) -> serde_json::Value {
+        let start = html.find(r#"<script type="application/json" id="data">"#).unwrap();
+        let json = &html[start..];
+        let json = &json[json.find('>').unwrap() + 1..json.find("</script>").unwrap()];
+        serde_json::from_str(json).unwrap()
+    }
+
+    #[test]
+    fn viewer_html_embeds_goals() {
+        let roster = vec![RosterPlayer {
+            player_id: 34,
+            team_id: 10,
+            first_name: String::from("Auston"),
+            last_name: String::from("Matthews"),
+            sweater_number: Some(34),
+            position_code: String::from("C"),
+        }];
+        let html = viewer_html(2025020140, &[goal()], &roster).unwrap();
+        assert!(html.contains("<title>Game 2025020140</title>"));
+        // nothing left to fill in, and nothing fetched from the network
+        assert!(!html.contains("{{"));
+        assert!(!html.contains("src=\"http"));
+
+        let data = embedded_json(&html);
+        assert_eq!(data["players"]["34"]["name"], "Auston Matthews");
+        let goal = &data["goals"][0];
+        assert_eq!(goal["event_id"], 663);
+        assert_eq!(goal["scoring_team"], "TOR");
+        assert_eq!(goal["frames"].as_array().unwrap().len(), 3);
+        assert_eq!(goal["frames"][2]["t"], 0.2);
+        assert_eq!(goal["frames"][0]["puck"], serde_json::json!([80.1, -2.0]));
+        assert_eq!(goal["frames"][0]["players"][0], serde_json::json!([34, 10, 34, 70.0, 5.6]));
+    }
+
+    // a name that would end the script tag if it wasn't escaped
+    #[test]
+    fn viewer_html_escapes_script() {
+        let roster = vec![RosterPlayer {
+            player_id: 34,
+            team_id: 10,
+            first_name: String::from("</script><script>alert(1)"),
+            last_name: String::from("</script>"),
+            sweater_number: Some(34),
+            position_code: String::from("C"),
+        }];
+        let html = viewer_html(2025020140, &[goal()], &roster).unwrap();
+        assert_eq!(html.matches("</script>").count(), 2);
+        let data = embedded_json(&html);
+        assert_eq!(data["players"]["34"]["name"], "</script><script>alert(1) </script>");
+    }
+}
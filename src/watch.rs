@@ -0,0 +1,214 @@
+use anyhow::Result;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use serde::{Deserialize, Serialize};
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+
+use crate::api_calls::{GameExportData, GoalDetails};
+use crate::json_file::{load_json_or_default, save_json_atomic};
+
+/// Name of the file in the output folder that remembers what's been watched
+pub const WATCH_STATE_FILENAME: &str = "watch_state.json";
+
+/// How often to poll by default, in seconds
+pub const DEFAULT_POLL_SECONDS: u64 = 300;
+
+/// How long to keep re-checking a finished game for corrections by default
+pub const DEFAULT_CORRECTION_HOURS: i64 = 24;
+
+/// Whether a game state from the NHL means the game has started: "LIVE" or
+/// "CRIT" while it's being played, "FINAL" or "OFF" once it's over
+/// Games that haven't started are "FUT" or "PRE".
+pub fn has_started(game_state: &str) -> bool {
+    matches!(game_state, "LIVE" | "CRIT" | "FINAL" | "OFF")
+}
+
+pub fn is_final(game_state: &str) -> bool {
+    matches!(game_state, "FINAL" | "OFF")
+}
+
+/// Everything the watcher has seen, saved between runs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct WatchState {
+    pub games: BTreeMap<u32, WatchedGame>,
+}
+
+/// What the watcher knows about a game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WatchedGame {
+    pub game_state: String,
+    /// when the game was first seen as final, in RFC 3339
+    pub final_at: Option<String>,
+    /// goals whose tracking data has been saved
+    pub saved_goals: BTreeSet<u32>,
+    /// the goals as of the last check, to spot corrections
+    pub game_data: Option<GameExportData>,
+    /// set once the correction window's passed; the game isn't checked again
+    pub done: bool,
+}
+
+impl WatchState {
+    /// Reads the state saved in a file, or an empty state if there isn't one
+    pub fn load<P>(path: P) -> Result<WatchState>
+    where
+        P: AsRef<Path>,
+    {
+        load_json_or_default(path, "watch state")
+    }
+
+    /// Saves the state to a file
+    pub fn save<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        save_json_atomic(path, self)
+    }
+
+    /// Adds a game from the schedule, or updates the state of one that's
+    /// already being watched
+    pub fn track(&mut self, game_id: u32, game_state: &str, now: DateTime<Utc>) {
+        self.games
+            .entry(game_id)
+            .or_insert_with(|| WatchedGame::new(game_state))
+            .set_state(game_state, now);
+    }
+}
+
+impl WatchedGame {
+    pub fn new(game_state: &str) -> WatchedGame {
+        WatchedGame {
+            game_state: String::from(game_state),
+            final_at: None,
+            saved_goals: BTreeSet::new(),
+            game_data: None,
+            done: false,
+        }
+    }
+
+    /// Updates the game's state, noting when it first went final
+    pub fn set_state(&mut self, game_state: &str, now: DateTime<Utc>) {
+        self.game_state = String::from(game_state);
+        if is_final(game_state) && self.final_at.is_none() {
+            self.final_at = Some(now.to_rfc3339());
+        }
+    }
+
+    /// Whether the game needs to be fetched: it's started, and either isn't
+    /// over yet or went final less than `correction_window` ago
+    /// Marks the game as done once the window's passed.
+    pub fn needs_check(&mut self, now: DateTime<Utc>, correction_window: TimeDelta) -> bool {
+        if self.done || !has_started(&self.game_state) {
+            return false;
+        }
+        let final_at = self
+            .final_at
+            .as_deref()
+            .and_then(|t| DateTime::parse_from_rfc3339(t).ok());
+        match final_at {
+            Some(final_at) if now.signed_duration_since(final_at) > correction_window => {
+                self.done = true;
+                false
+            }
+            _ => true,
+        }
+    }
+
+    /// The goals that have a replay URL but haven't been saved yet
+    pub fn goals_to_download<'a>(&self, game_data: &'a GameExportData) -> Vec<&'a GoalDetails> {
+        game_data
+            .goals
+            .iter()
+            .filter(|g| g.ppt_replay_url.is_some() && !self.saved_goals.contains(&g.event_id))
+            .collect()
+    }
+
+    /// Forgets the goals a correction took away, returning their event ids so
+    /// their tracking files can be removed
+    /// A goal counts as taken away when it was saved or in the last check's
+    /// goals, but isn't in `game_data`.
+    pub fn remove_stale_goals(&mut self, game_data: &GameExportData) -> Vec<u32> {
+        let current: BTreeSet<u32> = game_data.goals.iter().map(|g| g.event_id).collect();
+        let previous = self.game_data.iter().flat_map(|d| d.goals.iter().map(|g| g.event_id));
+        let stale: BTreeSet<u32> = self
+            .saved_goals
+            .iter()
+            .copied()
+            .chain(previous)
+            .filter(|id| !current.contains(id))
+            .collect();
+        self.saved_goals.retain(|id| current.contains(id));
+        stale.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::api_calls::IceSide;
+
+    fn time(text: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(text).unwrap().with_timezone(&Utc)
+    }
+
+    fn goal(event_id: u32, ppt_replay_url: Option<&str>) -> GoalDetails {
+        GoalDetails {
+            event_id,
+            ppt_replay_url: ppt_replay_url.map(String::from),
+            scoring_team_id: 1,
+            home_team_defending_side: IceSide::Left,
+            situation_code: None,
+        }
+    }
+
+    #[test]
+    fn needs_check_through_correction_window() {
+        let window = TimeDelta::hours(24);
+        let mut state = WatchState::default();
+        state.track(2025020140, "PRE", time("2025-10-26T22:00:00Z"));
+        let game = state.games.get_mut(&2025020140).unwrap();
+        assert!(!game.needs_check(time("2025-10-26T22:00:00Z"), window));
+
+        game.set_state("LIVE", time("2025-10-26T23:10:00Z"));
+        assert!(game.needs_check(time("2025-10-26T23:10:00Z"), window));
+
+        state.track(2025020140, "FINAL", time("2025-10-27T01:30:00Z"));
+        // going from FINAL to OFF doesn't restart the window
+        state.track(2025020140, "OFF", time("2025-10-27T02:00:00Z"));
+        let game = state.games.get_mut(&2025020140).unwrap();
+        assert_eq!(game.final_at.as_deref(), Some("2025-10-27T01:30:00+00:00"));
+        assert!(game.needs_check(time("2025-10-28T01:00:00Z"), window));
+        assert!(!game.needs_check(time("2025-10-28T01:31:00Z"), window));
+        assert!(game.done);
+        assert!(!game.needs_check(time("2025-10-28T01:00:00Z"), window));
+    }
+
+    #[test]
+    fn goals_to_download_skips_saved() {
+        let mut game = WatchedGame::new("LIVE");
+        game.saved_goals.insert(12);
+        let game_data = GameExportData {
+            goals: vec![goal(12, Some("https://wsr.nhle.com/12")), goal(13, None), goal(14, Some("https://wsr.nhle.com/14"))],
+            home_team_id: 1,
+        };
+        let ids: Vec<u32> = game.goals_to_download(&game_data).iter().map(|g| g.event_id).collect();
+        assert_eq!(ids, vec![14]);
+    }
+
+    #[test]
+    fn remove_stale_goals_after_correction() {
+        let mut game = WatchedGame::new("FINAL");
+        game.saved_goals.extend([12, 14]);
+        game.game_data = Some(GameExportData {
+            goals: vec![goal(12, Some("https://wsr.nhle.com/12")), goal(13, None), goal(14, Some("https://wsr.nhle.com/14"))],
+            home_team_id: 1,
+        });
+        // 14 was saved and 13 never had a replay, but both were taken away
+        let game_data = GameExportData { goals: vec![goal(12, Some("https://wsr.nhle.com/12"))], home_team_id: 1 };
+        assert_eq!(game.remove_stale_goals(&game_data), vec![13, 14]);
+        assert_eq!(game.saved_goals, BTreeSet::from([12]));
+    }
+}
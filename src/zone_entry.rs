@@ -0,0 +1,242 @@
+use serde::Serialize;
+
+use crate::normalize::BLUE_LINE_X_FT;
+use crate::possession::{find_possessions, Possession};
+use crate::tracking::Frame;
+
+/// How long after the puck crosses the blue line (in seconds) a teammate can
+/// pick it up for the entry to count as a pass rather than a dump-in
+pub const PASS_ENTRY_SECONDS: f64 = 1.5;
+
+/// How the scoring team got the puck into the offensive zone
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum EntryType {
+    /// a player skated the puck over the blue line
+    CarryIn,
+    /// the puck was sent in loose and had to be chased down
+    DumpIn,
+    /// the puck was passed over the blue line to a teammate
+    PassEntry,
+    /// the other team had the puck in its own zone and lost it
+    Turnover,
+}
+
+/// The zone entry leading to a goal
+#[derive(Debug, Clone, PartialEq)]
+pub struct ZoneEntry {
+    pub entry_type: EntryType,
+    /// first frame with the puck over the blue line, or for a turnover the
+    /// first frame the scoring team had the puck back
+    pub frame: usize,
+    pub seconds: f64,
+    /// player id of the player who carried, passed or dumped the puck in, or
+    /// who took it away for a turnover
+    pub player_id: Option<u32>,
+}
+
+/// Classifies how the scoring team entered the offensive zone before a goal
+/// The frames need to be normalized (see `normalize_frames`): the direction
+/// the scoring team attacks comes from `home_team_defending_side`, so its
+/// offensive blue line is always the one at positive x.  The entry is the
+/// last time the puck crossed that blue line before `end_frame` (usually the
+/// shot release).  If one of the other team's skaters picked the puck up in
+/// the zone after that, the goal came off a turnover instead; the goalie
+/// (from `goalie_ids`, the roster's player ids) having it, as on a save and
+/// rebound, doesn't count.  Returns None when the clip starts with the puck
+/// already in the zone and no turnover, or when who sent the puck in is
+/// unknown.
+pub fn classify_zone_entry(
+    frames: &[Frame],
+    scoring_team_id: u16,
+    goalie_ids: &[u32],
+    end_frame: usize,
+) -> Option<ZoneEntry> {
+    let first = frames.first()?;
+    let end = end_frame.min(frames.len() - 1);
+    let possessions: Vec<Possession> = find_possessions(&frames[..=end]);
+    let is_scoring = |p: &Possession| p.team_id == Some(scoring_team_id);
+
+    let crossing = last_blue_line_crossing(&frames[..=end]);
+
+    // one of the other team's skaters getting the puck in the zone after the
+    // entry, followed by the scoring team getting it back
+    let in_zone = |p: &Possession| frames[p.start_frame].puck().is_some_and(|puck| puck.x > BLUE_LINE_X_FT);
+    let turnover = possessions
+        .iter()
+        .rposition(|p| {
+            !is_scoring(p)
+                && !goalie_ids.contains(&p.player_id)
+                && crossing.is_none_or(|crossing| p.start_frame > crossing)
+                && in_zone(p)
+        })
+        .and_then(|i| possessions[i + 1..].iter().find(|p| is_scoring(p)));
+    if let Some(p) = turnover {
+        return Some(ZoneEntry {
+            entry_type: EntryType::Turnover,
+            frame: p.start_frame,
+            seconds: frames[p.start_frame].seconds_since(first),
+            player_id: Some(p.player_id),
+        });
+    }
+
+    let crossing = crossing?;
+    let entry = |entry_type, player_id| ZoneEntry {
+        entry_type,
+        frame: crossing,
+        seconds: frames[crossing].seconds_since(first),
+        player_id,
+    };
+
+    // carried over if someone on the scoring team had the puck as it went
+    // over the line
+    if let Some(p) = possessions
+        .iter()
+        .find(|p| is_scoring(p) && p.start_frame <= crossing && p.end_frame >= crossing)
+    {
+        return Some(entry(EntryType::CarryIn, Some(p.player_id)));
+    }
+
+    let sender = possessions.iter().rev().find(|p| p.end_frame < crossing)?;
+    if !is_scoring(sender) {
+        return None;
+    }
+    let picked_up_by_teammate = possessions
+        .iter()
+        .find(|p| p.start_frame >= crossing)
+        .is_some_and(|p| {
+            is_scoring(p)
+                && p.player_id != sender.player_id
+                && frames[p.start_frame].seconds_since(&frames[crossing]) <= PASS_ENTRY_SECONDS
+        });
+    if picked_up_by_teammate {
+        Some(entry(EntryType::PassEntry, Some(sender.player_id)))
+    } else {
+        Some(entry(EntryType::DumpIn, Some(sender.player_id)))
+    }
+}
+
+/// The first frame of the last time the puck went over the offensive blue
+/// line, skipping frames where the puck isn't tracked
+fn last_blue_line_crossing(frames: &[Frame]) -> Option<usize> {
+    let mut crossing = None;
+    let mut previous_x: Option<f64> = None;
+    for (i, frame) in frames.iter().enumerate() {
+        let x = match frame.puck() {
+            Some(puck) => puck.x,
+            None => continue,
+        };
+        if previous_x.is_some_and(|previous_x| previous_x <= BLUE_LINE_X_FT) && x > BLUE_LINE_X_FT {
+            crossing = Some(i);
+        }
+        previous_x = Some(x);
+    }
+    crossing
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::test_util::{entity, player};
+
+    /// Frames with the puck at the given spots, player 10 (scoring team 22)
+    /// skating from x = 15 to 35 over frames 0 to 10, teammate 20 waiting at
+    /// (45, 20), and an opponent 3 (player id 8479361) waiting at (70, -30)
+    fn frames(puck: impl Fn(usize) -> (f64, f64)) -> Vec<Frame> {
+        (0..20)
+            .map(|i| {
+                let skater_x = 15.0 + 2.0 * i.min(10) as f64;
+                let (puck_x, puck_y) = puck(i);
+                Frame {
+                    time_stamp: i as f64,
+                    entities: vec![
+                        entity(1, None, puck_x, puck_y),
+                        entity(10, Some(22), skater_x, 0.0),
+                        entity(20, Some(22), 45.0, 20.0),
+                        player(3, 8479361, Some(15), 70.0, -30.0),
+                    ],
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn classify_zone_entry_carry_in() {
+        let frames = frames(|i| (16.0 + 2.0 * i.min(10) as f64, 0.0));
+        let entry = classify_zone_entry(&frames, 22, &[], 19).unwrap();
+        assert_eq!(entry.entry_type, EntryType::CarryIn);
+        // the puck goes from 24 to 26 ft between frames 4 and 5
+        assert_eq!(entry.frame, 5);
+        assert_eq!(entry.player_id, Some(10));
+    }
+
+    #[test]
+    fn classify_zone_entry_pass_and_dump() {
+        // 10 passes from (20, 0) to 20 at (45, 20), arriving at frame 7
+        let pass = |i: usize| match i {
+            0..=2 => (16.0 + 2.0 * i as f64, 0.0),
+            3..=7 => (20.0 + 6.25 * (i - 3) as f64, 5.0 * (i - 3) as f64),
+            _ => (45.0, 20.0),
+        };
+        let entry = classify_zone_entry(&frames(pass), 22, &[], 19).unwrap();
+        assert_eq!(entry.entry_type, EntryType::PassEntry);
+        assert_eq!(entry.player_id, Some(10));
+
+        // the same pass, going into the corner with no one there
+        let dump = |i: usize| match i {
+            0..=2 => (16.0 + 2.0 * i as f64, 0.0),
+            _ => (20.0 + 6.25 * (i - 3) as f64, -5.0 * (i - 3) as f64),
+        };
+        let entry = classify_zone_entry(&frames(dump), 22, &[], 19).unwrap();
+        assert_eq!(entry.entry_type, EntryType::DumpIn);
+    }
+
+    #[test]
+    fn classify_zone_entry_turnover() {
+        // carried in, lost to 3, then picked up by 20
+        let turnover = |i: usize| match i {
+            0..=9 => (16.0 + 2.0 * i as f64, 0.0),
+            10..=13 => (70.0, -29.0),
+            _ => (45.0, 19.0),
+        };
+        let entry = classify_zone_entry(&frames(turnover), 22, &[], 19).unwrap();
+        assert_eq!(entry.entry_type, EntryType::Turnover);
+        assert_eq!(entry.frame, 14);
+        assert_eq!(entry.player_id, Some(20));
+    }
+
+    #[test]
+    fn classify_zone_entry_save_and_rebound() {
+        // carried in, saved by goalie 3, and the rebound picked up by 20
+        let rebound = |i: usize| match i {
+            0..=9 => (16.0 + 2.0 * i as f64, 0.0),
+            10..=13 => (70.0, -29.0),
+            _ => (45.0, 19.0),
+        };
+        let entry = classify_zone_entry(&frames(rebound), 22, &[8479361], 19).unwrap();
+        assert_eq!(entry.entry_type, EntryType::CarryIn);
+        assert_eq!(entry.player_id, Some(10));
+    }
+
+    #[test]
+    fn classify_zone_entry_failed_entry() {
+        // 3 steps up at the blue line and has the puck as it goes over, then
+        // 10 picks it back up in the zone
+        let poked = |i: usize| match i {
+            0..=2 => (16.0 + 2.0 * i as f64, 0.0),
+            3..=6 => (23.0 + 1.5 * (i - 3) as f64, -2.0),
+            _ => (16.0 + 2.0 * i.min(10) as f64, 0.0),
+        };
+        let mut frames = frames(poked);
+        for frame in &mut frames[3..=6] {
+            let puck_x = frame.entities[0].x;
+            frame.entities[3].x = puck_x;
+            frame.entities[3].y = -2.5;
+        }
+        let entry = classify_zone_entry(&frames, 22, &[], 19).unwrap();
+        assert_eq!(entry.entry_type, EntryType::DumpIn);
+        assert_eq!(entry.frame, 5);
+        assert_eq!(entry.player_id, Some(10));
+    }
+}